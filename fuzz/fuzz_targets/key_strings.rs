@@ -0,0 +1,24 @@
+#![no_main]
+use i3_conf_searcher::i3_config::{truncate_elements, ConfigMetadata, Modifiers};
+use libfuzzer_sys::fuzz_target;
+
+// Keys reach the tokenizer through the keys column of an annotation and through bindsym
+fuzz_target!(|keys: &str| {
+    let keys = keys.replace(['\n', '\r'], " ");
+    let text = format!(
+        "set $mod Mod4\n## group // description // {} ##\nbindsym {} exec true",
+        keys, keys
+    );
+    let mut config = match ConfigMetadata::parse(&text) {
+        Ok(config) => config,
+        Err(_) => return,
+    };
+    for held in 0..16 {
+        let modifiers = Modifiers::new(held & 1 != 0, held & 2 != 0, held & 4 != 0, held & 8 != 0);
+        for entry in config.filter(&keys, &modifiers) {
+            entry.sequence().remaining(&modifiers);
+            truncate_elements(entry.matched_description(), keys.len() % 40);
+            truncate_elements(entry.matched_group(), keys.len() % 40);
+        }
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+use i3_conf_searcher::i3_config::{ConfigMetadata, Modifiers};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    if let Ok(mut config) = ConfigMetadata::parse(&text) {
+        let entries: Vec<_> = config.entries().to_vec();
+        for entry in &entries {
+            config.excerpt(entry);
+        }
+        config.filter("", &Modifiers::default());
+    }
+});
@@ -0,0 +1,93 @@
+#ifndef I3_CONF_SEARCHER_H
+#define I3_CONF_SEARCHER_H
+
+/* Generated with cbindgen from src/ffi.rs, don't edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define I3CS_MODIFIER_SHIFT 1
+
+#define I3CS_MODIFIER_CONTROL 2
+
+#define I3CS_MODIFIER_ALT 4
+
+#define I3CS_MODIFIER_SUPER 8
+
+typedef struct I3csEntry I3csEntry;
+
+// Parsed config with the results of the last filter
+typedef struct I3csConfig I3csConfig;
+
+// Parses the annotations of a config.
+// Returns NULL if it can't be parsed, with the exit code of the error written to `error`
+//
+// # Safety
+// `text` has to be a NUL terminated UTF-8 string, `error` NULL or valid for writes
+I3csConfig *i3cs_parse_config(const char *text, int32_t *error);
+
+// # Safety
+// `config` has to come from `i3cs_parse_config` and not be used afterwards
+void i3cs_config_free(I3csConfig *config);
+
+// # Safety
+// `config` has to come from `i3cs_parse_config`
+size_t i3cs_config_entry_count(const I3csConfig *config);
+
+// Entry in config order, NULL if `index` is out of range
+//
+// # Safety
+// `config` has to come from `i3cs_parse_config`
+const I3csEntry *i3cs_config_entry(const I3csConfig *config, size_t index);
+
+// Fuzzy matches `query` against the entries using the held `I3CS_MODIFIER_*` flags.
+// Returns the number of results, read them with `i3cs_filter_result`
+//
+// # Safety
+// `config` has to come from `i3cs_parse_config`, `query` has to be a NUL terminated UTF-8 string
+size_t i3cs_filter(I3csConfig *config, const char *query, uint8_t modifiers);
+
+// Result of the last `i3cs_filter`, best match first. NULL if `index` is out of range
+//
+// # Safety
+// `config` has to come from `i3cs_parse_config`
+const I3csEntry *i3cs_filter_result(const I3csConfig *config, size_t index);
+
+// # Safety
+// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+char *i3cs_entry_group(const I3csEntry *entry);
+
+// # Safety
+// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+char *i3cs_entry_description(const I3csEntry *entry);
+
+// # Safety
+// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+char *i3cs_entry_keys(const I3csEntry *entry);
+
+// Command of the binding following the annotation, NULL if there is none
+//
+// # Safety
+// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+char *i3cs_entry_command(const I3csEntry *entry);
+
+// Mode of the binding, NULL for the default mode
+//
+// # Safety
+// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+char *i3cs_entry_mode(const I3csEntry *entry);
+
+// Line of the annotation, starting at 1
+//
+// # Safety
+// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+size_t i3cs_entry_line(const I3csEntry *entry);
+
+// # Safety
+// `string` has to come from this library and not be used afterwards
+void i3cs_string_free(char *string);
+
+#endif /* I3_CONF_SEARCHER_H */
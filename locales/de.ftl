@@ -0,0 +1,118 @@
+stage-connecting = Verbinde mit i3
+stage-fetching = Lade Konfiguration
+stage-downloading = Lade Konfiguration herunter
+stage-syncing = Synchronisiere Git-Repository
+stage-reading = Lese Konfigurationsdatei
+stage-parsing = Verarbeite Konfiguration
+stage-importing = Importiere Tastenkürzel
+
+loading-failed = Fehler beim Laden der i3-Konfiguration
+unsupported-platform = i3 läuft nur unter Linux
+search-placeholder = Suchbegriff eingeben...
+no-modifiers = Keine Modifikatortasten gedrückt...
+no-matches = Keine passenden Einträge
+weak-matches = { $count ->
+    [one] { $count } schwacher Treffer ausgeblendet
+   *[other] { $count } schwache Treffer ausgeblendet
+}
+source = Quelle: { $source }
+quick-run = Ausführung in { $seconds }s, Escape zum Abbrechen
+config-warnings = { $count ->
+    [one] { $count } Konfigurationswarnung
+   *[other] { $count } Konfigurationswarnungen
+}
+
+chip-mode = Modus: { $mode }
+chip-group = Gruppe: { $group } ×
+chip-exact-modifiers = Exakte Modifikatoren ×
+chip-capture = Tastenkombination drücken… ×
+chip-keys = Tasten: { $keys } ×
+all-groups = Alle ({ $count })
+
+run-failed = Tastenkürzel konnte nicht ausgeführt werden: { $error }
+run-failed-notification = { $description } konnte nicht ausgeführt werden
+dismiss = Schließen
+save = Speichern
+cancel = Abbrechen
+edit-annotation = Annotation in Zeile { $line } bearbeiten
+new-binding = Neues Tastenkürzel
+
+copied-keys = Tasten kopiert
+copied-command = Befehl kopiert
+no-command = Eintrag hat keinen Befehl
+entry-hidden = Ausgeblendet, `state unhide` blendet es wieder ein
+not-local = Die Konfiguration stammt nicht aus einer lokalen Datei
+not-in-config = Der Eintrag stammt nicht aus der Konfigurationsdatei
+editor-failed = Editor konnte nicht geöffnet werden: { $error }
+imported-not-editable = Importierte Einträge können nicht bearbeitet werden
+annotation-saved = Annotation gespeichert
+binding-added = Tastenkürzel hinzugefügt
+config-reloaded = i3-Konfiguration neu geladen
+
+help-controls = Steuerung
+help-colors = Farben
+help-mode = Tastenkürzel des Modus { $mode }
+
+action-run = Befehl über i3 ausführen
+action-preview = Vorschau der Auswirkungen
+action-copy-keys = Tasten kopieren
+action-copy-command = Befehl kopieren
+action-open-in-editor = Im Editor öffnen
+action-hide = Ausblenden
+
+field-group = Gruppe
+field-description = Beschreibung
+field-keys = Tasten, z. B. $mod+Shift+b
+field-command = Befehl, z. B. exec firefox
+keys-conflict = { $keys } ist schon mit { $bindings } belegt. Zum Hinzufügen erneut speichern
+stage-failed = Fehler bei „{ $stage }“: { $error }
+
+detail-description = Beschreibung: { $description }
+detail-mode = Modus: { $mode }
+detail-command = Befehl:
+detail-no-command = keiner
+detail-keys = Tasten: { $keys }
+detail-flags = Optionen: { $flags }
+detail-target = Ziel: { $target }
+detail-target-open = Ziel: { $target } (geöffnet)
+detail-target-not-connected = Ziel: { $target } (nicht angeschlossen)
+detail-target-not-open = Ziel: { $target } (nicht geöffnet)
+output-missing = Ausgabe fehlt
+
+control-toggle-help = Diese Hilfe ein- oder ausblenden
+control-select-next = Nächsten Eintrag auswählen
+control-select-previous = Vorherigen Eintrag auswählen
+control-toggle-details = Details ein- oder ausblenden
+control-show-details = Details einblenden
+control-hide-details = Details ausblenden
+control-toggle-groups = Gruppenleiste ein- oder ausblenden
+control-edit-annotation = Gruppe und Beschreibung bearbeiten
+control-new-binding = Neues Tastenkürzel hinzufügen
+control-open-in-editor = Konfiguration im Editor öffnen
+control-reload-i3 = i3-Konfiguration neu laden
+control-cycle-density = Zwischen kompakten, bequemen und großzügigen Zeilen wechseln
+control-capture-keys = Tastenkombination drücken, um ihre Belegung zu finden
+control-run = Ausgewählten Eintrag ausführen
+control-actions = Aktionen des ausgewählten Eintrags, auch per Rechtsklick
+control-search-group = Nur eine Gruppe durchsuchen, Strg+0 durchsucht alle
+control-close = Menüs, Hilfe oder den Sucher schließen
+token-criteria = Kriterien
+token-command = Befehl
+token-separator = Trennzeichen verketteter Befehle
+
+preview-then = { $before }, dann { $after }
+preview-starts = startet { $program }
+preview-switches = wechselt von Arbeitsfläche { $from } zu { $to }
+preview-switches-to = wechselt zu Arbeitsfläche { $to }
+preview-to-scratchpad = verschiebt { $subject } ins Scratchpad
+preview-moves = verschiebt { $subject } { $target }
+preview-closes = schließt { $subject }
+preview-focuses = fokussiert { $subject }
+preview-moves-focus = bewegt den Fokus { $direction }
+preview-enters-mode = wechselt in den Modus { $mode }
+preview-changes = { $command } für { $subject }
+preview-runs = führt `{ $command }` aus
+preview-window = „{ $title }“
+preview-focused-window = das fokussierte Fenster
+preview-no-window = kein Fenster passend zu { $criteria }
+preview-windows = { $count } Fenster passend zu { $criteria }
@@ -0,0 +1,123 @@
+# Strings of the searcher window, translations go next to this file as <language>.ftl
+
+stage-connecting = Connecting to i3
+stage-fetching = Fetching config
+stage-downloading = Downloading config
+stage-syncing = Syncing git repository
+stage-reading = Reading config file
+stage-parsing = Parsing config
+stage-importing = Importing bindings
+
+loading-failed = Error loading i3 config
+unsupported-platform = i3 only works on Linux
+search-placeholder = Enter search here...
+no-modifiers = No modifiers pressed...
+no-matches = No matching entries
+weak-matches = { $count ->
+    [one] { $count } weak match hidden
+   *[other] { $count } weak matches hidden
+}
+source = Source: { $source }
+quick-run = Running in { $seconds }s, Escape to cancel
+config-warnings = { $count ->
+    [one] { $count } config warning
+   *[other] { $count } config warnings
+}
+
+chip-mode = Mode: { $mode }
+chip-group = Group: { $group } ×
+chip-exact-modifiers = Exact modifiers ×
+chip-capture = Press a key combination… ×
+chip-keys = Keys: { $keys } ×
+all-groups = All ({ $count })
+
+run-failed = Failed to run binding: { $error }
+run-failed-notification = Failed to run { $description }
+dismiss = Dismiss
+save = Save
+cancel = Cancel
+edit-annotation = Edit annotation on line { $line }
+new-binding = New binding
+
+copied-keys = Copied keys
+copied-command = Copied command
+no-command = Entry has no bound command
+entry-hidden = Hidden, `state unhide` shows it again
+not-local = Config wasn't loaded from a local file
+not-in-config = Entry wasn't loaded from the config file
+editor-failed = Failed to open editor: { $error }
+imported-not-editable = Imported entries can't be edited
+annotation-saved = Annotation saved
+binding-added = Binding added
+config-reloaded = i3 config reloaded
+
+help-controls = Controls
+help-colors = Colors
+help-mode = Bindings of mode { $mode }
+
+action-run = Run command through i3
+action-preview = Preview what it would do
+action-copy-keys = Copy keys
+action-copy-command = Copy command
+action-open-in-editor = Open in editor
+action-hide = Hide
+
+field-group = Group
+field-description = Description
+field-keys = Keys, e.g. $mod+Shift+b
+field-command = Command, e.g. exec firefox
+keys-conflict = { $keys } is already bound to { $bindings }. Save again to add it anyway
+stage-failed = { $stage } failed: { $error }
+
+detail-description = Description: { $description }
+detail-mode = Mode: { $mode }
+detail-command = Command:
+detail-no-command = none
+detail-keys = Keys: { $keys }
+detail-flags = Flags: { $flags }
+detail-target = Target: { $target }
+detail-target-open = Target: { $target } (open)
+detail-target-not-connected = Target: { $target } (not connected)
+detail-target-not-open = Target: { $target } (not open)
+output-missing = output missing
+
+control-toggle-help = Show or hide this help
+control-select-next = Select next entry
+control-select-previous = Select previous entry
+control-toggle-details = Show or hide details
+control-show-details = Show details
+control-hide-details = Hide details
+control-toggle-groups = Show or hide the group sidebar
+control-edit-annotation = Edit group and description
+control-new-binding = Add a new binding
+control-open-in-editor = Open the config in the editor
+control-reload-i3 = Reload the i3 config
+control-cycle-density = Switch between compact, comfortable and spacious rows
+control-capture-keys = Press a key combination to find what it's bound to
+control-run = Run the selected entry
+control-actions = Actions of the selected entry, also a right click
+control-search-group = Only search a group, Ctrl+0 searches all
+control-close = Close menus, help or the searcher
+token-criteria = Criteria
+token-command = Command
+token-separator = Separator of chained commands
+
+preview-then = { $before }, then { $after }
+preview-starts = starts { $program }
+preview-switches = switches from workspace { $from } to { $to }
+preview-switches-to = switches to workspace { $to }
+preview-to-scratchpad = moves { $subject } to the scratchpad
+preview-moves = moves { $subject } { $target }
+preview-closes = closes { $subject }
+preview-focuses = focuses { $subject }
+preview-moves-focus = moves focus { $direction }
+preview-enters-mode = enters mode { $mode }
+preview-changes = { $command } on { $subject }
+preview-runs = runs `{ $command }`
+preview-window = "{ $title }"
+preview-focused-window = the focused window
+preview-no-window = no window matching { $criteria }
+preview-windows = { $count ->
+    [one] { $count } window matching { $criteria }
+   *[other] { $count } windows matching { $criteria }
+}
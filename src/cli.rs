@@ -0,0 +1,618 @@
+#[cfg(feature = "gui")]
+use crate::style::Theme;
+#[cfg(feature = "tray")]
+use crate::tray::TrayAction;
+use clap::Clap;
+#[cfg(feature = "gui")]
+use futures::stream::LocalBoxStream;
+#[cfg(feature = "gui")]
+use futures::StreamExt;
+#[cfg(feature = "gui")]
+use i3_conf_searcher::mode_events::{self, ConfigChange};
+use i3_conf_searcher::{
+    config_writer,
+    control::{self, Request, Response},
+    doctor, executor,
+    hidden::HiddenEntries,
+    i3_config::{ConfigEntry, ConfigMetadata, I3ConfigError, Modifiers},
+    loader::{self, SourceOptions},
+    preferences::Preferences,
+    presenter,
+    state::AppState,
+    usage::UsageStats,
+};
+use std::io::{IsTerminal, Read, Write};
+use std::path::PathBuf;
+#[cfg(feature = "gui")]
+use std::process::Child;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+#[derive(Clap)]
+pub enum SubCommand {
+    /// Execute the best matching binding without opening the GUI
+    Run(RunArgs),
+    /// Print the best matching entries
+    Query(QueryArgs),
+    /// Print all groups with number of entries
+    ListGroups,
+    /// Print entries in aligned columns
+    List(ListArgs),
+    /// Restore the config as it was before the last change made by the searcher
+    UndoLastEdit,
+    /// Keep running and pop up the bindings of every i3 mode while it is active
+    #[cfg(feature = "gui")]
+    WhichKey,
+    /// Check that i3, the config, the display and the font are usable
+    Doctor,
+    /// Print statistics about the config
+    Stats(StatsArgs),
+    /// Move what the searcher remembers between machines
+    State(StateArgs),
+    /// Send a command to the running which-key
+    Ctl(CtlArgs),
+}
+
+#[derive(Clap)]
+pub struct RunArgs {
+    /// Search query used to find the binding
+    query: String,
+    /// Run the best match even if other entries match equally well
+    #[clap(long)]
+    first: bool,
+}
+
+#[derive(Clap)]
+pub struct QueryArgs {
+    /// Search query used to find the bindings
+    query: String,
+    /// Number of matches to print
+    #[clap(short = 'n', long, default_value = "10")]
+    limit: usize,
+    /// Print the matches as a JSON array
+    #[clap(long)]
+    json: bool,
+}
+
+#[derive(Clap)]
+pub struct ListArgs {
+    /// Only print entries of this group
+    #[clap(short, long)]
+    group: Option<String>,
+}
+
+#[derive(Clap)]
+pub struct StatsArgs {
+    /// Print how often each entry was run from the searcher, needs usage-stats in the preferences
+    #[clap(long)]
+    usage: bool,
+}
+
+#[derive(Clap)]
+pub struct StateArgs {
+    #[clap(subcommand)]
+    command: StateCommand,
+}
+
+#[derive(Clap)]
+pub enum StateCommand {
+    /// Print the state as JSON
+    Export,
+    /// Replace the state with an exported one
+    Import(StateImportArgs),
+    /// Show all entries hidden from the searcher again
+    Unhide,
+}
+
+#[derive(Clap)]
+pub struct CtlArgs {
+    #[clap(subcommand)]
+    command: CtlCommand,
+}
+
+#[derive(Clap)]
+pub enum CtlCommand {
+    /// Open the searcher
+    Show,
+    /// Close the searcher opened through which-key
+    Hide,
+    /// Open the searcher or close it when it is open
+    Toggle,
+    /// Load the config again
+    Reload,
+    /// Print the best matches
+    Query(CtlQueryArgs),
+}
+
+#[derive(Clap)]
+pub struct CtlQueryArgs {
+    text: String,
+    /// Number of matches to print
+    #[clap(long, default_value = "10")]
+    limit: usize,
+}
+
+#[derive(Clap)]
+pub struct StateImportArgs {
+    /// Exported state, read from stdin if missing
+    path: Option<PathBuf>,
+}
+
+const DEFAULT_PICKER: &str = "fzf";
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Runtime::new().expect("Failed to start tokio runtime")
+}
+
+/// Loads the config and reports annotations that couldn't be parsed
+pub fn check(options: SourceOptions) -> Result<(), I3ConfigError> {
+    runtime().block_on(async move {
+        let config = loader::load(options).await?;
+        for diagnostic in config.diagnostics() {
+            println!("{}", diagnostic);
+        }
+        println!(
+            "{} entries, {} problems",
+            config.entries().len(),
+            config.diagnostics().len()
+        );
+        if config.diagnostics().is_empty() {
+            Ok(())
+        } else {
+            Err(I3ConfigError::InvalidAnnotations(
+                config.diagnostics().len(),
+            ))
+        }
+    })
+}
+
+/// Pipes the entries into a terminal fuzzy finder and runs the picked one.
+/// Nothing is run when the picker is cancelled
+pub fn pick(options: SourceOptions, preferences: &Preferences) -> Result<(), I3ConfigError> {
+    let picker = preferences
+        .picker
+        .clone()
+        .unwrap_or_else(|| String::from(DEFAULT_PICKER));
+    let order = preferences.order;
+    let stay_in_mode = preferences.stay_in_mode;
+    runtime().block_on(async move {
+        let mut config = loader::load(options).await?;
+        config.set_order(order);
+        let entries = config.ordered_entries();
+        let spawn_error = |error| I3ConfigError::FailedSpawn(Arc::new(error));
+        let mut words = picker.split_whitespace();
+        let program = words.next().unwrap_or(DEFAULT_PICKER);
+        // fzf and sk both hide the index column with these
+        let mut child = Command::new(program)
+            .args(words)
+            .args(["--delimiter", "\t", "--with-nth", "2.."])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // The picker may be closed before it read every entry
+            let _ = stdin.write_all(presenter::picker_lines(&entries).as_bytes());
+        }
+        let output = child.wait_with_output().map_err(spawn_error)?;
+        let picked = String::from_utf8_lossy(&output.stdout)
+            .split('\t')
+            .next()
+            .and_then(|index| index.trim().parse::<usize>().ok())
+            .and_then(|index| entries.get(index).copied());
+        match picked {
+            Some(entry) => executor::execute(entry, stay_in_mode).await,
+            None => Ok(()),
+        }
+    })
+}
+
+pub fn run(
+    command: SubCommand,
+    options: SourceOptions,
+    preferences: &Preferences,
+    #[cfg(feature = "gui")] theme: Theme,
+) -> Result<(), I3ConfigError> {
+    let order = preferences.order;
+    let min_score = preferences.min_score;
+    let matcher = preferences.matcher;
+    let search_text = preferences.search_fields.clone();
+    let usage_stats = preferences.usage_stats;
+    let stay_in_mode = preferences.stay_in_mode;
+    #[cfg(feature = "gui")]
+    let hotkey = preferences
+        .hotkey
+        .as_deref()
+        .map(str::parse::<crate::hotkey::Hotkey>)
+        .transpose()?;
+    #[cfg(feature = "gui")]
+    let tray = preferences.tray;
+    if let SubCommand::UndoLastEdit = command {
+        let path = config_writer::undo_last_edit()?;
+        println!("Restored {}", path.display());
+        return Ok(());
+    }
+    if let SubCommand::State(args) = command {
+        return run_state(args.command);
+    }
+    if let SubCommand::Ctl(args) = command {
+        return run_ctl(args.command);
+    }
+    if let SubCommand::Doctor = command {
+        return runtime().block_on(run_doctor(options));
+    }
+    runtime().block_on(async move {
+        let mut config = loader::load(options.clone()).await?;
+        config.set_order(order);
+        config.set_min_score(min_score);
+        config.set_matcher(matcher);
+        config.set_search_text(search_text);
+        match command {
+            SubCommand::Run(args) => {
+                let entry = config.best_match(&args.query, args.first)?;
+                executor::execute(entry, stay_in_mode).await
+            }
+            SubCommand::Query(args) => {
+                let matches = config.filter(&args.query, &Modifiers::default());
+                let matches = &matches[..args.limit.min(matches.len())];
+                if args.json {
+                    println!("{}", presenter::entries_json(matches));
+                } else {
+                    print!("{}", presenter::entries_table(matches));
+                }
+                Ok(())
+            }
+            SubCommand::ListGroups => {
+                print!("{}", presenter::groups_table(&config.groups()));
+                Ok(())
+            }
+            SubCommand::List(args) => {
+                let entries = match args.group {
+                    Some(group) => config.entries_in_group(&group),
+                    None => config.ordered_entries(),
+                };
+                print!("{}", presenter::entries_table(&entries));
+                Ok(())
+            }
+            SubCommand::Stats(args) => {
+                if args.usage {
+                    print_usage(&config, usage_stats)
+                } else {
+                    println!(
+                        "{} entries in {} groups, {} problems",
+                        config.entries().len(),
+                        config.groups().len(),
+                        config.diagnostics().len()
+                    );
+                    Ok(())
+                }
+            }
+            #[cfg(feature = "gui")]
+            SubCommand::WhichKey => which_key(config, &options, hotkey, tray, theme).await,
+            SubCommand::UndoLastEdit
+            | SubCommand::Doctor
+            | SubCommand::State(_)
+            | SubCommand::Ctl(_) => {
+                unreachable!("handled before loading the config")
+            }
+        }
+    })
+}
+
+fn run_state(command: StateCommand) -> Result<(), I3ConfigError> {
+    match command {
+        StateCommand::Export => {
+            println!("{}", AppState::load()?.to_json());
+            Ok(())
+        }
+        StateCommand::Import(args) => {
+            let read_error = |error| I3ConfigError::FailedConfigRead(Arc::new(error));
+            let text = match args.path {
+                Some(path) => std::fs::read_to_string(path).map_err(read_error)?,
+                None => {
+                    let mut text = String::new();
+                    std::io::stdin()
+                        .read_to_string(&mut text)
+                        .map_err(read_error)?;
+                    text
+                }
+            };
+            AppState::from_json(&text)?.save()
+        }
+        StateCommand::Unhide => {
+            println!("{} entries shown again", HiddenEntries::clear()?);
+            Ok(())
+        }
+    }
+}
+
+fn run_ctl(command: CtlCommand) -> Result<(), I3ConfigError> {
+    let request = match command {
+        CtlCommand::Show => Request::Show,
+        CtlCommand::Hide => Request::Hide,
+        CtlCommand::Toggle => Request::Toggle,
+        CtlCommand::Reload => Request::Reload,
+        CtlCommand::Query(args) => Request::Query {
+            text: args.text,
+            limit: args.limit,
+        },
+    };
+    if let Response::Matches { matches } = control::send(&request)? {
+        let entries: Vec<ConfigEntry> = matches
+            .into_iter()
+            .map(|found| ConfigEntry::new(found.group, found.description, found.keys))
+            .collect();
+        print!(
+            "{}",
+            presenter::entries_table(&entries.iter().collect::<Vec<_>>())
+        );
+    }
+    Ok(())
+}
+
+fn print_usage(config: &ConfigMetadata, enabled: bool) -> Result<(), I3ConfigError> {
+    if !enabled {
+        eprintln!("Usage stats are off, set usage-stats = true in the preferences to record them");
+    }
+    let stats = match UsageStats::path() {
+        Some(path) => UsageStats::load(&path)?,
+        None => UsageStats::default(),
+    };
+    print!(
+        "{}",
+        presenter::usage_table(&config.ordered_entries(), &stats)
+    );
+    Ok(())
+}
+
+/// Prints the result of every check, fails if any of them failed
+async fn run_doctor(options: SourceOptions) -> Result<(), I3ConfigError> {
+    #[cfg(feature = "gui")]
+    let font = Some(crate::gui::font_bytes());
+    #[cfg(not(feature = "gui"))]
+    let font = None;
+    let checks = doctor::run_checks(options, font).await;
+    let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    print!("{}", presenter::doctor_report(&checks, color));
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == doctor::Status::Failed)
+        .count();
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(I3ConfigError::FailedChecks(failed))
+    }
+}
+
+#[cfg(feature = "gui")]
+enum WhichKeyEvent {
+    Mode(String),
+    Config(ConfigChange),
+    Hotkey,
+    Control(control::Incoming),
+    #[cfg(feature = "tray")]
+    Tray(TrayAction),
+}
+
+/// Opens a searcher limited to the active mode whenever i3 enters a mode with annotated bindings
+/// and closes it once the mode is left. The config is loaded again when i3 reloads it.
+/// Pressing the hotkey opens the full searcher or closes it when it is still open,
+/// scripts do the same through the control socket
+#[cfg(feature = "gui")]
+async fn which_key(
+    mut config: ConfigMetadata,
+    options: &SourceOptions,
+    hotkey: Option<crate::hotkey::Hotkey>,
+    tray: bool,
+    // Only the tray switches themes
+    #[cfg_attr(not(feature = "tray"), allow(unused_mut))] mut theme: Theme,
+) -> Result<(), I3ConfigError> {
+    let modes = mode_events::mode_changes()
+        .await?
+        .map(|mode| mode.map(WhichKeyEvent::Mode));
+    let config_changes = mode_events::config_changes()
+        .await?
+        .map(|change| change.map(WhichKeyEvent::Config));
+    let presses = match hotkey {
+        Some(hotkey) => crate::hotkey::listen(hotkey)?
+            .map(|_| Ok(WhichKeyEvent::Hotkey))
+            .boxed_local(),
+        None => futures::stream::empty().boxed_local(),
+    };
+    let (_socket, requests) = control::listen()?;
+    let requests = requests.map(|incoming| Ok(WhichKeyEvent::Control(incoming)));
+    let mut events = futures::stream::select_all(vec![
+        modes.boxed_local(),
+        config_changes.boxed_local(),
+        presses,
+        requests.boxed_local(),
+        tray_actions(tray, theme)?,
+    ]);
+    let mut popup: Option<Child> = None;
+    let mut searcher: Option<Child> = None;
+    while let Some(event) = events.next().await {
+        let mode = match event? {
+            WhichKeyEvent::Mode(mode) => mode,
+            WhichKeyEvent::Hotkey => {
+                report(toggle(&mut searcher, theme, options));
+                continue;
+            }
+            WhichKeyEvent::Control((request, reply)) => {
+                let response = match request {
+                    Request::Show => show(&mut searcher, theme, options).map(|_| Response::Ok),
+                    Request::Hide => {
+                        hide(&mut searcher);
+                        Ok(Response::Ok)
+                    }
+                    Request::Toggle => toggle(&mut searcher, theme, options).map(|_| Response::Ok),
+                    Request::Reload => loader::load(options.clone()).await.map(|loaded| {
+                        config = loaded;
+                        Response::Ok
+                    }),
+                    Request::Query { text, limit } => {
+                        let entries = config.filter(&text, &Modifiers::default());
+                        Ok(Response::matches(&entries[..limit.min(entries.len())]))
+                    }
+                };
+                // The client may have hung up already
+                let _ = reply.send(response.unwrap_or_else(|error| Response::error(&error)));
+                continue;
+            }
+            WhichKeyEvent::Config(ConfigChange::Reloaded) => {
+                report(
+                    loader::load(options.clone())
+                        .await
+                        .map(|loaded| config = loaded),
+                );
+                continue;
+            }
+            WhichKeyEvent::Config(ConfigChange::Restarting) => continue,
+            // Closes the popup, the events end with the connection
+            WhichKeyEvent::Config(ConfigChange::Exited) => String::from("default"),
+            #[cfg(feature = "tray")]
+            WhichKeyEvent::Tray(action) => {
+                match action {
+                    TrayAction::Show => report(show(&mut searcher, theme, options)),
+                    TrayAction::Reload => report(
+                        loader::load(options.clone())
+                            .await
+                            .map(|loaded| config = loaded),
+                    ),
+                    TrayAction::Theme(selected) => theme = selected,
+                    TrayAction::Quit => {
+                        popup.into_iter().chain(searcher).for_each(close);
+                        return Ok(());
+                    }
+                }
+                continue;
+            }
+        };
+        // The popup may have been closed already
+        if let Some(child) = popup.take() {
+            close(child);
+        }
+        if config
+            .entries()
+            .iter()
+            .any(|entry| entry.mode() == Some(mode.as_str()))
+        {
+            report(
+                spawn_searcher(&["--keep-alive", "--mode", &mode], theme, options)
+                    .map(|child| popup = Some(child)),
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "tray")]
+fn tray_actions(
+    enabled: bool,
+    theme: Theme,
+) -> Result<LocalBoxStream<'static, Result<WhichKeyEvent, I3ConfigError>>, I3ConfigError> {
+    Ok(if enabled {
+        crate::tray::spawn(theme)
+            .map(|action| action.map(WhichKeyEvent::Tray))
+            .boxed_local()
+    } else {
+        futures::stream::empty().boxed_local()
+    })
+}
+
+#[cfg(all(feature = "gui", not(feature = "tray")))]
+fn tray_actions(
+    enabled: bool,
+    _theme: Theme,
+) -> Result<LocalBoxStream<'static, Result<WhichKeyEvent, I3ConfigError>>, I3ConfigError> {
+    if enabled {
+        Err(I3ConfigError::MissingFeature("tray"))
+    } else {
+        Ok(futures::stream::empty().boxed_local())
+    }
+}
+
+/// Prints the error, `which-key` keeps running with the config and searcher it has
+#[cfg(feature = "gui")]
+fn report(result: Result<(), I3ConfigError>) {
+    if let Err(error) = result {
+        eprintln!("{}", presenter::error_chain(&error));
+    }
+}
+
+#[cfg(feature = "gui")]
+fn is_running(child: &mut Child) -> bool {
+    matches!(child.try_wait(), Ok(None))
+}
+
+/// Opens the full searcher unless it is still open
+#[cfg(feature = "gui")]
+fn show(
+    searcher: &mut Option<Child>,
+    theme: Theme,
+    options: &SourceOptions,
+) -> Result<(), I3ConfigError> {
+    if !searcher.as_mut().is_some_and(is_running) {
+        *searcher = Some(spawn_searcher(&[], theme, options)?);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "gui")]
+fn hide(searcher: &mut Option<Child>) {
+    if let Some(child) = searcher.take() {
+        close(child);
+    }
+}
+
+#[cfg(feature = "gui")]
+fn toggle(
+    searcher: &mut Option<Child>,
+    theme: Theme,
+    options: &SourceOptions,
+) -> Result<(), I3ConfigError> {
+    if searcher.as_mut().is_some_and(is_running) {
+        hide(searcher);
+        Ok(())
+    } else {
+        show(searcher, theme, options)
+    }
+}
+
+#[cfg(feature = "gui")]
+fn close(mut child: Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(feature = "gui")]
+fn spawn_searcher(
+    args: &[&str],
+    theme: Theme,
+    options: &SourceOptions,
+) -> Result<Child, I3ConfigError> {
+    let spawn_error = |error| I3ConfigError::FailedSpawn(Arc::new(error));
+    let mut command = Command::new(std::env::current_exe().map_err(spawn_error)?);
+    command.args(args);
+    match theme {
+        Theme::Light => command.arg("--light"),
+        Theme::Dark => &mut command,
+        Theme::HighContrast => command.arg("--high-contrast"),
+    };
+    if let Some(path) = &options.config {
+        command.arg("--config").arg(path);
+    }
+    if let Some(git) = &options.git {
+        command
+            .args(["--git", &git.url])
+            .arg("--path")
+            .arg(&git.path);
+    }
+    if let Some(url) = &options.url {
+        command.args(["--url", url]);
+    }
+    for import in &options.imports {
+        command.arg("--import").arg(import);
+    }
+    command.spawn().map_err(spawn_error)
+}
@@ -0,0 +1,426 @@
+//! Writing changes back to the i3 config file
+
+use crate::i3_config::{
+    annotation_keys, content_hash, default_config_path, ConfigEntry, ConfigMetadata, I3ConfigError,
+};
+use crate::paths;
+use regex::Regex;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+const BACKUP_PREFIX: &str = "config-";
+/// Extension of the file next to a backup holding the path of the config it belongs to
+const TARGET_EXTENSION: &str = "path";
+
+/// Replaces group and description of the annotation on `line` (starting from 1).
+/// Keys, indentation and anything after the closing `##` are kept
+pub fn rewrite_annotation(
+    config: &mut ConfigMetadata,
+    line: usize,
+    group: &str,
+    description: &str,
+) -> Result<()> {
+    modify(config, &backup_dir()?, |text| {
+        replace_annotation(text, line, group, description)
+    })?;
+    config.update_annotation(line, group, description);
+    Ok(())
+}
+
+/// Appends an annotated `bindsym` block to the end of the config and adds it to the entries
+pub fn append_binding(
+    config: &mut ConfigMetadata,
+    group: &str,
+    description: &str,
+    keys: &str,
+    command: &str,
+) -> Result<()> {
+    let mut line = 0;
+    modify(config, &backup_dir()?, |text| {
+        let (text, annotation_line) = append_binding_text(text, group, description, keys, command)?;
+        line = annotation_line;
+        Ok(text)
+    })?;
+    config.add_entry(
+        ConfigEntry::new(
+            group.trim().to_owned(),
+            description.trim().to_owned(),
+            annotation_keys(keys.trim()),
+        )
+        .with_binding(keys.trim().to_owned(), command.trim().to_owned())
+        .with_line(line),
+    );
+    Ok(())
+}
+
+/// Restores the config from the newest backup and removes that backup,
+/// so calling it again goes further back. Returns the restored config path
+pub fn undo_last_edit() -> Result<PathBuf> {
+    restore_latest(&backup_dir()?)
+}
+
+/// Writes the newest backup in `backups` back to the config it was taken from.
+/// Backups saved without their config path belong to the default config
+fn restore_latest(backups: &Path) -> Result<PathBuf> {
+    let backup = latest_backup(backups)?.ok_or(I3ConfigError::NoBackup)?;
+    let target = backup.with_extension(TARGET_EXTENSION);
+    let path = match fs::read_to_string(&target) {
+        Ok(path) => PathBuf::from(path),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            default_config_path().ok_or_else(|| {
+                write_error(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "no i3 config file found",
+                ))
+            })?
+        }
+        Err(error) => return Err(write_error(error)),
+    };
+    let text = fs::read_to_string(&backup).map_err(write_error)?;
+    write_atomically(&path, &text).map_err(write_error)?;
+    fs::remove_file(&backup).map_err(write_error)?;
+    match fs::remove_file(&target) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(write_error(error)),
+        _ => Ok(path),
+    }
+}
+
+/// Applies `edit` to the config file after checking it wasn't changed since it was parsed.
+/// The previous version is saved into `backups` first
+fn modify(
+    config: &mut ConfigMetadata,
+    backups: &Path,
+    edit: impl FnOnce(&str) -> Result<String>,
+) -> Result<()> {
+    let path = config
+        .source_path()
+        .ok_or_else(|| {
+            write_error(io::Error::new(
+                io::ErrorKind::NotFound,
+                "config wasn't loaded from a local file",
+            ))
+        })?
+        .to_owned();
+    let text = fs::read_to_string(&path).map_err(write_error)?;
+    if content_hash(&text) != config.content_hash() {
+        return Err(I3ConfigError::ConfigChangedOnDisk);
+    }
+    let new_text = edit(&text)?;
+    save_backup(backups, &path, &text).map_err(write_error)?;
+    write_atomically(&path, &new_text).map_err(write_error)?;
+    config.set_text(new_text);
+    Ok(())
+}
+
+/// Backups are kept in `$XDG_DATA_HOME/i3-conf-searcher/backups`
+fn backup_dir() -> Result<PathBuf> {
+    paths::data_dir()
+        .map(|data| data.join("backups"))
+        .ok_or_else(|| {
+            write_error(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no data directory for config backups",
+            ))
+        })
+}
+
+/// Saves `text` together with the path of the config it was read from
+fn save_backup(dir: &Path, config: &Path, text: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(io::Error::other)?
+        .as_millis();
+    let path = dir.join(format!("{}{}", BACKUP_PREFIX, timestamp));
+    fs::write(&path, text)?;
+    let config = fs::canonicalize(config)?;
+    fs::write(
+        path.with_extension(TARGET_EXTENSION),
+        config.to_string_lossy().as_bytes(),
+    )?;
+    Ok(path)
+}
+
+/// Newest backup judging by the timestamp in its file name
+fn latest_backup(dir: &Path) -> Result<Option<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(write_error(error)),
+    };
+    let mut backups = vec![];
+    for entry in entries {
+        let path = entry.map_err(write_error)?.path();
+        let timestamp = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(BACKUP_PREFIX))
+            .and_then(|timestamp| timestamp.parse::<u128>().ok());
+        if let Some(timestamp) = timestamp {
+            backups.push((timestamp, path));
+        }
+    }
+    Ok(backups
+        .into_iter()
+        .max_by_key(|(timestamp, _)| *timestamp)
+        .map(|(_, path)| path))
+}
+
+fn write_error(error: io::Error) -> I3ConfigError {
+    I3ConfigError::FailedConfigWrite(Arc::new(error))
+}
+
+fn validate_annotation(group: &str, description: &str) -> Result<()> {
+    for field in &[group, description] {
+        if field.trim().is_empty() || field.contains("//") || field.contains("##") {
+            return Err(I3ConfigError::InvalidAnnotationField(field.to_string()));
+        }
+    }
+    Ok(())
+}
+
+fn append_binding_text(
+    text: &str,
+    group: &str,
+    description: &str,
+    keys: &str,
+    command: &str,
+) -> Result<(String, usize)> {
+    validate_annotation(group, description)?;
+    let keys = keys.trim();
+    if keys.is_empty() || keys.contains(char::is_whitespace) {
+        return Err(I3ConfigError::InvalidAnnotationField(keys.to_owned()));
+    }
+    let command = command.trim();
+    if command.is_empty() || command.contains('\n') {
+        return Err(I3ConfigError::InvalidAnnotationField(command.to_owned()));
+    }
+    let mut output = text.to_owned();
+    if !output.is_empty() && !output.ends_with('\n') {
+        output.push('\n');
+    }
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    let line = output.lines().count() + 1;
+    output.push_str(&format!(
+        "## {} // {} // {} ##\nbindsym {} {}\n",
+        group.trim(),
+        description.trim(),
+        annotation_keys(keys),
+        keys,
+        command
+    ));
+    Ok((output, line))
+}
+
+fn replace_annotation(text: &str, line: usize, group: &str, description: &str) -> Result<String> {
+    validate_annotation(group, description)?;
+    let re = Regex::new(r"^(?P<indent>\s*)##.*//.*//(?P<keys>.*)##(?P<rest>.*)$")
+        .map_err(|_| I3ConfigError::ConfigParsingError)?;
+    let mut output = String::with_capacity(text.len());
+    let mut replaced = false;
+    for (index, original) in text.split_inclusive('\n').enumerate() {
+        if index + 1 != line {
+            output.push_str(original);
+            continue;
+        }
+        let content = original.trim_end_matches(&['\r', '\n'][..]);
+        let line_ending = &original[content.len()..];
+        let cap = re.captures(content).ok_or_else(|| {
+            write_error(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {} is not an annotation", line),
+            ))
+        })?;
+        output.push_str(&format!(
+            "{}## {} // {} // {} ##{}{}",
+            &cap["indent"],
+            group.trim(),
+            description.trim(),
+            cap["keys"].trim(),
+            &cap["rest"],
+            line_ending
+        ));
+        replaced = true;
+    }
+    if !replaced {
+        return Err(write_error(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("config has no line {}", line),
+        )));
+    }
+    Ok(output)
+}
+
+/// Writes into a temporary file next to the config and renames it over the original
+/// so readers never see a partially written config. Symlinks are followed
+fn write_atomically(path: &Path, text: &str) -> io::Result<()> {
+    let path = fs::canonicalize(path)?;
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "config path has no file name")
+    })?;
+    let temporary = path.with_file_name(format!(
+        ".{}.i3-conf-searcher.tmp",
+        file_name.to_string_lossy()
+    ));
+    fs::write(&temporary, text)?;
+    fs::set_permissions(&temporary, fs::metadata(&path)?.permissions())?;
+    fs::rename(&temporary, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i3_config::ConfigSource;
+
+    fn temporary_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("i3-conf-searcher-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn modify_backs_up_previous_version() {
+        let dir = temporary_dir("backup");
+        let path = dir.join("config");
+        fs::write(&path, SAMPLE).unwrap();
+        let mut config = ConfigMetadata::parse(SAMPLE)
+            .unwrap()
+            .with_source(ConfigSource::File(path.clone()));
+        let backups = dir.join("backups");
+        modify(&mut config, &backups, |text| {
+            Ok(text.replace("kill", "exec true"))
+        })
+        .unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.contains("exec true"));
+        assert_eq!(config.content_hash(), content_hash(&written));
+        let backup = latest_backup(&backups).unwrap().unwrap();
+        assert_eq!(fs::read_to_string(backup).unwrap(), SAMPLE);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn modify_refuses_changed_file() {
+        let dir = temporary_dir("changed");
+        let path = dir.join("config");
+        fs::write(&path, "bindsym $mod+b exec firefox\n").unwrap();
+        let mut config = ConfigMetadata::parse(SAMPLE)
+            .unwrap()
+            .with_source(ConfigSource::File(path.clone()));
+        assert!(matches!(
+            modify(
+                &mut config,
+                &dir.join("backups"),
+                |text| Ok(text.to_owned())
+            ),
+            Err(I3ConfigError::ConfigChangedOnDisk)
+        ));
+        assert!(latest_backup(&dir.join("backups")).unwrap().is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn undo_restores_the_edited_config() {
+        let dir = temporary_dir("undo");
+        let path = dir.join("work.config");
+        fs::write(&path, SAMPLE).unwrap();
+        let mut config = ConfigMetadata::parse(SAMPLE)
+            .unwrap()
+            .with_source(ConfigSource::File(path.clone()));
+        let backups = dir.join("backups");
+        modify(&mut config, &backups, |text| {
+            Ok(text.replace("kill", "exec true"))
+        })
+        .unwrap();
+        assert_eq!(
+            restore_latest(&backups).unwrap(),
+            fs::canonicalize(&path).unwrap()
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), SAMPLE);
+        assert_eq!(fs::read_dir(&backups).unwrap().count(), 0);
+        assert!(matches!(
+            restore_latest(&backups),
+            Err(I3ConfigError::NoBackup)
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn latest_backup_by_timestamp() {
+        let dir = temporary_dir("latest");
+        for name in &["config-900", "config-1000", "config-1000.path", "notes"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+        assert_eq!(latest_backup(&dir).unwrap(), Some(dir.join("config-1000")));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    const SAMPLE: &str =
+        "set $mod Mod4\n    ## group1 // description1 // <> a ## comment\r\nbindsym $mod+a kill\n";
+
+    #[test]
+    fn replaces_group_and_description() {
+        let text = replace_annotation(SAMPLE, 2, "new group", "new description").unwrap();
+        assert_eq!(
+            text,
+            "set $mod Mod4\n    ## new group // new description // <> a ## comment\r\nbindsym $mod+a kill\n"
+        );
+    }
+
+    #[test]
+    fn rejects_separators_in_fields() {
+        assert!(matches!(
+            replace_annotation(SAMPLE, 2, "a // b", "description"),
+            Err(I3ConfigError::InvalidAnnotationField(_))
+        ));
+        assert!(matches!(
+            replace_annotation(SAMPLE, 2, "group", " "),
+            Err(I3ConfigError::InvalidAnnotationField(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_lines_without_annotation() {
+        assert!(matches!(
+            replace_annotation(SAMPLE, 1, "group", "description"),
+            Err(I3ConfigError::FailedConfigWrite(_))
+        ));
+        assert!(matches!(
+            replace_annotation(SAMPLE, 10, "group", "description"),
+            Err(I3ConfigError::FailedConfigWrite(_))
+        ));
+    }
+
+    #[test]
+    fn appends_annotated_binding() {
+        let (text, line) = append_binding_text(
+            "bindsym $mod+a kill",
+            "launch",
+            "browser",
+            "$mod+b",
+            "exec firefox",
+        )
+        .unwrap();
+        assert_eq!(
+            text,
+            format!(
+                "bindsym $mod+a kill\n\n## launch // browser // {} b ##\nbindsym $mod+b exec firefox\n",
+                annotation_keys("$mod")
+            )
+        );
+        assert_eq!(line, 3);
+        assert!(matches!(
+            append_binding_text("", "launch", "browser", "$mod+ b", "exec firefox"),
+            Err(I3ConfigError::InvalidAnnotationField(_))
+        ));
+    }
+}
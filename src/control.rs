@@ -0,0 +1,197 @@
+//! Control socket of `which-key`, one JSON request per line answered by one JSON response line
+
+use crate::i3_config::{ConfigEntry, I3ConfigError};
+use crate::paths;
+use futures::channel::{mpsc, oneshot};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+pub const DEFAULT_QUERY_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    Show,
+    Hide,
+    Toggle,
+    /// Load the config again
+    Reload,
+    /// Best matches of the text
+    Query {
+        text: String,
+        #[serde(default = "default_query_limit")]
+        limit: usize,
+    },
+}
+
+fn default_query_limit() -> usize {
+    DEFAULT_QUERY_LIMIT
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum Response {
+    Ok,
+    Matches { matches: Vec<Match> },
+    Error { error: String, message: String },
+}
+
+impl Response {
+    pub fn matches(entries: &[&ConfigEntry]) -> Self {
+        Response::Matches {
+            matches: entries
+                .iter()
+                .map(|entry| Match {
+                    group: entry.group().to_owned(),
+                    description: entry.description().to_owned(),
+                    keys: entry.keys().to_owned(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn error(error: &I3ConfigError) -> Self {
+        Response::Error {
+            error: error.code().to_owned(),
+            message: crate::presenter::error_chain(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Match {
+    pub group: String,
+    pub description: String,
+    pub keys: String,
+}
+
+/// Request waiting for `which-key` to answer it
+pub type Incoming = (Request, oneshot::Sender<Response>);
+
+/// `$XDG_RUNTIME_DIR/i3-conf-searcher.sock`, falling back to the temp directory
+pub fn socket_path() -> PathBuf {
+    paths::runtime_dir().join("i3-conf-searcher.sock")
+}
+
+/// Removes the socket file when dropped
+#[derive(Debug)]
+pub struct ControlSocket {
+    path: PathBuf,
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn control_error(error: impl std::fmt::Display) -> I3ConfigError {
+    I3ConfigError::FailedControl(error.to_string())
+}
+
+/// Sends the request to the running `which-key`, error responses are turned into errors
+#[cfg(unix)]
+pub fn send(request: &Request) -> Result<Response> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(socket_path())
+        .map_err(|error| control_error(format!("which-key isn't running: {}", error)))?;
+    let line = serde_json::to_string(request).map_err(control_error)?;
+    writeln!(stream, "{}", line).map_err(control_error)?;
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .map_err(control_error)?;
+    match serde_json::from_str(&reply).map_err(control_error)? {
+        Response::Error { message, .. } => Err(control_error(message)),
+        response => Ok(response),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn send(_request: &Request) -> Result<Response> {
+    Err(I3ConfigError::UnsupportedPlatform)
+}
+
+/// Listens on the socket and passes every request on until the receiver is dropped.
+/// Fails if another `which-key` is listening already, sockets of killed ones are replaced
+#[cfg(unix)]
+pub fn listen() -> Result<(ControlSocket, mpsc::UnboundedReceiver<Incoming>)> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let path = socket_path();
+    if UnixStream::connect(&path).is_ok() {
+        return Err(control_error("another which-key is running"));
+    }
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).map_err(control_error)?;
+    let (sender, receiver) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut line = String::new();
+            let read = stream
+                .try_clone()
+                .and_then(|reader| BufReader::new(reader).read_line(&mut line));
+            if read.is_err() {
+                continue;
+            }
+            let response = match serde_json::from_str(&line) {
+                Ok(request) => {
+                    let (reply, response) = oneshot::channel();
+                    if sender.unbounded_send((request, reply)).is_err() {
+                        break;
+                    }
+                    futures::executor::block_on(response)
+                        .unwrap_or_else(|_| Response::error(&control_error("which-key stopped")))
+                }
+                Err(error) => Response::error(&control_error(error)),
+            };
+            if let Ok(line) = serde_json::to_string(&response) {
+                let _ = writeln!(stream, "{}", line);
+            }
+        }
+    });
+    Ok((ControlSocket { path }, receiver))
+}
+
+#[cfg(not(unix))]
+pub fn listen() -> Result<(ControlSocket, mpsc::UnboundedReceiver<Incoming>)> {
+    Err(I3ConfigError::UnsupportedPlatform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_and_responses_as_json() {
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"command":"query","text":"screenshot"}"#).unwrap(),
+            Request::Query {
+                text: String::from("screenshot"),
+                limit: DEFAULT_QUERY_LIMIT
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&Request::Toggle).unwrap(),
+            r#"{"command":"toggle"}"#
+        );
+        let entry = ConfigEntry::new(
+            String::from("media"),
+            String::from("play"),
+            String::from("<> p"),
+        );
+        assert_eq!(
+            serde_json::to_string(&Response::matches(&[&entry])).unwrap(),
+            r#"{"status":"matches","matches":[{"group":"media","description":"play","keys":"<> p"}]}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Response::error(&I3ConfigError::NoBackup)).unwrap(),
+            r#"{"status":"error","error":"no_backup","message":"no config backups to restore"}"#
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Self-test of everything the searcher needs to work
+
+use crate::i3_config::ConfigMetadata;
+use crate::loader::{self, SourceOptions};
+use crate::{presenter, targets};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warning,
+    Failed,
+}
+
+/// Outcome of a single check
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Check {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Runs all checks, a failing check doesn't stop the ones after it.
+/// Display and font are only checked for frontends with a window
+pub async fn run_checks(options: SourceOptions, font: Option<&[u8]>) -> Vec<Check> {
+    let mut checks = vec![ipc_check().await];
+    match loader::load(options).await {
+        Ok(config) => {
+            checks.push(Check::new(
+                "config",
+                Status::Ok,
+                format!("loaded from {}", config.source()),
+            ));
+            checks.push(annotations_check(&config));
+        }
+        Err(error) => checks.push(Check::new(
+            "config",
+            Status::Failed,
+            presenter::error_chain(&error),
+        )),
+    }
+    if let Some(font) = font {
+        checks.push(display_check());
+        checks.push(font_check(font));
+    }
+    checks
+}
+
+async fn ipc_check() -> Check {
+    let socket = ["I3SOCK", "SWAYSOCK"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok());
+    match targets::query().await {
+        Ok(_) => Check::new(
+            "i3 IPC",
+            Status::Ok,
+            match socket {
+                Some(socket) => format!("reachable at {}", socket),
+                None => String::from("reachable"),
+            },
+        ),
+        Err(error) => Check::new("i3 IPC", Status::Failed, presenter::error_chain(&error)),
+    }
+}
+
+fn annotations_check(config: &ConfigMetadata) -> Check {
+    let count = config.entries().len();
+    let malformed = config.diagnostics().len();
+    if count == 0 {
+        Check::new(
+            "annotations",
+            Status::Warning,
+            "no annotated bindings found, see the README on how to annotate them",
+        )
+    } else if malformed > 0 {
+        Check::new(
+            "annotations",
+            Status::Warning,
+            format!(
+                "{} annotated bindings, {} malformed, run --check for details",
+                count, malformed
+            ),
+        )
+    } else {
+        Check::new(
+            "annotations",
+            Status::Ok,
+            format!("{} annotated bindings", count),
+        )
+    }
+}
+
+/// Bindings are run through i3 IPC so only the window needs a display server
+fn display_check() -> Check {
+    let wayland = std::env::var("WAYLAND_DISPLAY").ok();
+    let x11 = std::env::var("DISPLAY").ok();
+    match (wayland, x11) {
+        (Some(display), _) => Check::new(
+            "display",
+            Status::Ok,
+            format!("Wayland ({}), bindings run through IPC", display),
+        ),
+        (None, Some(display)) => Check::new(
+            "display",
+            Status::Ok,
+            format!("X11 ({}), bindings run through IPC", display),
+        ),
+        (None, None) => Check::new(
+            "display",
+            Status::Failed,
+            "neither WAYLAND_DISPLAY nor DISPLAY is set, the window can't open",
+        ),
+    }
+}
+
+/// Checks the font starts with a TrueType or OpenType header with at least one table
+fn font_check(bytes: &[u8]) -> Check {
+    let valid = bytes.len() > 12
+        && matches!(&bytes[..4], [0, 1, 0, 0] | b"OTTO" | b"true")
+        && u16::from_be_bytes([bytes[4], bytes[5]]) > 0;
+    if valid {
+        Check::new("font", Status::Ok, format!("{} KiB", bytes.len() / 1024))
+    } else {
+        Check::new(
+            "font",
+            Status::Failed,
+            "bundled font is not a TrueType font",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_font_is_valid() {
+        let font = include_bytes!("../fonts/MesloLGS NF Regular.ttf");
+        assert_eq!(font_check(font).status, Status::Ok);
+        assert_eq!(font_check(b"not a font at all").status, Status::Failed);
+    }
+
+    #[test]
+    fn annotation_problems_are_warnings() {
+        let config = ConfigMetadata::parse("## group1 // description1 ##").unwrap();
+        assert_eq!(annotations_check(&config).status, Status::Warning);
+        let config = ConfigMetadata::parse("## group1 // description1 // keys1 ##").unwrap();
+        assert_eq!(annotations_check(&config).status, Status::Ok);
+    }
+}
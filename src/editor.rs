@@ -0,0 +1,43 @@
+//! Opening the config file in an external editor
+
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Used when no editor command is configured, runs `$EDITOR` inside a terminal
+fn default_template() -> String {
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| String::from("vi"));
+    format!("x-terminal-emulator -e {} +{{line}} {{file}}", editor)
+}
+
+/// Spawns the editor without waiting for it to exit.
+/// `{file}` and `{line}` in the template are replaced with the location
+pub fn open_at_line(template: Option<&str>, file: &Path, line: usize) -> io::Result<()> {
+    let template = template.map(str::to_owned).unwrap_or_else(default_template);
+    let arguments = build_arguments(&template, file, line);
+    let (program, arguments) = arguments
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "editor command is empty"))?;
+    Command::new(program).args(arguments).spawn()?;
+    Ok(())
+}
+
+fn build_arguments(template: &str, file: &Path, line: usize) -> Vec<String> {
+    let file = file.to_string_lossy();
+    let line = line.to_string();
+    template
+        .split_whitespace()
+        .map(|part| part.replace("{file}", &file).replace("{line}", &line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_replaced() {
+        let arguments = build_arguments("code --goto {file}:{line}", Path::new("/i3/config"), 12);
+        assert_eq!(arguments, vec!["code", "--goto", "/i3/config:12"]);
+    }
+}
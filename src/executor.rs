@@ -0,0 +1,156 @@
+use crate::geometry::Geometry;
+use crate::i3_command::{self, TokenKind};
+use crate::i3_config::{ConfigEntry, I3ConfigError};
+use crate::preferences::WindowPosition;
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+use tokio_i3ipc::{
+    reply::{Floating, Node},
+    I3,
+};
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+/// Time i3 gets to switch modes before the command runs
+#[cfg(feature = "tokio")]
+const MODE_DELAY: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Runs the command bound to the entry through i3 IPC.
+/// Bindings of a mode enter it first so they behave as if their keys were pressed in it,
+/// and return to the default mode afterwards unless `stay_in_mode` is set
+pub async fn execute(entry: &ConfigEntry, stay_in_mode: bool) -> Result<()> {
+    let command = entry.command().ok_or(I3ConfigError::MissingCommand)?;
+    let mode = match entry.mode() {
+        Some(mode) => mode,
+        None => return run_i3_command(command).await,
+    };
+    run_i3_command(&format!("mode \"{}\"", mode)).await?;
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(MODE_DELAY).await;
+    let result = run_i3_command(command).await;
+    // Leaving even if the command failed so i3 isn't stuck in the mode
+    if returns_to_default_mode(command, stay_in_mode) {
+        result.and(run_i3_command("mode \"default\"").await)
+    } else {
+        result
+    }
+}
+
+/// Commands switching modes themselves, like the usual `Escape` binding, are left alone
+fn returns_to_default_mode(command: &str, stay_in_mode: bool) -> bool {
+    !stay_in_mode
+        && !i3_command::tokenize(command)
+            .iter()
+            .any(|token| token.kind == TokenKind::Verb && token.text == "mode")
+}
+
+/// Asks i3 to reload its config
+pub async fn reload_i3() -> Result<()> {
+    run_i3_command("reload").await
+}
+
+/// Focuses the window of a searcher that's already open
+pub async fn focus_searcher() -> Result<()> {
+    run_i3_command(r#"[class="^i3-conf-searcher$"] focus"#).await
+}
+
+/// Resizes the floating searcher window, i3 keeps its width
+pub async fn resize_searcher(height: u32) -> Result<()> {
+    run_i3_command(&format!(
+        r#"[class="^i3-conf-searcher$"] resize set height {} px"#,
+        height
+    ))
+    .await
+}
+
+/// Moves the floating searcher window
+pub async fn move_searcher(position: WindowPosition) -> Result<()> {
+    run_i3_command(&format!(
+        r#"[class="^i3-conf-searcher$"] move position {}"#,
+        position.i3_arguments()
+    ))
+    .await
+}
+
+/// Geometry of the searcher window, `None` unless i3 lets it float
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+pub async fn searcher_geometry() -> Result<Option<Geometry>> {
+    let mut i3 = I3::connect().await.map_err(I3ConfigError::ipc)?;
+    let tree = i3.get_tree().await.map_err(I3ConfigError::ipc)?;
+    Ok(find_searcher(&tree)
+        .filter(|node| matches!(node.floating, Some(Floating::AutoOn | Floating::UserOn)))
+        .map(|node| Geometry {
+            x: node.rect.x as i32,
+            y: node.rect.y as i32,
+            width: node.window_rect.width as u32,
+            height: node.window_rect.height as u32,
+        }))
+}
+
+#[cfg(not(all(target_family = "unix", feature = "ipc")))]
+pub async fn searcher_geometry() -> Result<Option<Geometry>> {
+    Err(I3ConfigError::ipc_unavailable())
+}
+
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+fn find_searcher(node: &Node) -> Option<&Node> {
+    let is_searcher = node
+        .window_properties
+        .as_ref()
+        .and_then(|properties| properties.class.as_deref())
+        == Some("i3-conf-searcher");
+    if is_searcher {
+        return Some(node);
+    }
+    node.nodes
+        .iter()
+        .chain(&node.floating_nodes)
+        .find_map(find_searcher)
+}
+
+/// Checks that i3 can be reached before committing to run a command
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+pub async fn ping() -> Result<()> {
+    I3::connect().await.map_err(I3ConfigError::ipc)?;
+    Ok(())
+}
+
+#[cfg(not(all(target_family = "unix", feature = "ipc")))]
+pub async fn ping() -> Result<()> {
+    Err(I3ConfigError::ipc_unavailable())
+}
+
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+async fn run_i3_command(command: &str) -> Result<()> {
+    let mut i3 = I3::connect().await.map_err(I3ConfigError::ipc)?;
+    let outcomes = i3.run_command(command).await.map_err(I3ConfigError::ipc)?;
+    for outcome in outcomes {
+        if !outcome.success {
+            return Err(I3ConfigError::CommandFailed(
+                outcome.error.unwrap_or_default(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(all(target_family = "unix", feature = "ipc")))]
+async fn run_i3_command(_command: &str) -> Result<()> {
+    Err(I3ConfigError::ipc_unavailable())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_mode_is_restored_unless_the_command_switches() {
+        assert!(returns_to_default_mode("resize shrink width 10 px", false));
+        assert!(!returns_to_default_mode("resize shrink width 10 px", true));
+        assert!(!returns_to_default_mode("mode \"default\"", false));
+        assert!(!returns_to_default_mode(
+            "exec i3lock; mode \"default\"",
+            false
+        ));
+        assert!(returns_to_default_mode("exec notify-send mode", false));
+    }
+}
@@ -0,0 +1,247 @@
+//! C ABI over the parser and filter for helpers written in other languages.
+//! The header is generated with `cbindgen --config cbindgen.toml --output include/i3_conf_searcher.h`
+//!
+//! Strings returned by the library are owned by the caller and freed with `i3cs_string_free`,
+//! entries are borrowed from their config and valid until it's filtered again or freed
+
+use crate::i3_config::{ConfigEntry, ConfigMetadata, Modifiers};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+pub const I3CS_MODIFIER_SHIFT: u8 = 1;
+pub const I3CS_MODIFIER_CONTROL: u8 = 2;
+pub const I3CS_MODIFIER_ALT: u8 = 4;
+pub const I3CS_MODIFIER_SUPER: u8 = 8;
+
+/// Parsed config with the results of the last filter
+pub struct I3Config {
+    config: ConfigMetadata,
+    results: Vec<ConfigEntry>,
+}
+
+/// Parses the annotations of a config.
+/// Returns NULL if it can't be parsed, with the exit code of the error written to `error`
+///
+/// # Safety
+/// `text` has to be a NUL terminated UTF-8 string, `error` NULL or valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_parse_config(text: *const c_char, error: *mut i32) -> *mut I3Config {
+    let text = match str_argument(text) {
+        Some(text) => text,
+        None => return ptr::null_mut(),
+    };
+    match ConfigMetadata::parse(text) {
+        Ok(config) => Box::into_raw(Box::new(I3Config {
+            config,
+            results: vec![],
+        })),
+        Err(parse_error) => {
+            if !error.is_null() {
+                *error = parse_error.exit_code();
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+/// `config` has to come from `i3cs_parse_config` and not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_config_free(config: *mut I3Config) {
+    if !config.is_null() {
+        drop(Box::from_raw(config));
+    }
+}
+
+/// # Safety
+/// `config` has to come from `i3cs_parse_config`
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_config_entry_count(config: *const I3Config) -> usize {
+    config
+        .as_ref()
+        .map_or(0, |config| config.config.entries().len())
+}
+
+/// Entry in config order, NULL if `index` is out of range
+///
+/// # Safety
+/// `config` has to come from `i3cs_parse_config`
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_config_entry(
+    config: *const I3Config,
+    index: usize,
+) -> *const ConfigEntry {
+    config
+        .as_ref()
+        .and_then(|config| config.config.entries().get(index))
+        .map_or(ptr::null(), |entry| entry as *const ConfigEntry)
+}
+
+/// Fuzzy matches `query` against the entries using the held `I3CS_MODIFIER_*` flags.
+/// Returns the number of results, read them with `i3cs_filter_result`
+///
+/// # Safety
+/// `config` has to come from `i3cs_parse_config`, `query` has to be a NUL terminated UTF-8 string
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_filter(
+    config: *mut I3Config,
+    query: *const c_char,
+    modifiers: u8,
+) -> usize {
+    let (config, query) = match (config.as_mut(), str_argument(query)) {
+        (Some(config), Some(query)) => (config, query),
+        _ => return 0,
+    };
+    let modifiers = Modifiers::new(
+        modifiers & I3CS_MODIFIER_SHIFT != 0,
+        modifiers & I3CS_MODIFIER_CONTROL != 0,
+        modifiers & I3CS_MODIFIER_ALT != 0,
+        modifiers & I3CS_MODIFIER_SUPER != 0,
+    );
+    config.results = config
+        .config
+        .filter(query, &modifiers)
+        .into_iter()
+        .cloned()
+        .collect();
+    config.results.len()
+}
+
+/// Result of the last `i3cs_filter`, best match first. NULL if `index` is out of range
+///
+/// # Safety
+/// `config` has to come from `i3cs_parse_config`
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_filter_result(
+    config: *const I3Config,
+    index: usize,
+) -> *const ConfigEntry {
+    config
+        .as_ref()
+        .and_then(|config| config.results.get(index))
+        .map_or(ptr::null(), |entry| entry as *const ConfigEntry)
+}
+
+/// # Safety
+/// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_entry_group(entry: *const ConfigEntry) -> *mut c_char {
+    owned_string(entry.as_ref().map(ConfigEntry::group))
+}
+
+/// # Safety
+/// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_entry_description(entry: *const ConfigEntry) -> *mut c_char {
+    owned_string(entry.as_ref().map(ConfigEntry::description))
+}
+
+/// # Safety
+/// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_entry_keys(entry: *const ConfigEntry) -> *mut c_char {
+    owned_string(entry.as_ref().map(ConfigEntry::keys))
+}
+
+/// Command of the binding following the annotation, NULL if there is none
+///
+/// # Safety
+/// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_entry_command(entry: *const ConfigEntry) -> *mut c_char {
+    owned_string(entry.as_ref().and_then(ConfigEntry::command))
+}
+
+/// Mode of the binding, NULL for the default mode
+///
+/// # Safety
+/// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_entry_mode(entry: *const ConfigEntry) -> *mut c_char {
+    owned_string(entry.as_ref().and_then(ConfigEntry::mode))
+}
+
+/// Line of the annotation, starting at 1
+///
+/// # Safety
+/// `entry` has to come from `i3cs_config_entry` or `i3cs_filter_result`
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_entry_line(entry: *const ConfigEntry) -> usize {
+    entry.as_ref().map_or(0, ConfigEntry::line)
+}
+
+/// # Safety
+/// `string` has to come from this library and not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn i3cs_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+unsafe fn str_argument<'a>(string: *const c_char) -> Option<&'a str> {
+    if string.is_null() {
+        return None;
+    }
+    CStr::from_ptr(string).to_str().ok()
+}
+
+/// Interior NULs can't be represented in C strings, text is cut at the first one
+fn owned_string(text: Option<&str>) -> *mut c_char {
+    match text {
+        Some(text) => {
+            let text = text.split('\0').next().unwrap_or_default();
+            CString::new(text).map_or(ptr::null_mut(), CString::into_raw)
+        }
+        None => ptr::null_mut(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn take_string(string: *mut c_char) -> Option<String> {
+        if string.is_null() {
+            return None;
+        }
+        let text = CStr::from_ptr(string).to_string_lossy().into_owned();
+        i3cs_string_free(string);
+        Some(text)
+    }
+
+    #[test]
+    fn parse_and_filter_through_c_abi() {
+        let text = CString::new(
+            "## Launch // Terminal // <> Enter ##
+            bindsym $mod+Return exec alacritty
+            ## Launch // Browser // <> b ##",
+        )
+        .unwrap();
+        let query = CString::new("term").unwrap();
+        unsafe {
+            let config = i3cs_parse_config(text.as_ptr(), ptr::null_mut());
+            assert!(!config.is_null());
+            assert_eq!(i3cs_config_entry_count(config), 2);
+            assert!(i3cs_config_entry(config, 2).is_null());
+
+            assert_eq!(i3cs_filter(config, query.as_ptr(), I3CS_MODIFIER_SUPER), 1);
+            let entry = i3cs_filter_result(config, 0);
+            assert_eq!(
+                take_string(i3cs_entry_description(entry)).as_deref(),
+                Some("Terminal")
+            );
+            assert_eq!(
+                take_string(i3cs_entry_command(entry)).as_deref(),
+                Some("exec alacritty")
+            );
+            assert_eq!(i3cs_entry_line(entry), 1);
+
+            let browser = i3cs_config_entry(config, 1);
+            assert_eq!(take_string(i3cs_entry_command(browser)), None);
+            assert_eq!(take_string(i3cs_entry_mode(browser)), None);
+            i3cs_config_free(config);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Size and position of the floating searcher window remembered per profile in
+//! `$XDG_DATA_HOME/i3-conf-searcher/geometry.json`
+
+use crate::i3_config::I3ConfigError;
+use crate::{json_file, paths};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+/// Top left corner of the window frame and size of its content in pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Geometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Last geometry of every profile, see [`crate::loader::SourceOptions::profile`]
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct WindowGeometries {
+    profiles: BTreeMap<String, Geometry>,
+}
+
+impl WindowGeometries {
+    /// `$XDG_DATA_HOME/i3-conf-searcher/geometry.json`
+    pub fn path() -> Option<PathBuf> {
+        paths::data_dir().map(|data| data.join("geometry.json"))
+    }
+
+    /// [`WindowGeometries::path`], an error without a data directory
+    pub fn required_path() -> Result<PathBuf> {
+        json_file::required(
+            WindowGeometries::path(),
+            "the window geometry",
+            geometry_error,
+        )
+    }
+
+    /// A missing file means no window was remembered yet
+    pub fn load(path: &Path) -> Result<WindowGeometries> {
+        json_file::load(path, geometry_error)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        json_file::save(self, path, geometry_error)
+    }
+
+    pub fn get(&self, profile: &str) -> Option<Geometry> {
+        self.profiles.get(profile).copied()
+    }
+
+    pub fn set(&mut self, profile: &str, geometry: Geometry) {
+        self.profiles.insert(profile.to_owned(), geometry);
+    }
+
+    /// Geometry the window of the profile had when it was last closed.
+    /// An unreadable file is treated like a missing one, the window opens at its default size
+    pub fn recall(profile: &str) -> Option<Geometry> {
+        let path = WindowGeometries::path()?;
+        WindowGeometries::load(&path).ok()?.get(profile)
+    }
+
+    /// Loads the geometries, replaces the one of the profile and saves them again
+    pub fn remember(profile: &str, geometry: Geometry) -> Result<()> {
+        json_file::update(
+            &WindowGeometries::required_path()?,
+            geometry_error,
+            |geometries: &mut WindowGeometries| geometries.set(profile, geometry),
+        )
+    }
+}
+
+fn geometry_error(error: io::Error) -> I3ConfigError {
+    I3ConfigError::FailedGeometry(Arc::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geometries_are_kept_per_profile() {
+        let path = std::env::temp_dir().join(format!(
+            "i3-conf-searcher-geometry-{}.json",
+            std::process::id()
+        ));
+        assert_eq!(
+            WindowGeometries::load(&path).unwrap(),
+            WindowGeometries::default()
+        );
+        let geometry = Geometry {
+            x: 100,
+            y: 50,
+            width: 800,
+            height: 600,
+        };
+        let mut geometries = WindowGeometries::default();
+        geometries.set("i3", geometry);
+        geometries.save(&path).unwrap();
+        let loaded = WindowGeometries::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.get("i3"), Some(geometry));
+        assert_eq!(loaded.get("/etc/i3/config"), None);
+    }
+}
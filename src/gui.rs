@@ -0,0 +1,3466 @@
+//! Searcher window
+
+use crate::keyboard_grab::KeyboardGrab;
+use crate::override_redirect;
+use crate::style::{self, Metrics, Theme};
+use i3_conf_searcher::geometry::WindowGeometries;
+use i3_conf_searcher::localization::Localizer;
+use i3_conf_searcher::preferences::{Density, WindowPosition, WindowPreferences};
+use i3_conf_searcher::{
+    config_writer, editor, executor, hidden, i3_command, i3_config, instance, loader, matcher,
+    mode_events, presenter, preview, targets, usage, validation,
+};
+use iced::{
+    button, scrollable, text_input, tooltip, Align, Application, Button, Clipboard, Color, Column,
+    Command, Container, Element, Font, Length, Row, Scrollable, Settings, Space, Subscription,
+    Text, TextInput, Tooltip,
+};
+use iced_native::{
+    keyboard::{self, Event, KeyCode},
+    mouse, window,
+    Event::{Keyboard, Mouse, Window},
+};
+
+/// Opens the searcher window and blocks until it is closed
+pub fn run(flags: InitFlags) {
+    let mut settings = Settings::with_flags(flags);
+    // Needs a compositor, without one the background stays opaque
+    settings.window.transparent = settings.flags.opacity < 1.0;
+    settings.window.decorations = settings.flags.window.decorations;
+    settings.window.resizable = settings.flags.window.resizable;
+    settings.window.always_on_top = settings.flags.window.always_on_top;
+    // The height follows the entries with --auto-height
+    if !settings.flags.auto_height {
+        if let Some(geometry) = WindowGeometries::recall(&settings.flags.sources.profile()) {
+            settings.window.size = (geometry.width, geometry.height);
+            settings
+                .flags
+                .window
+                .position
+                .get_or_insert(WindowPosition::At([geometry.x, geometry.y]));
+        }
+    }
+    ApplicationState::run(settings).unwrap()
+}
+
+/// Options the searcher window is opened with
+#[derive(Debug)]
+pub struct InitFlags {
+    pub theme: Theme,
+    pub density: Density,
+    /// Decorations, resizing, stacking and position of the window
+    pub window: WindowPreferences,
+    /// Show entries running the same command as one row
+    pub collapse_duplicates: bool,
+    pub exit_on_focus_loss: bool,
+    pub focus_loss_grace: std::time::Duration,
+    pub group_sidebar: bool,
+    pub order: i3_config::EntryOrder,
+    pub min_score: Option<i3_config::MinScore>,
+    pub matcher: matcher::MatcherKind,
+    pub search_text: i3_config::SearchText,
+    pub sources: loader::SourceOptions,
+    pub editor: Option<String>,
+    pub strict_modifiers: bool,
+    pub mode: Option<String>,
+    pub source_badges: bool,
+    pub grab_keyboard: bool,
+    /// Show a desktop notification when the binding fails after the window closed
+    pub notify_failures: bool,
+    /// Record run entries for `stats --usage`
+    pub usage_stats: bool,
+    /// Where hidden entries are remembered, without it they are shown again on the next start
+    pub hidden_entries: Option<std::path::PathBuf>,
+    /// Stay in the mode of a run binding instead of returning to the default mode
+    pub stay_in_mode: bool,
+    /// Take the window away from the window manager once it's mapped
+    pub override_redirect: bool,
+    /// Run the only matching entry without pressing Enter
+    pub quick: bool,
+    /// Shrink the window to the matching entries
+    pub auto_height: bool,
+    /// Opacity of the window background between 0 and 1, text stays opaque
+    pub opacity: f32,
+    /// Fade in new results and the selection highlight
+    pub animations: bool,
+    /// Print the selected entry whenever it changes
+    pub announce: bool,
+    /// Held while the window is open so a second launch focuses it
+    pub instance_lock: Option<instance::InstanceLock>,
+}
+
+#[derive(Debug)]
+struct ApplicationState {
+    theme: Theme,
+    density: Density,
+    collapse_duplicates: bool,
+    exit_on_focus_loss: bool,
+    focus_loss_grace: std::time::Duration,
+    /// Set while unfocused, the searcher closes if it's still set when the grace period ends
+    focus_lost: Option<usize>,
+    focus_loss_count: usize,
+    /// Used to fit descriptions into the rows
+    window_width: u32,
+    group_sidebar: bool,
+    order: i3_config::EntryOrder,
+    min_score: Option<i3_config::MinScore>,
+    matcher: matcher::MatcherKind,
+    search_text: i3_config::SearchText,
+    editor: Option<String>,
+    strict_modifiers: bool,
+    mode: Option<String>,
+    source_badges: bool,
+    live_targets: Option<targets::LiveTargets>,
+    sources: loader::SourceOptions,
+    load_stage: loader::LoadStage,
+    spinner_frame: usize,
+    state: Searcher,
+    modifier_state: i3_config::Modifiers,
+    toast: Option<Toast>,
+    toast_count: usize,
+    execution_error: Option<ErrorBanner>,
+    quick: bool,
+    quick_run: Option<QuickRun>,
+    auto_height: bool,
+    requested_height: Option<u32>,
+    opacity: f32,
+    animations: Animations,
+    announce: bool,
+    announced: Option<String>,
+    localizer: Localizer,
+    quick_run_count: usize,
+    geometry_saved: bool,
+    exiting: bool,
+    // Declared before the pending binding so the grab is released before it runs
+    _keyboard_grab: Option<KeyboardGrab>,
+    _instance_lock: Option<instance::InstanceLock>,
+    pending_execution: PendingExecution,
+    notify_failures: bool,
+    usage_stats: bool,
+    hidden_entries: Option<std::path::PathBuf>,
+    stay_in_mode: bool,
+    /// When the window opened and when the first character was typed, for usage stats
+    opened: std::time::Instant,
+    search_started: Option<std::time::Instant>,
+    // Last so everything else is dropped before the process exits
+    _exit_status: ExitStatus,
+}
+
+/// Binding that runs as the last step of shutting down.
+/// It's run on drop because the event loop never returns after the window closes
+#[derive(Debug, Default)]
+struct PendingExecution {
+    entry: Option<i3_config::ConfigEntry>,
+    /// Summary of the desktop notification shown when the binding fails, `None` if disabled
+    failure_notification: Option<String>,
+    stay_in_mode: bool,
+}
+
+impl Drop for PendingExecution {
+    fn drop(&mut self) {
+        let entry = match self.entry.take() {
+            Some(entry) => entry,
+            None => return,
+        };
+        let failure_notification = self.failure_notification.take();
+        let stay_in_mode = self.stay_in_mode;
+        // Runs on its own thread since this may be dropped inside the executor of the window
+        let result = std::thread::spawn(move || {
+            let result = match tokio::runtime::Runtime::new() {
+                Ok(runtime) => runtime.block_on(executor::execute(&entry, stay_in_mode)),
+                Err(error) => Err(i3_config::I3ConfigError::FailedSpawn(std::sync::Arc::new(
+                    error,
+                ))),
+            };
+            if let (Err(error), Some(summary)) = (&result, failure_notification) {
+                notify_failure(&summary, &presenter::error_chain(error));
+            }
+            result
+        })
+        .join();
+        let exit_code = match result {
+            Ok(Ok(())) => return,
+            Ok(Err(error)) => {
+                eprintln!("Failed to run binding: {}", presenter::error_chain(&error));
+                error.exit_code()
+            }
+            // The panic message was already printed, exit like the panic would have
+            Err(_) => 101,
+        };
+        EXIT_CODE.store(exit_code, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// Exit status of the binding run after the window closed
+static EXIT_CODE: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(0);
+
+/// Applies the exit status of the binding once the state is dropped.
+/// The event loop ends the process with 0 right after and never returns to `main`
+#[derive(Debug)]
+struct ExitStatus;
+
+impl Drop for ExitStatus {
+    fn drop(&mut self) {
+        let exit_code = EXIT_CODE.load(std::sync::atomic::Ordering::SeqCst);
+        if exit_code != 0 {
+            std::process::exit(exit_code);
+        }
+    }
+}
+
+/// The window is gone once the binding runs, so its errors would only reach stderr
+fn notify_failure(summary: &str, body: &str) {
+    let shown = notify_rust::Notification::new()
+        .appname("i3-conf-searcher")
+        .summary(summary)
+        .body(body)
+        .show();
+    if let Err(error) = shown {
+        eprintln!("Failed to show notification: {}", error);
+    }
+}
+
+/// Failure of running the selected binding, stays until dismissed
+#[derive(Debug)]
+struct ErrorBanner {
+    text: String,
+    dismiss_button: button::State,
+}
+
+/// Countdown before the only matching entry runs in quick mode
+#[derive(Debug)]
+struct QuickRun {
+    id: usize,
+    remaining: u32,
+}
+
+const QUICK_RUN_STEPS: u32 = 4;
+const QUICK_RUN_STEP: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Short lived notification shown under the search bar
+#[derive(Debug)]
+struct Toast {
+    id: usize,
+    text: String,
+    is_error: bool,
+}
+
+const TOAST_DURATION: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Runs `i3 -C` on its own thread since it waits for the process
+async fn validate_config(path: Option<std::path::PathBuf>) -> Result<(), i3_config::I3ConfigError> {
+    tokio::task::spawn_blocking(move || validation::validate(path.as_deref()))
+        .await
+        .unwrap_or_else(|error| {
+            Err(i3_config::I3ConfigError::FailedSpawn(std::sync::Arc::new(
+                std::io::Error::other(error),
+            )))
+        })
+}
+
+/// Checks the config file an edit was written to
+fn validate_edit(path: Option<std::path::PathBuf>) -> Command<Message> {
+    match path {
+        Some(path) => Command::perform(validate_config(Some(path)), Message::EditValidated),
+        None => Command::none(),
+    }
+}
+
+fn quick_run_step(id: usize) -> Command<Message> {
+    Command::perform(tokio::time::sleep(QUICK_RUN_STEP), move |_| {
+        Message::QuickRunStep(id)
+    })
+}
+
+impl ApplicationState {
+    fn new(flags: InitFlags) -> ApplicationState {
+        ApplicationState {
+            theme: flags.theme,
+            density: flags.density,
+            collapse_duplicates: flags.collapse_duplicates,
+            exit_on_focus_loss: flags.exit_on_focus_loss,
+            focus_loss_grace: flags.focus_loss_grace,
+            focus_lost: None,
+            focus_loss_count: 0,
+            window_width: iced::window::Settings::default().size.0,
+            group_sidebar: flags.group_sidebar,
+            order: flags.order,
+            min_score: flags.min_score,
+            matcher: flags.matcher,
+            search_text: flags.search_text.clone(),
+            editor: flags.editor.clone(),
+            strict_modifiers: flags.strict_modifiers,
+            mode: flags.mode.clone(),
+            source_badges: flags.source_badges,
+            live_targets: None,
+            sources: flags.sources.clone(),
+            load_stage: match flags.sources {
+                loader::SourceOptions {
+                    config: Some(_), ..
+                } => loader::LoadStage::Reading,
+                loader::SourceOptions { git: Some(_), .. } => loader::LoadStage::Syncing,
+                loader::SourceOptions { url: Some(_), .. } => loader::LoadStage::Downloading,
+                _ => loader::LoadStage::Connecting,
+            },
+            spinner_frame: 0,
+            state: Searcher::Loading,
+            modifier_state: i3_config::Modifiers::default(),
+            toast: None,
+            toast_count: 0,
+            execution_error: None,
+            quick: flags.quick,
+            auto_height: flags.auto_height,
+            opacity: flags.opacity,
+            animations: Animations::new(flags.animations),
+            announce: flags.announce,
+            announced: None,
+            localizer: Localizer::detect().unwrap_or_else(|error| {
+                eprintln!("{}", presenter::error_chain(&error));
+                Localizer::new("C")
+            }),
+            requested_height: None,
+            quick_run: None,
+            quick_run_count: 0,
+            geometry_saved: false,
+            exiting: false,
+            _keyboard_grab: if flags.grab_keyboard {
+                // Searching still works without the grab
+                KeyboardGrab::new()
+                    .map_err(|error| eprintln!("{}", presenter::error_chain(&error)))
+                    .ok()
+            } else {
+                None
+            },
+            _instance_lock: flags.instance_lock,
+            pending_execution: PendingExecution::default(),
+            notify_failures: flags.notify_failures,
+            usage_stats: flags.usage_stats,
+            hidden_entries: flags.hidden_entries,
+            stay_in_mode: flags.stay_in_mode,
+            opened: std::time::Instant::now(),
+            search_started: None,
+            _exit_status: ExitStatus,
+        }
+    }
+
+    fn show_toast(&mut self, text: String, is_error: bool) -> Command<Message> {
+        self.toast_count += 1;
+        let id = self.toast_count;
+        self.toast = Some(Toast { id, text, is_error });
+        Command::perform(tokio::time::sleep(TOAST_DURATION), move |_| {
+            Message::ToastExpired(id)
+        })
+    }
+
+    /// Closes the window and runs the command of the selected binding once it's gone,
+    /// so commands like `move container` don't act on the searcher itself.
+    /// i3 is checked beforehand so an unreachable i3 is still shown in the window.
+    /// Entries without a binding just close the searcher
+    fn execute_selected(&mut self) -> Command<Message> {
+        let entry = match &mut self.state {
+            Searcher::Searching(state) => state.selected_entry(),
+            _ => None,
+        };
+        match entry {
+            Some(entry) if entry.command().is_some() => {
+                let failure_notification = if self.notify_failures {
+                    Some(self.localizer.format(
+                        "run-failed-notification",
+                        vec![("description", entry.description().into())],
+                    ))
+                } else {
+                    None
+                };
+                if self.usage_stats {
+                    let selection_time = self.search_started.unwrap_or(self.opened).elapsed();
+                    if let Err(error) = usage::UsageStats::record_run(&entry, selection_time) {
+                        eprintln!("{}", presenter::error_chain(&error));
+                    }
+                }
+                self.pending_execution = PendingExecution {
+                    entry: Some(entry),
+                    failure_notification,
+                    stay_in_mode: self.stay_in_mode,
+                };
+                Command::perform(executor::ping(), |result| match result {
+                    Ok(()) => Message::Exit,
+                    Err(error) => Message::ExecutionFailed(presenter::error_chain(&error)),
+                })
+            }
+            _ => self.exit(),
+        }
+    }
+
+    /// Starts the quick mode countdown if the query narrowed the list to one runnable entry.
+    /// Any change to the query or filters restarts or cancels it
+    fn schedule_quick_run(&mut self) -> Command<Message> {
+        self.quick_run = None;
+        let unique = match &mut self.state {
+            Searcher::Searching(state) if self.quick && !state.search_string.trim().is_empty() => {
+                // Called right after the query changed, before `update` refreshes the matches
+                state.refresh(&self.modifier_state);
+                state.has_unique_runnable_match()
+            }
+            _ => false,
+        };
+        if !unique {
+            return Command::none();
+        }
+        self.quick_run_count += 1;
+        let id = self.quick_run_count;
+        self.quick_run = Some(QuickRun {
+            id,
+            remaining: QUICK_RUN_STEPS,
+        });
+        quick_run_step(id)
+    }
+
+    fn open_action_menu(&mut self) {
+        if let Searcher::Searching(state) = &mut self.state {
+            if state.selected_entry().is_some() {
+                state.action_menu = Some(ActionMenu::default());
+            }
+        }
+    }
+
+    fn run_action(
+        &mut self,
+        action: EntryAction,
+        clipboard: &mut dyn iced_native::Clipboard,
+    ) -> Command<Message> {
+        let entry = match &mut self.state {
+            Searcher::Searching(state) => {
+                state.action_menu = None;
+                match state.selected_entry() {
+                    Some(entry) => entry,
+                    None => return Command::none(),
+                }
+            }
+            _ => return Command::none(),
+        };
+        match action {
+            EntryAction::Run => self.execute_selected(),
+            EntryAction::Preview => match entry.command() {
+                Some(command) => {
+                    Command::perform(preview::preview(command.to_owned()), Message::PreviewReady)
+                }
+                None => self.show_toast(self.localizer.text("no-command"), true),
+            },
+            EntryAction::CopyKeys => {
+                clipboard.write(entry.keys().to_owned());
+                self.show_toast(self.localizer.text("copied-keys"), false)
+            }
+            EntryAction::CopyCommand => match entry.command() {
+                Some(command) => {
+                    clipboard.write(command.to_owned());
+                    self.show_toast(self.localizer.text("copied-command"), false)
+                }
+                None => self.show_toast(self.localizer.text("no-command"), true),
+            },
+            EntryAction::OpenInEditor => self.open_selected_in_editor(),
+            EntryAction::Hide => {
+                if let Searcher::Searching(state) = &mut self.state {
+                    state.shortcuts.hide_entry(entry.id());
+                    // The cached positions are off once the entry is gone
+                    state.invalidate();
+                    state.refresh(&self.modifier_state);
+                }
+                let remembered = match &self.hidden_entries {
+                    Some(path) => hidden::HiddenEntries::remember(path, &entry),
+                    None => Ok(()),
+                };
+                match remembered {
+                    Ok(()) => self.show_toast(self.localizer.text("entry-hidden"), false),
+                    Err(error) => self.show_toast(presenter::error_chain(&error), true),
+                }
+            }
+        }
+    }
+
+    fn open_selected_in_editor(&mut self) -> Command<Message> {
+        let opened = match &self.state {
+            Searcher::Searching(state) => {
+                let line = match state.selected_entry() {
+                    Some(entry) if state.shortcuts.is_editable(&entry) => entry.line(),
+                    Some(_) => {
+                        return self.show_toast(self.localizer.text("not-in-config"), true);
+                    }
+                    None => return Command::none(),
+                };
+                match state.shortcuts.source_path() {
+                    Some(path) => editor::open_at_line(self.editor.as_deref(), path, line),
+                    None => return self.show_toast(self.localizer.text("not-local"), true),
+                }
+            }
+            _ => return Command::none(),
+        };
+        match opened {
+            Ok(()) => Command::none(),
+            Err(error) => {
+                let text = self.localizer.format(
+                    "editor-failed",
+                    vec![("error", presenter::error_chain(&error).into())],
+                );
+                self.show_toast(text, true)
+            }
+        }
+    }
+
+    fn start_editing(&mut self) -> Command<Message> {
+        let state = match std::mem::replace(&mut self.state, Searcher::Loading) {
+            Searcher::Searching(state) => state,
+            other => {
+                self.state = other;
+                return Command::none();
+            }
+        };
+        if state.shortcuts.source_path().is_none() {
+            self.state = Searcher::Searching(state);
+            return self.show_toast(self.localizer.text("not-local"), true);
+        }
+        self.state = match state.selected_entry() {
+            Some(entry) if !state.shortcuts.is_editable(&entry) => {
+                self.state = Searcher::Searching(state);
+                return self.show_toast(self.localizer.text("imported-not-editable"), true);
+            }
+            Some(entry) => Searcher::Editing(state, Box::new(EditForm::new(&entry))),
+            None => Searcher::Searching(state),
+        };
+        Command::none()
+    }
+
+    fn start_creating(&mut self) -> Command<Message> {
+        let state = match std::mem::replace(&mut self.state, Searcher::Loading) {
+            Searcher::Searching(state) => state,
+            other => {
+                self.state = other;
+                return Command::none();
+            }
+        };
+        if state.shortcuts.source_path().is_none() {
+            self.state = Searcher::Searching(state);
+            return self.show_toast(self.localizer.text("not-local"), true);
+        }
+        self.state = Searcher::Creating(state, Box::new(NewBindingForm::new()));
+        Command::none()
+    }
+
+    fn stop_editing(&mut self) {
+        self.state = match std::mem::replace(&mut self.state, Searcher::Loading) {
+            Searcher::Editing(state, _) | Searcher::Creating(state, _) => {
+                Searcher::Searching(state)
+            }
+            other => other,
+        };
+    }
+
+    fn save_edit(&mut self) -> Command<Message> {
+        let mut edited = None;
+        if let Searcher::Editing(state, form) = &mut self.state {
+            if let Err(error) = config_writer::rewrite_annotation(
+                &mut state.shortcuts,
+                form.line,
+                &form.group,
+                &form.description,
+            ) {
+                form.error = Some(presenter::error_chain(&error));
+                return Command::none();
+            }
+            edited = state.shortcuts.source_path().map(ToOwned::to_owned);
+        }
+        self.stop_editing();
+        let toast = self.show_toast(self.localizer.text("annotation-saved"), false);
+        Command::batch(vec![toast, validate_edit(edited)])
+    }
+
+    fn save_new_binding(&mut self) -> Command<Message> {
+        let mut edited = None;
+        if let Searcher::Creating(state, form) = &mut self.state {
+            // Duplicates are reported first, saving again adds the binding anyway
+            if form.conflicts.is_empty() {
+                form.conflicts = state
+                    .shortcuts
+                    .entries_bound_to(&form.keys)
+                    .iter()
+                    .map(|entry| format!("{} // {}", entry.group(), entry.description()))
+                    .collect();
+                if !form.conflicts.is_empty() {
+                    return Command::none();
+                }
+            }
+            if let Err(error) = config_writer::append_binding(
+                &mut state.shortcuts,
+                &form.group,
+                &form.description,
+                &form.keys,
+                &form.command,
+            ) {
+                form.error = Some(presenter::error_chain(&error));
+                return Command::none();
+            }
+            edited = state.shortcuts.source_path().map(ToOwned::to_owned);
+        }
+        self.stop_editing();
+        let toast = self.show_toast(self.localizer.text("binding-added"), false);
+        Command::batch(vec![toast, validate_edit(edited)])
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    scroll: scrollable::State,
+    search_string: String,
+    text_input_state: text_input::State,
+    shortcuts: i3_config::ConfigMetadata,
+    warnings_button: button::State,
+    show_warnings: bool,
+    selection: usize,
+    /// Entry moved to with the arrow keys, stays selected while the matches change around it
+    selected: Option<i3_config::EntryId>,
+    show_details: bool,
+    action_menu: Option<ActionMenu>,
+    /// Only entries of this group are searched
+    group: Option<String>,
+    show_groups: bool,
+    /// F1 help listing the controls and colors, covers the results while shown
+    show_help: bool,
+    capture: Capture,
+    /// Only entries bound to this key combination are searched, set with Ctrl+K
+    chord: Option<i3_config::KeyCombo>,
+    /// Positions of the shown entries in the config, refreshed by `update` so `view` only reads them
+    matches: Vec<usize>,
+    /// Inputs of the last filter pass, the entries are only filtered again once they change
+    filtered: Option<FilterKey>,
+    #[cfg(test)]
+    filter_passes: usize,
+    group_buttons: Vec<button::State>,
+    chip_buttons: [button::State; 3],
+}
+
+/// Everything besides the entries themselves that decides which of them are shown
+#[derive(Debug, Clone, PartialEq)]
+struct FilterKey {
+    query: String,
+    modifiers: i3_config::Modifiers,
+    group: Option<String>,
+    chord: Option<i3_config::KeyCombo>,
+}
+
+/// Ctrl+K waits for the next key combination instead of typing it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Capture {
+    Off,
+    Waiting,
+    /// The combination was taken, text input is ignored until its key is released
+    Captured,
+}
+
+/// Restriction of the results that can be removed from the chips above the list
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ActiveFilter {
+    Group,
+    StrictModifiers,
+    Chord,
+}
+
+/// Things that can be done with the selected entry from its action menu
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EntryAction {
+    Run,
+    Preview,
+    CopyKeys,
+    CopyCommand,
+    OpenInEditor,
+    Hide,
+}
+
+impl EntryAction {
+    const ALL: [EntryAction; 6] = [
+        EntryAction::Run,
+        EntryAction::Preview,
+        EntryAction::CopyKeys,
+        EntryAction::CopyCommand,
+        EntryAction::OpenInEditor,
+        EntryAction::Hide,
+    ];
+
+    fn label(self, localizer: &Localizer) -> String {
+        match self {
+            EntryAction::Run => localizer.text("action-run"),
+            EntryAction::Preview => localizer.text("action-preview"),
+            EntryAction::CopyKeys => localizer.text("action-copy-keys"),
+            EntryAction::CopyCommand => localizer.text("action-copy-command"),
+            EntryAction::OpenInEditor => localizer.text("action-open-in-editor"),
+            EntryAction::Hide => localizer.text("action-hide"),
+        }
+    }
+}
+
+/// Controls of the window bound to a key in [`KEYMAP`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Control {
+    ToggleHelp,
+    SelectNext,
+    SelectPrevious,
+    ToggleDetails,
+    ShowDetails,
+    HideDetails,
+    ToggleGroups,
+    EditAnnotation,
+    NewBinding,
+    OpenInEditor,
+    ReloadI3,
+    CycleDensity,
+    CaptureKeys,
+}
+
+impl Control {
+    fn label(self, localizer: &Localizer) -> String {
+        match self {
+            Control::ToggleHelp => localizer.text("control-toggle-help"),
+            Control::SelectNext => localizer.text("control-select-next"),
+            Control::SelectPrevious => localizer.text("control-select-previous"),
+            Control::ToggleDetails => localizer.text("control-toggle-details"),
+            Control::ShowDetails => localizer.text("control-show-details"),
+            Control::HideDetails => localizer.text("control-hide-details"),
+            Control::ToggleGroups => localizer.text("control-toggle-groups"),
+            Control::EditAnnotation => localizer.text("control-edit-annotation"),
+            Control::NewBinding => localizer.text("control-new-binding"),
+            Control::OpenInEditor => localizer.text("control-open-in-editor"),
+            Control::ReloadI3 => localizer.text("control-reload-i3"),
+            Control::CycleDensity => localizer.text("control-cycle-density"),
+            Control::CaptureKeys => localizer.text("control-capture-keys"),
+        }
+    }
+}
+
+/// Key of a control, the modifiers that are set have to be held
+struct KeyBinding {
+    key: KeyCode,
+    control: bool,
+    shift: bool,
+    action: Control,
+}
+
+impl KeyBinding {
+    const fn new(key: KeyCode, action: Control) -> Self {
+        KeyBinding {
+            key,
+            control: false,
+            shift: false,
+            action,
+        }
+    }
+
+    const fn with_control(mut self) -> Self {
+        self.control = true;
+        self
+    }
+
+    const fn with_shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    fn matches(&self, key: KeyCode, modifiers: keyboard::Modifiers) -> bool {
+        self.key == key && (!self.control || modifiers.control) && (!self.shift || modifiers.shift)
+    }
+
+    /// Keys as shown in the help, e.g. `Ctrl+Shift+R`
+    fn keys(&self) -> String {
+        let mut keys = String::new();
+        if self.control {
+            keys.push_str("Ctrl+");
+        }
+        if self.shift {
+            keys.push_str("Shift+");
+        }
+        keys + &format!("{:?}", self.key)
+    }
+}
+
+/// Keys handled by the window, the F1 help is generated from this table
+const KEYMAP: [KeyBinding; 13] = [
+    KeyBinding::new(KeyCode::F1, Control::ToggleHelp),
+    KeyBinding::new(KeyCode::Down, Control::SelectNext),
+    KeyBinding::new(KeyCode::Up, Control::SelectPrevious),
+    KeyBinding::new(KeyCode::Tab, Control::ToggleDetails),
+    KeyBinding::new(KeyCode::Right, Control::ShowDetails),
+    KeyBinding::new(KeyCode::Left, Control::HideDetails),
+    KeyBinding::new(KeyCode::F3, Control::ToggleGroups),
+    KeyBinding::new(KeyCode::F2, Control::EditAnnotation),
+    KeyBinding::new(KeyCode::N, Control::NewBinding).with_control(),
+    KeyBinding::new(KeyCode::E, Control::OpenInEditor).with_control(),
+    KeyBinding::new(KeyCode::R, Control::ReloadI3)
+        .with_control()
+        .with_shift(),
+    KeyBinding::new(KeyCode::D, Control::CycleDensity).with_control(),
+    KeyBinding::new(KeyCode::K, Control::CaptureKeys).with_control(),
+];
+
+/// Controls handled by the search input, mouse or a range of keys, listed after [`KEYMAP`]
+/// with the message describing them
+const OTHER_CONTROLS: [(&str, &str); 4] = [
+    ("Enter", "control-run"),
+    ("Shift+Enter", "control-actions"),
+    ("Ctrl+1..9", "control-search-group"),
+    ("Escape", "control-close"),
+];
+
+/// Menu shown under the selected entry with Shift+Enter or a right click
+#[derive(Debug, Default)]
+struct ActionMenu {
+    selection: usize,
+    buttons: [button::State; EntryAction::ALL.len()],
+}
+
+impl State {
+    pub fn new(config: i3_config::ConfigMetadata, show_groups: bool) -> State {
+        // One more for showing all groups
+        let group_buttons = (0..=config.groups().len())
+            .map(|_| button::State::new())
+            .collect();
+        State {
+            scroll: scrollable::State::new(),
+            search_string: String::from(""),
+            text_input_state: text_input::State::focused(),
+            shortcuts: config,
+            warnings_button: button::State::new(),
+            show_warnings: false,
+            selection: 0,
+            selected: None,
+            show_details: false,
+            action_menu: None,
+            group: None,
+            show_groups,
+            show_help: false,
+            capture: Capture::Off,
+            chord: None,
+            matches: Vec::new(),
+            filtered: None,
+            #[cfg(test)]
+            filter_passes: 0,
+            group_buttons,
+            chip_buttons: Default::default(),
+        }
+    }
+
+    /// Filters the entries again if the query, modifiers or filters changed since the last pass
+    /// and keeps the selection inside the matches, returns whether other entries are shown
+    fn refresh(&mut self, modifiers: &i3_config::Modifiers) -> bool {
+        let key = FilterKey {
+            query: self.search_string.clone(),
+            modifiers: modifiers.clone(),
+            group: self.group.clone(),
+            chord: self.chord.clone(),
+        };
+        if self.filtered.as_ref() == Some(&key) {
+            return false;
+        }
+        let matches = self.filter(modifiers);
+        let changed = matches != self.matches;
+        self.matches = matches;
+        self.filtered = Some(key);
+        let entries = self.shortcuts.entries();
+        let matches = &self.matches;
+        let kept = self
+            .selected
+            .and_then(|id| matches.iter().position(|index| entries[*index].id() == id));
+        self.selection =
+            kept.unwrap_or_else(|| self.selection.min(matches.len().saturating_sub(1)));
+        changed
+    }
+
+    /// Filters on the next refresh even if the query and filters stayed the same,
+    /// needed once the entries themselves changed
+    fn invalidate(&mut self) {
+        self.filtered = None;
+    }
+
+    /// Selects the first match once the query or filters changed
+    fn reset_selection(&mut self) {
+        self.selection = 0;
+        self.selected = None;
+    }
+
+    fn select(&mut self, selection: usize) {
+        self.selection = selection;
+        let entries = self.shortcuts.entries();
+        self.selected = self
+            .matches
+            .get(selection)
+            .map(|index| entries[*index].id());
+    }
+
+    fn filter(&mut self, modifiers: &i3_config::Modifiers) -> Vec<usize> {
+        #[cfg(test)]
+        {
+            self.filter_passes += 1;
+        }
+        let matches = self
+            .shortcuts
+            .filter_indices(&self.search_string, modifiers);
+        let group = self.group.as_deref();
+        let chord = self.chord.as_ref();
+        let entries = self.shortcuts.entries();
+        matches
+            .into_iter()
+            .filter(|index| {
+                let entry = &entries[*index];
+                group.is_none_or(|group| entry.is_in_group(group))
+                    && chord.is_none_or(|chord| entry.is_bound_to(chord))
+            })
+            .collect()
+    }
+
+    /// Entries of the last refresh
+    fn matching_entries(&self) -> Vec<&i3_config::ConfigEntry> {
+        let entries = self.shortcuts.entries();
+        self.matches.iter().map(|index| &entries[*index]).collect()
+    }
+
+    /// Whether quick mode may run the only match
+    fn has_unique_runnable_match(&self) -> bool {
+        let entries = self.matching_entries();
+        entries.len() == 1 && entries[0].command().is_some()
+    }
+
+    fn select_group(&mut self, group: Option<String>) {
+        self.group = group;
+        self.reset_selection();
+        self.scroll = scrollable::State::new();
+        self.action_menu = None;
+    }
+
+    fn select_next(&mut self) {
+        if self.selection + 1 < self.matches.len() {
+            self.select(self.selection + 1);
+        }
+    }
+
+    fn select_previous(&mut self) {
+        self.select(self.selection.saturating_sub(1));
+    }
+
+    fn selected_entry(&self) -> Option<i3_config::ConfigEntry> {
+        self.matches
+            .get(self.selection)
+            .map(|index| self.shortcuts.entries()[*index].clone())
+    }
+}
+
+/// Form for changing group and description of an annotation
+#[derive(Debug)]
+struct EditForm {
+    line: usize,
+    keys: String,
+    group: String,
+    description: String,
+    group_input: text_input::State,
+    description_input: text_input::State,
+    save_button: button::State,
+    cancel_button: button::State,
+    error: Option<String>,
+}
+
+impl EditForm {
+    fn new(entry: &i3_config::ConfigEntry) -> Self {
+        EditForm {
+            line: entry.line(),
+            keys: entry.keys().to_owned(),
+            group: entry.group().to_owned(),
+            description: entry.description().to_owned(),
+            group_input: text_input::State::focused(),
+            description_input: text_input::State::new(),
+            save_button: button::State::new(),
+            cancel_button: button::State::new(),
+            error: None,
+        }
+    }
+
+    fn toggle_focus(&mut self) {
+        if self.group_input.is_focused() {
+            self.group_input.unfocus();
+            self.description_input.focus();
+        } else {
+            self.description_input.unfocus();
+            self.group_input.focus();
+        }
+    }
+}
+
+/// Field of the new binding form
+#[derive(Debug, Clone, Copy)]
+enum BindingField {
+    Group,
+    Description,
+    Keys,
+    Command,
+}
+
+/// Form for appending a new annotated binding to the config
+#[derive(Debug)]
+struct NewBindingForm {
+    group: String,
+    description: String,
+    keys: String,
+    command: String,
+    inputs: [text_input::State; 4],
+    save_button: button::State,
+    cancel_button: button::State,
+    /// Entries already bound to the entered keys
+    conflicts: Vec<String>,
+    error: Option<String>,
+}
+
+impl NewBindingForm {
+    fn new() -> Self {
+        NewBindingForm {
+            group: String::new(),
+            description: String::new(),
+            keys: String::new(),
+            command: String::new(),
+            inputs: [
+                text_input::State::focused(),
+                text_input::State::new(),
+                text_input::State::new(),
+                text_input::State::new(),
+            ],
+            save_button: button::State::new(),
+            cancel_button: button::State::new(),
+            conflicts: vec![],
+            error: None,
+        }
+    }
+
+    fn set_field(&mut self, field: BindingField, value: String) {
+        match field {
+            BindingField::Group => self.group = value,
+            BindingField::Description => self.description = value,
+            BindingField::Keys => {
+                self.keys = value;
+                self.conflicts.clear();
+            }
+            BindingField::Command => self.command = value,
+        }
+    }
+
+    fn focus_next(&mut self) {
+        let focused = self.inputs.iter().position(|input| input.is_focused());
+        let next = focused.map_or(0, |index| (index + 1) % self.inputs.len());
+        for input in &mut self.inputs {
+            input.unfocus();
+        }
+        self.inputs[next].focus();
+    }
+}
+
+#[derive(Debug)]
+enum Searcher {
+    Loading,
+    Searching(State),
+    Editing(State, Box<EditForm>),
+    Creating(State, Box<NewBindingForm>),
+    Error(String),
+    UnsupportedPlatform,
+}
+
+#[derive(Debug, Clone)]
+enum Message {
+    LoadProgressed(loader::LoadProgress),
+    SpinnerTick,
+    /// Only redraws, sent while an animation is running
+    AnimationFrame,
+    InputChanged(String),
+    ToggleWarnings,
+    I3Reloaded(Result<(), i3_config::I3ConfigError>),
+    PreviewReady(Result<Vec<preview::Effect>, i3_config::I3ConfigError>),
+    /// `i3 -C` finished on the edited config
+    EditValidated(Result<(), i3_config::I3ConfigError>),
+    TargetsResolved(Result<targets::LiveTargets, i3_config::I3ConfigError>),
+    ExecuteSelected,
+    ExecutionFailed(String),
+    DismissError,
+    ToastExpired(usize),
+    FocusLossExpired(usize),
+    QuickRunStep(usize),
+    RunAction(EntryAction),
+    SelectGroup(Option<String>),
+    RemoveFilter(ActiveFilter),
+    HeightFitted,
+    WindowPlaced,
+    OverrideRedirectApplied(Result<(), i3_config::I3ConfigError>),
+    EditGroupChanged(String),
+    EditDescriptionChanged(String),
+    SaveEdit,
+    NewBindingChanged(BindingField, String),
+    SaveNewBinding,
+    CancelEdit,
+    Exit,
+    Signal(Signal),
+    ConfigChanged(mode_events::ConfigChange),
+    EventOccurred(iced_native::Event),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Signal {
+    /// SIGHUP
+    Reload,
+    /// SIGTERM or SIGINT
+    Terminate,
+}
+
+/// Reloads and shutdowns of i3, nothing is sent without IPC
+struct ConfigWatcher;
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for ConfigWatcher
+where
+    H: std::hash::Hasher,
+{
+    type Output = mode_events::ConfigChange;
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+        std::any::TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: futures::stream::BoxStream<'static, I>,
+    ) -> futures::stream::BoxStream<'static, Self::Output> {
+        use futures::StreamExt;
+        futures::stream::once(mode_events::config_changes())
+            .flat_map(|changes| match changes {
+                Ok(changes) => changes.filter_map(|change| async { change.ok() }).boxed(),
+                Err(_) => futures::stream::empty().boxed(),
+            })
+            .boxed()
+    }
+}
+
+/// Unix signals sent to the running window
+struct SignalListener;
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for SignalListener
+where
+    H: std::hash::Hasher,
+{
+    type Output = Signal;
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+        std::any::TypeId::of::<Self>().hash(state);
+    }
+
+    #[cfg(unix)]
+    fn stream(
+        self: Box<Self>,
+        _input: futures::stream::BoxStream<'static, I>,
+    ) -> futures::stream::BoxStream<'static, Self::Output> {
+        use futures::StreamExt;
+        use tokio::signal::unix::{signal, SignalKind};
+        let signals = vec![
+            (SignalKind::hangup(), Signal::Reload),
+            (SignalKind::terminate(), Signal::Terminate),
+            (SignalKind::interrupt(), Signal::Terminate),
+        ];
+        // Handlers are installed once the stream is polled inside the tokio runtime
+        futures::stream::once(async move {
+            futures::stream::select_all(signals.into_iter().filter_map(|(kind, output)| {
+                let mut listener = signal(kind).ok()?;
+                Some(futures::stream::poll_fn(move |context| {
+                    listener
+                        .poll_recv(context)
+                        .map(|received| received.map(|()| output))
+                }))
+            }))
+        })
+        .flatten()
+        .boxed()
+    }
+
+    #[cfg(not(unix))]
+    fn stream(
+        self: Box<Self>,
+        _input: futures::stream::BoxStream<'static, I>,
+    ) -> futures::stream::BoxStream<'static, Self::Output> {
+        Box::pin(futures::stream::empty())
+    }
+}
+
+/// Runs the staged config loading for the loading screen
+struct ConfigLoader {
+    sources: loader::SourceOptions,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for ConfigLoader
+where
+    H: std::hash::Hasher,
+{
+    type Output = loader::LoadProgress;
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+        std::any::TypeId::of::<Self>().hash(state);
+        self.sources.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: futures::stream::BoxStream<'static, I>,
+    ) -> futures::stream::BoxStream<'static, Self::Output> {
+        Box::pin(loader::load_in_stages(self.sources))
+    }
+}
+
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const SPINNER_INTERVAL: std::time::Duration = std::time::Duration::from_millis(80);
+/// Time i3 gets to come back before the config is loaded over IPC again
+const I3_RESTART_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+impl ApplicationState {
+    fn handle_message(
+        &mut self,
+        message: Message,
+        clipboard: &mut dyn iced_native::Clipboard,
+    ) -> Command<Message> {
+        match message {
+            Message::LoadProgressed(loader::LoadProgress::Stage(stage)) => {
+                self.load_stage = stage;
+                Command::none()
+            }
+            Message::LoadProgressed(loader::LoadProgress::Finished(Ok(config))) => {
+                let mut config = *config;
+                config.set_strict_modifiers(self.strict_modifiers);
+                config.set_order(self.order);
+                config.set_min_score(self.min_score);
+                config.set_matcher(self.matcher);
+                config.set_search_text(self.search_text.clone());
+                if let Some(mode) = &self.mode {
+                    config.retain_mode(mode);
+                }
+                // An unreadable file shows all entries instead of keeping the searcher closed
+                if let Some(path) = &self.hidden_entries {
+                    let hidden = hidden::HiddenEntries::load(path).unwrap_or_default();
+                    config.hide_entries(|entry| hidden.contains(entry));
+                }
+                if self.collapse_duplicates {
+                    config.collapse_duplicates();
+                }
+                self.state = Searcher::Searching(State::new(config, self.group_sidebar));
+                Command::perform(targets::query(), Message::TargetsResolved)
+            }
+            Message::TargetsResolved(targets) => {
+                // Without i3 running targets just aren't checked
+                self.live_targets = targets.ok();
+                Command::none()
+            }
+            Message::LoadProgressed(loader::LoadProgress::Finished(Err(error))) => {
+                self.state = match error {
+                    i3_config::I3ConfigError::UnsupportedPlatform => Searcher::UnsupportedPlatform,
+                    _ => Searcher::Error(self.localizer.format(
+                        "stage-failed",
+                        vec![
+                            (
+                                "stage",
+                                self.localizer.text(stage_message(self.load_stage)).into(),
+                            ),
+                            ("error", presenter::error_chain(&error).into()),
+                        ],
+                    )),
+                };
+                Command::none()
+            }
+            Message::AnimationFrame => Command::none(),
+            Message::SpinnerTick => {
+                self.spinner_frame = (self.spinner_frame + 1) % SPINNER_FRAMES.len();
+                Command::none()
+            }
+            Message::InputChanged(input) => match &mut self.state {
+                // The key of a captured combination isn't typed
+                Searcher::Searching(state) if state.capture != Capture::Off => Command::none(),
+                Searcher::Searching(state) => {
+                    state.scroll = scrollable::State::new();
+                    state.search_string = input;
+                    state.reset_selection();
+                    state.action_menu = None;
+                    self.search_started
+                        .get_or_insert_with(std::time::Instant::now);
+                    self.schedule_quick_run()
+                }
+                _ => Command::none(),
+            },
+            Message::QuickRunStep(id) => match &mut self.quick_run {
+                Some(run) if run.id == id => {
+                    run.remaining -= 1;
+                    if run.remaining > 0 {
+                        return quick_run_step(id);
+                    }
+                    self.quick_run = None;
+                    // Filters changed while counting down can show more entries again
+                    match &self.state {
+                        Searcher::Searching(state) if state.has_unique_runnable_match() => {
+                            self.execute_selected()
+                        }
+                        _ => Command::none(),
+                    }
+                }
+                _ => Command::none(),
+            },
+            Message::ToggleWarnings => {
+                if let Searcher::Searching(state) = &mut self.state {
+                    state.show_warnings = !state.show_warnings;
+                }
+                Command::none()
+            }
+            Message::I3Reloaded(Ok(())) => {
+                self.show_toast(self.localizer.text("config-reloaded"), false)
+            }
+            Message::I3Reloaded(Err(error)) | Message::EditValidated(Err(error)) => {
+                self.show_toast(presenter::error_chain(&error), true)
+            }
+            Message::EditValidated(Ok(())) => Command::none(),
+            Message::PreviewReady(Ok(effects)) => {
+                self.show_toast(explanation(&effects, &self.localizer), false)
+            }
+            Message::PreviewReady(Err(error)) => {
+                self.show_toast(presenter::error_chain(&error), true)
+            }
+            Message::ToastExpired(id) => {
+                if self.toast.as_ref().map(|toast| toast.id) == Some(id) {
+                    self.toast = None;
+                }
+                Command::none()
+            }
+            Message::EditGroupChanged(group) => {
+                if let Searcher::Editing(_, form) = &mut self.state {
+                    form.group = group;
+                }
+                Command::none()
+            }
+            Message::EditDescriptionChanged(description) => {
+                if let Searcher::Editing(_, form) = &mut self.state {
+                    form.description = description;
+                }
+                Command::none()
+            }
+            Message::SaveEdit => self.save_edit(),
+            Message::NewBindingChanged(field, value) => {
+                if let Searcher::Creating(_, form) = &mut self.state {
+                    form.set_field(field, value);
+                }
+                Command::none()
+            }
+            Message::SaveNewBinding => self.save_new_binding(),
+            Message::CancelEdit => {
+                self.stop_editing();
+                Command::none()
+            }
+            Message::ExecuteSelected => {
+                let menu_selection = match &self.state {
+                    Searcher::Searching(state) => {
+                        state.action_menu.as_ref().map(|menu| menu.selection)
+                    }
+                    _ => None,
+                };
+                match menu_selection {
+                    Some(selection) => self.run_action(EntryAction::ALL[selection], clipboard),
+                    None if self.modifier_state.shift() => {
+                        self.open_action_menu();
+                        Command::none()
+                    }
+                    None => self.execute_selected(),
+                }
+            }
+            Message::RunAction(action) => self.run_action(action, clipboard),
+            // Without i3 the window just keeps its size
+            Message::HeightFitted | Message::WindowPlaced => Command::none(),
+            Message::OverrideRedirectApplied(result) => {
+                // The window stays usable under the window manager
+                if let Err(error) = result {
+                    eprintln!("{}", presenter::error_chain(&error));
+                }
+                Command::none()
+            }
+            Message::RemoveFilter(filter) => {
+                if let Searcher::Searching(state) = &mut self.state {
+                    match filter {
+                        ActiveFilter::Group => state.select_group(None),
+                        ActiveFilter::Chord => {
+                            state.chord = None;
+                            state.capture = Capture::Off;
+                            state.reset_selection();
+                        }
+                        ActiveFilter::StrictModifiers => {
+                            self.strict_modifiers = false;
+                            state.shortcuts.set_strict_modifiers(false);
+                            state.invalidate();
+                            state.reset_selection();
+                        }
+                    }
+                }
+                self.schedule_quick_run()
+            }
+            Message::SelectGroup(group) => {
+                if let Searcher::Searching(state) = &mut self.state {
+                    state.select_group(group);
+                }
+                self.schedule_quick_run()
+            }
+            Message::ExecutionFailed(text) => {
+                self.pending_execution.entry = None;
+                self.execution_error = Some(ErrorBanner {
+                    text,
+                    dismiss_button: button::State::new(),
+                });
+                Command::none()
+            }
+            Message::DismissError => {
+                self.execution_error = None;
+                Command::none()
+            }
+            Message::Exit => self.exit(),
+            Message::Signal(Signal::Reload)
+            | Message::ConfigChanged(mode_events::ConfigChange::Reloaded) => {
+                // Unsaved edits aren't thrown away
+                if let Searcher::Searching(_) | Searcher::Error(_) = self.state {
+                    self.state = Searcher::Loading;
+                }
+                Command::none()
+            }
+            Message::ConfigChanged(mode_events::ConfigChange::Restarting) => {
+                Command::perform(tokio::time::sleep(I3_RESTART_DELAY), |_| {
+                    Message::ConfigChanged(mode_events::ConfigChange::Reloaded)
+                })
+            }
+            Message::ConfigChanged(mode_events::ConfigChange::Exited) => {
+                // There is no i3 left to ask for the geometry
+                self.exiting = true;
+                Command::none()
+            }
+            Message::Signal(Signal::Terminate) => self.exit(),
+            Message::EventOccurred(Keyboard(Event::ModifiersChanged(modifiers))) => {
+                self.modifier_state = held_modifiers(modifiers);
+                Command::none()
+            }
+            Message::EventOccurred(Keyboard(Event::KeyPressed {
+                key_code,
+                modifiers,
+            })) => {
+                let control = KEYMAP
+                    .iter()
+                    .find(|binding| binding.matches(key_code, modifiers))
+                    .map(|binding| binding.action);
+                if let Searcher::Searching(state) = &mut self.state {
+                    // Only the help reacts to keys while it covers the results
+                    if state.show_help {
+                        state.show_help = control != Some(Control::ToggleHelp);
+                        return Command::none();
+                    }
+                    // Escape cancels when it's released
+                    if state.capture == Capture::Waiting && key_code != KeyCode::Escape {
+                        if let Some(key) = keysym(key_code) {
+                            state.chord =
+                                Some(i3_config::KeyCombo::new(held_modifiers(modifiers), key));
+                            state.capture = Capture::Captured;
+                            state.reset_selection();
+                            state.scroll = scrollable::State::new();
+                            return self.schedule_quick_run();
+                        }
+                        return Command::none();
+                    }
+                }
+                match control {
+                    Some(Control::ToggleHelp) => {
+                        if let Searcher::Searching(state) = &mut self.state {
+                            state.show_help = true;
+                            state.action_menu = None;
+                        }
+                    }
+                    Some(Control::SelectNext) => {
+                        if let Searcher::Searching(state) = &mut self.state {
+                            match &mut state.action_menu {
+                                Some(menu) => {
+                                    menu.selection =
+                                        (menu.selection + 1).min(EntryAction::ALL.len() - 1)
+                                }
+                                None => state.select_next(),
+                            }
+                        }
+                    }
+                    Some(Control::SelectPrevious) => {
+                        if let Searcher::Searching(state) = &mut self.state {
+                            match &mut state.action_menu {
+                                Some(menu) => menu.selection = menu.selection.saturating_sub(1),
+                                None => state.select_previous(),
+                            }
+                        }
+                    }
+                    Some(Control::OpenInEditor) => return self.open_selected_in_editor(),
+                    Some(Control::EditAnnotation) => return self.start_editing(),
+                    Some(Control::ToggleGroups) => {
+                        if let Searcher::Searching(state) = &mut self.state {
+                            state.show_groups = !state.show_groups;
+                        }
+                    }
+                    None if modifiers.control && group_number(key_code).is_some() => {
+                        if let Searcher::Searching(state) = &mut self.state {
+                            // Ctrl+0 shows all groups, Ctrl+1 the first one
+                            let group = group_number(key_code)
+                                .and_then(|number| number.checked_sub(1))
+                                .and_then(|index| {
+                                    state
+                                        .shortcuts
+                                        .groups()
+                                        .get(index)
+                                        .map(|(group, _)| group.to_string())
+                                });
+                            state.select_group(group);
+                        }
+                        return self.schedule_quick_run();
+                    }
+                    Some(Control::NewBinding) => return self.start_creating(),
+                    Some(Control::ToggleDetails) => match &mut self.state {
+                        Searcher::Searching(state) => state.show_details = !state.show_details,
+                        Searcher::Editing(_, form) => form.toggle_focus(),
+                        Searcher::Creating(_, form) => form.focus_next(),
+                        _ => (),
+                    },
+                    Some(control @ (Control::ShowDetails | Control::HideDetails)) => {
+                        if let Searcher::Searching(state) = &mut self.state {
+                            state.show_details = control == Control::ShowDetails;
+                        }
+                    }
+                    Some(Control::ReloadI3) => {
+                        // i3 shows its own error bar when reloading a broken config
+                        return Command::perform(
+                            async {
+                                validate_config(None).await?;
+                                executor::reload_i3().await
+                            },
+                            Message::I3Reloaded,
+                        );
+                    }
+                    Some(Control::CycleDensity) => self.density = self.density.next(),
+                    Some(Control::CaptureKeys) => {
+                        if let Searcher::Searching(state) = &mut self.state {
+                            state.capture = Capture::Waiting;
+                            state.action_menu = None;
+                        }
+                    }
+                    None => (),
+                }
+                Command::none()
+            }
+            Message::EventOccurred(Keyboard(Event::KeyReleased {
+                key_code,
+                modifiers,
+            })) => {
+                // This will work because KeyDown will release focus from the text input
+                // and then we get the event here
+                // This may be flaky and in the future this may need a better solution
+                self.modifier_state = held_modifiers(modifiers);
+                if let Searcher::Searching(state) = &mut self.state {
+                    if state.capture == Capture::Captured && keysym(key_code).is_some() {
+                        state.capture = Capture::Off;
+                    }
+                }
+                if key_code == KeyCode::Escape {
+                    if let Searcher::Editing(..) | Searcher::Creating(..) = self.state {
+                        self.stop_editing();
+                    } else if let Searcher::Searching(State {
+                        capture: capture @ Capture::Waiting,
+                        ..
+                    }) = &mut self.state
+                    {
+                        *capture = Capture::Off;
+                    } else if let Searcher::Searching(State {
+                        show_help: show_help @ true,
+                        ..
+                    }) = &mut self.state
+                    {
+                        *show_help = false;
+                    } else if let Searcher::Searching(State {
+                        action_menu: action_menu @ Some(_),
+                        ..
+                    }) = &mut self.state
+                    {
+                        *action_menu = None;
+                    } else if self.quick_run.is_some() {
+                        self.quick_run = None;
+                    } else if self.execution_error.is_some() {
+                        self.execution_error = None;
+                    } else {
+                        return self.exit();
+                    }
+                }
+                Command::none()
+            }
+            Message::EventOccurred(Window(window::Event::Unfocused)) => {
+                // Losing focus before a pending binding ran would drop it
+                if !self.exit_on_focus_loss || self.pending_execution.entry.is_some() {
+                    return Command::none();
+                }
+                // Notifications can take focus for a moment, so give it a chance to come back
+                self.focus_loss_count += 1;
+                let id = self.focus_loss_count;
+                self.focus_lost = Some(id);
+                Command::perform(tokio::time::sleep(self.focus_loss_grace), move |_| {
+                    Message::FocusLossExpired(id)
+                })
+            }
+            Message::EventOccurred(Mouse(mouse::Event::ButtonPressed(mouse::Button::Right))) => {
+                self.open_action_menu();
+                Command::none()
+            }
+            Message::EventOccurred(Window(window::Event::Resized { width, .. })) => {
+                self.window_width = width;
+                Command::none()
+            }
+            Message::EventOccurred(Window(window::Event::Focused)) => {
+                self.focus_lost = None;
+                Command::none()
+            }
+            Message::FocusLossExpired(id) => {
+                if self.focus_lost == Some(id) && self.pending_execution.entry.is_none() {
+                    return self.exit();
+                }
+                Command::none()
+            }
+            Message::EventOccurred(_) => Command::none(),
+        }
+    }
+
+    /// Refreshes the matches for the held modifiers, whether that shows other entries.
+    /// The refresh in `dispatch` reuses them afterwards
+    fn changes_matches(&mut self) -> bool {
+        match &mut self.state {
+            Searcher::Searching(state) => state.refresh(&self.modifier_state),
+            _ => false,
+        }
+    }
+
+    /// Closes the window once its geometry is remembered, unless it fits itself to the entries
+    fn exit(&mut self) -> Command<Message> {
+        if self.auto_height || self.geometry_saved {
+            self.exiting = true;
+            return Command::none();
+        }
+        self.geometry_saved = true;
+        Command::perform(remember_geometry(self.sources.profile()), |_| Message::Exit)
+    }
+
+    /// Prints the selected entry once per change, screen readers can speak the lines
+    /// with something like `i3-conf-searcher --announce | while read -r line; do spd-say "$line"; done`
+    fn announce_selection(&mut self) {
+        let announcement = match &mut self.state {
+            Searcher::Searching(state) => match state.selected_entry() {
+                Some(entry) => format!(
+                    "{}, {}, {}",
+                    entry.description(),
+                    entry.group(),
+                    displayed_keys(&entry)
+                ),
+                None => self.localizer.text("no-matches"),
+            },
+            Searcher::Error(error) => error.clone(),
+            _ => return,
+        };
+        if self.announced.as_ref() != Some(&announcement) {
+            println!("{}", announcement);
+            self.announced = Some(announcement);
+        }
+    }
+
+    /// Asks i3 to resize the window to fit the matching entries, up to its initial height
+    fn fit_height(&mut self) -> Command<Message> {
+        let rows = match &mut self.state {
+            Searcher::Searching(state) => state.matches.len(),
+            _ => usize::MAX,
+        };
+        let max_height = iced::window::Settings::default().size.1;
+        let row_height = Metrics::of(self.density).row_height;
+        let height = (AUTO_HEIGHT_HEADER + row_height.saturating_mul(rows as u32))
+            .clamp(AUTO_HEIGHT_HEADER + row_height, max_height);
+        if self.requested_height == Some(height) {
+            return Command::none();
+        }
+        self.requested_height = Some(height);
+        Command::perform(executor::resize_searcher(height), |_| Message::HeightFitted)
+    }
+
+    /// Body of `update`, takes any clipboard so it can be driven without a window
+    fn dispatch(
+        &mut self,
+        message: Message,
+        clipboard: &mut dyn iced_native::Clipboard,
+    ) -> Command<Message> {
+        // Modifier events arrive with most key presses while typing, when the entries stay
+        // the same only the indicator needs the new modifiers
+        if let Message::EventOccurred(Keyboard(Event::ModifiersChanged(modifiers))) = message {
+            self.modifier_state = held_modifiers(modifiers);
+            if !self.changes_matches() {
+                return Command::none();
+            }
+        }
+        let command = self.handle_message(message, clipboard);
+        if let Searcher::Searching(state) = &mut self.state {
+            state.refresh(&self.modifier_state);
+            if self.animations.enabled {
+                let shown = state
+                    .matching_entries()
+                    .into_iter()
+                    .map(i3_config::ConfigEntry::id)
+                    .collect();
+                self.animations.update(shown, state.selection);
+            }
+        }
+        if self.announce {
+            self.announce_selection();
+        }
+        if self.auto_height {
+            Command::batch(vec![command, self.fit_height()])
+        } else {
+            command
+        }
+    }
+}
+
+/// Failures are ignored, the window closes either way
+async fn remember_geometry(profile: String) {
+    if let Ok(Some(geometry)) = executor::searcher_geometry().await {
+        let _ = WindowGeometries::remember(&profile, geometry);
+    }
+}
+
+/// Estimated height of the search bar for `--auto-height`
+const AUTO_HEIGHT_HEADER: u32 = 140;
+
+impl Application for ApplicationState {
+    type Executor = iced::executor::Default;
+    type Message = Message;
+    type Flags = InitFlags;
+
+    fn new(flags: Self::Flags) -> (ApplicationState, Command<Message>) {
+        let command = if flags.override_redirect {
+            let position = flags.window.position;
+            Command::perform(
+                tokio::task::spawn_blocking(move || override_redirect::apply(position)),
+                |result| {
+                    Message::OverrideRedirectApplied(result.unwrap_or_else(|error| {
+                        Err(i3_config::I3ConfigError::FailedOverrideRedirect(
+                            error.to_string(),
+                        ))
+                    }))
+                },
+            )
+        } else if let Some(position) = flags.window.position {
+            // Override-redirect windows are placed by the searcher itself, not by i3
+            Command::perform(executor::move_searcher(position), |_| Message::WindowPlaced)
+        } else {
+            Command::none()
+        };
+        (ApplicationState::new(flags), command)
+    }
+
+    fn title(&self) -> String {
+        String::from("i3 Config Searcher")
+    }
+
+    fn update(&mut self, message: Message, clipboard: &mut Clipboard) -> Command<Message> {
+        self.dispatch(message, clipboard)
+    }
+
+    /// Transparent windows are painted by the root container so its alpha applies
+    fn background_color(&self) -> Color {
+        if self.opacity < 1.0 {
+            Color::TRANSPARENT
+        } else {
+            Color::WHITE
+        }
+    }
+
+    fn should_exit(&self) -> bool {
+        self.exiting
+    }
+
+    fn subscription(&self) -> Subscription<Self::Message> {
+        // Key presses are captured by the focused text input
+        // so we need all events regardless of their status
+        let events = iced_native::subscription::events_with(|event, _| Some(event))
+            .map(Message::EventOccurred);
+        let signals = Subscription::from_recipe(SignalListener).map(Message::Signal);
+        let mut subscriptions = vec![events, signals];
+        if let Searcher::Loading = self.state {
+            subscriptions.push(
+                Subscription::from_recipe(ConfigLoader {
+                    sources: self.sources.clone(),
+                })
+                .map(Message::LoadProgressed),
+            );
+            subscriptions.push(iced::time::every(SPINNER_INTERVAL).map(|_| Message::SpinnerTick));
+        }
+        // Resubscribes after every reload since a restart of i3 closes the connection
+        if let Searcher::Searching(_) = self.state {
+            subscriptions
+                .push(Subscription::from_recipe(ConfigWatcher).map(Message::ConfigChanged));
+        }
+        if self.animations.running() {
+            subscriptions.push(iced::time::every(ANIMATION_FRAME).map(|_| Message::AnimationFrame));
+        }
+        Subscription::batch(subscriptions)
+    }
+
+    fn view(&mut self) -> Element<'_, Message> {
+        let window_style = style::Window {
+            theme: self.theme,
+            opacity: self.opacity,
+        };
+        match &mut self.state {
+            Searcher::Loading => Container::new(
+                Row::new()
+                    .spacing(20)
+                    .align_items(Align::Center)
+                    .push(
+                        Text::new(SPINNER_FRAMES[self.spinner_frame])
+                            .font(FONT)
+                            .size(40),
+                    )
+                    .push(Text::new(self.localizer.text(stage_message(self.load_stage))).size(40)),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(window_style)
+            .into(),
+            Searcher::Error(details) => Container::new(
+                Column::new()
+                    .align_items(Align::Center)
+                    .spacing(10)
+                    .push(
+                        Text::new(self.localizer.text("loading-failed"))
+                            .size(40)
+                            .color(Color::from_rgb(1., 0., 0.)),
+                    )
+                    .push(
+                        Text::new(details.as_str())
+                            .size(20)
+                            .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                    ),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(window_style)
+            .into(),
+            Searcher::UnsupportedPlatform => Container::new(
+                Text::new(self.localizer.text("unsupported-platform"))
+                    .size(40)
+                    .color(Color::from_rgb(1., 0., 0.)),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(window_style)
+            .into(),
+            Searcher::Editing(_, form) => {
+                let group_input = TextInput::new(
+                    &mut form.group_input,
+                    &self.localizer.text("field-group"),
+                    &form.group,
+                    Message::EditGroupChanged,
+                )
+                .style(self.theme)
+                .size(30)
+                .padding(10)
+                .on_submit(Message::SaveEdit);
+                let description_input = TextInput::new(
+                    &mut form.description_input,
+                    &self.localizer.text("field-description"),
+                    &form.description,
+                    Message::EditDescriptionChanged,
+                )
+                .style(self.theme)
+                .size(30)
+                .padding(10)
+                .on_submit(Message::SaveEdit);
+                let buttons = Row::new()
+                    .spacing(10)
+                    .push(
+                        Button::new(
+                            &mut form.save_button,
+                            Text::new(self.localizer.text("save")),
+                        )
+                        .on_press(Message::SaveEdit)
+                        .style(self.theme),
+                    )
+                    .push(
+                        Button::new(
+                            &mut form.cancel_button,
+                            Text::new(self.localizer.text("cancel")),
+                        )
+                        .on_press(Message::CancelEdit)
+                        .style(self.theme),
+                    );
+                let mut content = Column::new()
+                    .spacing(10)
+                    .padding(20)
+                    .push(
+                        Text::new(
+                            self.localizer
+                                .format("edit-annotation", vec![("line", form.line.into())]),
+                        )
+                        .size(30),
+                    )
+                    .push(
+                        Text::new(form.keys.as_str())
+                            .font(FONT)
+                            .size(20)
+                            .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                    )
+                    .push(group_input)
+                    .push(description_input)
+                    .push(buttons);
+                if let Some(error) = &form.error {
+                    content = content.push(
+                        Text::new(error.as_str())
+                            .size(20)
+                            .color(Color::from_rgb(1., 0., 0.)),
+                    );
+                }
+                Container::new(content)
+                    .style(window_style)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .into()
+            }
+            Searcher::Creating(_, form) => {
+                let theme = self.theme;
+                let [group_input, description_input, keys_input, command_input] = &mut form.inputs;
+                let buttons = Row::new()
+                    .spacing(10)
+                    .push(
+                        Button::new(
+                            &mut form.save_button,
+                            Text::new(self.localizer.text("save")),
+                        )
+                        .on_press(Message::SaveNewBinding)
+                        .style(theme),
+                    )
+                    .push(
+                        Button::new(
+                            &mut form.cancel_button,
+                            Text::new(self.localizer.text("cancel")),
+                        )
+                        .on_press(Message::CancelEdit)
+                        .style(theme),
+                    );
+                let mut content = Column::new()
+                    .spacing(10)
+                    .padding(20)
+                    .push(Text::new(self.localizer.text("new-binding")).size(30))
+                    .push(form_input(
+                        group_input,
+                        &self.localizer.text("field-group"),
+                        &form.group,
+                        BindingField::Group,
+                        theme,
+                    ))
+                    .push(form_input(
+                        description_input,
+                        &self.localizer.text("field-description"),
+                        &form.description,
+                        BindingField::Description,
+                        theme,
+                    ))
+                    .push(form_input(
+                        keys_input,
+                        &self.localizer.text("field-keys"),
+                        &form.keys,
+                        BindingField::Keys,
+                        theme,
+                    ))
+                    .push(form_input(
+                        command_input,
+                        &self.localizer.text("field-command"),
+                        &form.command,
+                        BindingField::Command,
+                        theme,
+                    ));
+                if !form.conflicts.is_empty() {
+                    content = content.push(
+                        Text::new(self.localizer.format(
+                            "keys-conflict",
+                            vec![
+                                ("keys", form.keys.trim().into()),
+                                ("bindings", form.conflicts.join(", ").into()),
+                            ],
+                        ))
+                        .size(20)
+                        .color(Color::from_rgb(0.9, 0.6, 0.1)),
+                    );
+                }
+                content = content.push(buttons);
+                if let Some(error) = &form.error {
+                    content = content.push(
+                        Text::new(error.as_str())
+                            .size(20)
+                            .color(Color::from_rgb(1., 0., 0.)),
+                    );
+                }
+                Container::new(content)
+                    .style(window_style)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .into()
+            }
+            Searcher::Searching(state) if state.show_help => {
+                Container::new(help(&state.shortcuts, self.theme, &self.localizer))
+                    .style(window_style)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .center_x()
+                    .into()
+            }
+            Searcher::Searching(state) => {
+                let metrics = Metrics::of(self.density);
+                let live_targets = self.live_targets.as_ref();
+                let localizer = &self.localizer;
+                let mut details = if state.show_details {
+                    state.selected_entry().map(|entry| {
+                        details_panel(&state.shortcuts, &entry, live_targets, localizer)
+                    })
+                } else {
+                    None
+                };
+                let theme = self.theme;
+                let mut menu = state
+                    .action_menu
+                    .as_mut()
+                    .map(|menu| action_menu(menu, theme, localizer));
+
+                let input = TextInput::new(
+                    &mut state.text_input_state,
+                    &self.localizer.text("search-placeholder"),
+                    &state.search_string,
+                    Message::InputChanged,
+                )
+                .width(Length::Fill)
+                .style(self.theme)
+                .size(metrics.input_size)
+                .padding(metrics.input_padding)
+                .on_submit(Message::ExecuteSelected);
+
+                let modifiers_label = Row::new()
+                    .width(Length::Fill)
+                    .align_items(Align::Start)
+                    .push(Space::new(Length::Units(10), Length::Units(20)))
+                    .push(
+                        Text::new(if self.modifier_state.is_empty() {
+                            self.localizer.text("no-modifiers")
+                        } else {
+                            self.modifier_state.description()
+                        })
+                        .color(Color::from_rgb(0.5, 0.5, 0.5))
+                        .font(FONT)
+                        .size(20),
+                    )
+                    .push(Space::new(Length::Fill, Length::Shrink))
+                    .push(
+                        Text::new(self.localizer.format(
+                            "source",
+                            vec![("source", state.shortcuts.source().to_string().into())],
+                        ))
+                        .color(Color::from_rgb(0.5, 0.5, 0.5))
+                        .size(16),
+                    )
+                    .push(Space::new(Length::Units(10), Length::Shrink));
+
+                let mut content = Column::new()
+                    .push(input)
+                    .push(modifiers_label)
+                    .spacing(10)
+                    .padding(5);
+                if let Some(banner) = &mut self.execution_error {
+                    content = content.push(error_banner(banner, self.theme, &self.localizer));
+                }
+                if let Some(run) = &self.quick_run {
+                    let seconds = (QUICK_RUN_STEP * run.remaining).as_secs_f32();
+                    content = content.push(
+                        Row::new()
+                            .push(Space::new(Length::Units(10), Length::Shrink))
+                            .push(
+                                Text::new(self.localizer.format(
+                                    "quick-run",
+                                    vec![("seconds", format!("{:.1}", seconds).into())],
+                                ))
+                                .size(20)
+                                .color(Color::from_rgb(0.9, 0.6, 0.1)),
+                            ),
+                    );
+                }
+                if let Some(toast) = &self.toast {
+                    let color = if toast.is_error {
+                        Color::from_rgb(1., 0., 0.)
+                    } else {
+                        Color::from_rgb(0.3, 0.8, 0.3)
+                    };
+                    content = content.push(
+                        Row::new()
+                            .push(Space::new(Length::Units(10), Length::Shrink))
+                            .push(Text::new(toast.text.as_str()).size(20).color(color)),
+                    );
+                }
+                if let Some(warnings) = warnings_panel(
+                    &mut state.warnings_button,
+                    state.show_warnings,
+                    state.shortcuts.diagnostics(),
+                    self.theme,
+                    &self.localizer,
+                ) {
+                    content = content.push(warnings);
+                }
+
+                let keys_chip = match (state.capture, &state.chord) {
+                    (Capture::Waiting, _) => Some(self.localizer.text("chip-capture")),
+                    (_, Some(chord)) => Some(
+                        self.localizer
+                            .format("chip-keys", vec![("keys", chord.to_string().into())]),
+                    ),
+                    _ => None,
+                };
+                if let Some(chips) = filter_chips(
+                    state.group.as_deref(),
+                    keys_chip,
+                    self.strict_modifiers,
+                    self.mode.as_deref(),
+                    &mut state.chip_buttons,
+                    self.theme,
+                    &self.localizer,
+                ) {
+                    content = content.push(chips);
+                }
+                let sidebar = if state.show_groups {
+                    let groups: Vec<(String, usize)> = state
+                        .shortcuts
+                        .groups()
+                        .into_iter()
+                        .map(|(group, count)| (group.to_owned(), count))
+                        .collect();
+                    Some(group_sidebar(
+                        groups,
+                        state.group.as_deref(),
+                        &mut state.group_buttons,
+                        self.theme,
+                        &self.localizer,
+                    ))
+                } else {
+                    None
+                };
+                // Cloned so the config can be read for the tooltips,
+                // by field since the buttons above borrow the rest of the state
+                let config_entries = state.shortcuts.entries();
+                let entries: Vec<i3_config::ConfigEntry> = state
+                    .matches
+                    .iter()
+                    .map(|index| config_entries[*index].clone())
+                    .collect();
+                let shortcuts = &state.shortcuts;
+                // Shown so a too strict min-score doesn't silently hide the wanted entry
+                let content = match shortcuts.weak_matches() {
+                    0 => content,
+                    count => content.push(
+                        Row::new()
+                            .push(Space::new(Length::Units(10), Length::Shrink))
+                            .push(
+                                Text::new(
+                                    self.localizer
+                                        .format("weak-matches", vec![("count", count.into())]),
+                                )
+                                .size(16)
+                                .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                            ),
+                    ),
+                };
+
+                let content = if entries.is_empty() {
+                    let warning = Text::new(self.localizer.text("no-matches"))
+                        .size(40)
+                        .horizontal_alignment(iced::HorizontalAlignment::Center)
+                        .vertical_alignment(iced::VerticalAlignment::Top)
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .color(Color::from_rgb(0.9, 0.6, 0.1));
+
+                    content.push(warning)
+                } else {
+                    let theme = self.theme;
+                    let modifier_state = &self.modifier_state;
+                    let source_badges = self.source_badges;
+                    let selected = state.selection;
+                    let sidebar_width = if sidebar.is_some() { SIDEBAR_WIDTH } else { 0 };
+                    let widths = ColumnWidths::of(
+                        &entries,
+                        self.window_width.saturating_sub(sidebar_width + 50),
+                        metrics,
+                    );
+                    let animations = &self.animations;
+                    let context = RowContext {
+                        held: modifier_state,
+                        source_badge: source_badges,
+                        live_targets,
+                        widths,
+                        theme,
+                        localizer,
+                    };
+                    let entries_column = entries.iter().enumerate().fold(
+                        Column::new().padding(metrics.list_padding),
+                        |column: Column<Message>, (index, config_entry)| {
+                            let fade = animations.fade(config_entry);
+                            let row = Container::new(config_entry.view(context, fade))
+                                .width(Length::Fill)
+                                .style(style::Row {
+                                    theme,
+                                    highlight: animations.highlight(index, selected),
+                                    fade,
+                                });
+                            let row = Tooltip::new(
+                                row,
+                                row_tooltip(shortcuts, config_entry),
+                                tooltip::Position::FollowCursor,
+                            )
+                            .font(FONT)
+                            .size(16)
+                            .padding(5)
+                            .style(style::Selection(theme));
+                            if index == selected {
+                                let column = column.push(row);
+                                let column = match menu.take() {
+                                    Some(menu) => column.push(menu),
+                                    None => column,
+                                };
+                                match details.take() {
+                                    Some(details) => column.push(details),
+                                    None => column,
+                                }
+                            } else {
+                                column.push(row)
+                            }
+                        },
+                    );
+
+                    let scrollable_entries = Scrollable::new(&mut state.scroll)
+                        .push(entries_column)
+                        .style(self.theme);
+                    content.push(scrollable_entries)
+                };
+
+                let content = match sidebar {
+                    Some(sidebar) => Row::new().push(sidebar).push(content),
+                    None => Row::new().push(content),
+                };
+                Container::new(content)
+                    .style(window_style)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .center_x()
+                    .align_y(iced::Align::Start)
+                    .into()
+            }
+        }
+    }
+}
+
+/// Chips summarizing why results are restricted, `None` when nothing is
+fn filter_chips<'a>(
+    group: Option<&str>,
+    keys: Option<String>,
+    strict_modifiers: bool,
+    mode: Option<&str>,
+    buttons: &'a mut [button::State; 3],
+    theme: Theme,
+    localizer: &Localizer,
+) -> Option<Element<'a, Message>> {
+    if group.is_none() && keys.is_none() && !strict_modifiers && mode.is_none() {
+        return None;
+    }
+    let [group_button, keys_button, strict_button] = buttons;
+    let mut chips = Row::new()
+        .spacing(10)
+        .align_items(Align::Center)
+        .push(Space::new(Length::Units(10), Length::Shrink));
+    if let Some(mode) = mode {
+        // Entries of other modes aren't loaded so this one can't be removed
+        chips = chips.push(
+            Text::new(localizer.format("chip-mode", vec![("mode", mode.into())]))
+                .size(16)
+                .color(Color::from_rgb(0.5, 0.5, 0.5)),
+        );
+    }
+    if let Some(group) = group {
+        chips = chips.push(
+            Button::new(
+                group_button,
+                Text::new(localizer.format("chip-group", vec![("group", group.into())])).size(16),
+            )
+            .on_press(Message::RemoveFilter(ActiveFilter::Group))
+            .style(theme),
+        );
+    }
+    if let Some(keys) = keys {
+        chips = chips.push(
+            Button::new(keys_button, Text::new(keys).size(16))
+                .on_press(Message::RemoveFilter(ActiveFilter::Chord))
+                .style(theme),
+        );
+    }
+    if strict_modifiers {
+        chips = chips.push(
+            Button::new(
+                strict_button,
+                Text::new(localizer.text("chip-exact-modifiers")).size(16),
+            )
+            .on_press(Message::RemoveFilter(ActiveFilter::StrictModifiers))
+            .style(theme),
+        );
+    }
+    Some(chips.into())
+}
+
+const SIDEBAR_WIDTH: u32 = 220;
+
+fn held_modifiers(modifiers: keyboard::Modifiers) -> i3_config::Modifiers {
+    i3_config::Modifiers::new(
+        modifiers.shift,
+        modifiers.control,
+        modifiers.alt,
+        modifiers.logo,
+    )
+}
+
+/// i3 name of the key, `None` for modifiers which can't complete a combination
+fn keysym(key_code: KeyCode) -> Option<String> {
+    let name = match key_code {
+        KeyCode::LShift
+        | KeyCode::RShift
+        | KeyCode::LControl
+        | KeyCode::RControl
+        | KeyCode::LAlt
+        | KeyCode::RAlt
+        | KeyCode::LWin
+        | KeyCode::RWin => return None,
+        KeyCode::Space => "space",
+        KeyCode::Backspace => "BackSpace",
+        KeyCode::PageUp => "Prior",
+        KeyCode::PageDown => "Next",
+        KeyCode::Snapshot => "Print",
+        KeyCode::Minus => "minus",
+        KeyCode::Equals => "equal",
+        KeyCode::Comma => "comma",
+        KeyCode::Period => "period",
+        KeyCode::Slash => "slash",
+        KeyCode::Backslash => "backslash",
+        KeyCode::Semicolon => "semicolon",
+        KeyCode::Apostrophe => "apostrophe",
+        KeyCode::Grave => "grave",
+        KeyCode::LBracket => "bracketleft",
+        KeyCode::RBracket => "bracketright",
+        // Letters are lower case keysyms, digits drop the prefix and the rest match i3's names
+        _ => {
+            let name = format!("{:?}", key_code);
+            return Some(match name.strip_prefix("Key") {
+                Some(digit) => digit.to_owned(),
+                None if name.len() == 1 => name.to_lowercase(),
+                None => name,
+            });
+        }
+    };
+    Some(name.to_owned())
+}
+
+fn group_number(key_code: KeyCode) -> Option<usize> {
+    let keys = [
+        KeyCode::Key0,
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+        KeyCode::Key7,
+        KeyCode::Key8,
+        KeyCode::Key9,
+    ];
+    keys.iter().position(|key| *key == key_code)
+}
+
+/// Groups with their entry counts, the selected one restricts the search
+fn group_sidebar<'a>(
+    groups: Vec<(String, usize)>,
+    selected: Option<&str>,
+    buttons: &'a mut [button::State],
+    theme: Theme,
+    localizer: &Localizer,
+) -> Element<'a, Message> {
+    let total = groups.iter().map(|(_, count)| count).sum::<usize>();
+    let all = localizer.format("all-groups", vec![("count", total.into())]);
+    let items = std::iter::once((None, all)).chain(groups.into_iter().map(|(group, count)| {
+        let label = format!("{} ({})", breadcrumbs(&group), count);
+        (Some(group), label)
+    }));
+    let column = items.zip(buttons.iter_mut()).fold(
+        Column::new()
+            .spacing(2)
+            .padding(10)
+            .width(Length::Units(SIDEBAR_WIDTH as u16)),
+        |column, ((group, label), button_state)| {
+            let is_selected = group.as_deref() == selected;
+            let button = Button::new(button_state, Text::new(label).size(18))
+                .on_press(Message::SelectGroup(group))
+                .width(Length::Fill)
+                .style(theme);
+            let row = Container::new(button).width(Length::Fill);
+            if is_selected {
+                column.push(row.style(style::Selection(theme)))
+            } else {
+                column.push(row)
+            }
+        },
+    );
+    column.into()
+}
+
+fn form_input<'a>(
+    state: &'a mut text_input::State,
+    placeholder: &str,
+    value: &str,
+    field: BindingField,
+    theme: Theme,
+) -> TextInput<'a, Message> {
+    TextInput::new(state, placeholder, value, move |value| {
+        Message::NewBindingChanged(field, value)
+    })
+    .style(theme)
+    .size(30)
+    .padding(10)
+    .on_submit(Message::SaveNewBinding)
+}
+
+/// Raw config lines and parsed metadata of the selected entry
+fn details_panel<'a>(
+    config: &i3_config::ConfigMetadata,
+    entry: &i3_config::ConfigEntry,
+    live_targets: Option<&targets::LiveTargets>,
+    localizer: &Localizer,
+) -> Element<'a, Message> {
+    let label_color = Color::from_rgb(0.5, 0.5, 0.5);
+    let source = entry_location(config, entry);
+    let mut panel = Column::new()
+        .spacing(5)
+        .padding(10)
+        .push(
+            Text::new(localizer.format("source", vec![("source", source.into())]))
+                .size(16)
+                .color(label_color),
+        )
+        .push(
+            Text::new(localizer.format(
+                "detail-description",
+                vec![("description", entry.description().into())],
+            ))
+            .size(16)
+            .color(label_color),
+        )
+        .push(
+            Text::new(localizer.format(
+                "detail-mode",
+                vec![("mode", entry.mode().unwrap_or("default").into())],
+            ))
+            .size(16)
+            .color(label_color),
+        )
+        .push(
+            Text::new(localizer.text("detail-command"))
+                .size(16)
+                .color(label_color),
+        );
+    let actions = i3_command::actions(entry.command().unwrap_or_default());
+    panel = match actions.as_slice() {
+        [] => panel.push(
+            Text::new(localizer.text("detail-no-command"))
+                .size(16)
+                .color(label_color),
+        ),
+        [action] => panel.push(highlighted_command(action)),
+        // Chained commands are still sent to i3 as a whole
+        actions => actions.iter().fold(panel, |panel, action| {
+            panel.push(
+                Row::new()
+                    .push(Text::new("• ").font(FONT).size(16))
+                    .push(highlighted_command(action)),
+            )
+        }),
+    };
+    if !entry.sequence().combos().is_empty() {
+        let combos: Vec<String> = entry
+            .sequence()
+            .combos()
+            .iter()
+            .map(|combo| combo.to_string())
+            .collect();
+        panel = panel.push(
+            Text::new(
+                localizer.format("detail-keys", vec![("keys", combos.join(" then ").into())]),
+            )
+            .size(16)
+            .color(label_color),
+        );
+    }
+    if !entry.flags().is_empty() {
+        let flags: Vec<String> = entry.flags().iter().map(|flag| flag.to_string()).collect();
+        panel = panel.push(
+            Text::new(localizer.format("detail-flags", vec![("flags", flags.join(" ").into())]))
+                .size(16)
+                .color(label_color),
+        );
+    }
+    for target in targets::command_targets(entry.command().unwrap_or_default()) {
+        let (message, color) = match (live_targets, &target) {
+            (None, _) => ("detail-target", label_color),
+            (Some(live), _) if live.exists(&target) => ("detail-target-open", label_color),
+            (Some(_), targets::CommandTarget::Output(_)) => {
+                ("detail-target-not-connected", Color::from_rgb(1., 0., 0.))
+            }
+            (Some(_), _) => ("detail-target-not-open", label_color),
+        };
+        panel = panel.push(
+            Text::new(localizer.format(message, vec![("target", target.to_string().into())]))
+                .size(16)
+                .color(color),
+        );
+    }
+    for line in config.excerpt(entry) {
+        panel = panel.push(Text::new(line.to_owned()).font(FONT).size(16));
+    }
+    Row::new()
+        .push(Space::new(Length::Units(20), Length::Shrink))
+        .push(panel)
+        .into()
+}
+
+/// Command colored by the parts i3 parses it into
+fn highlighted_command<'a>(command: &str) -> Element<'a, Message> {
+    let mut row = Row::new();
+    for token in i3_command::tokenize(command) {
+        let text = Text::new(token.text.to_owned()).font(FONT).size(16);
+        row = row.push(match token_color(token.kind) {
+            Some(color) => text.color(color),
+            None => text,
+        });
+    }
+    row.into()
+}
+
+fn token_color(kind: i3_command::TokenKind) -> Option<Color> {
+    match kind {
+        i3_command::TokenKind::Criteria => Some(Color::from_rgb(0.6, 0.4, 0.9)),
+        i3_command::TokenKind::Verb => Some(Color::from_rgb(0.9, 0.6, 0.1)),
+        i3_command::TokenKind::Separator => Some(Color::from_rgb(1.0, 0.0, 0.5)),
+        i3_command::TokenKind::Argument | i3_command::TokenKind::Whitespace => None,
+    }
+}
+
+/// F1 help listing the keys of [`KEYMAP`] and what the colors of modes and commands mean
+fn help<'a>(
+    config: &i3_config::ConfigMetadata,
+    theme: Theme,
+    localizer: &Localizer,
+) -> Element<'a, Message> {
+    let row = |keys: String, label: &str| {
+        Row::new()
+            .spacing(20)
+            .push(
+                Text::new(keys)
+                    .font(FONT)
+                    .size(18)
+                    .width(Length::Units(200)),
+            )
+            .push(Text::new(label.to_owned()).size(18))
+    };
+    let mut column = Column::new()
+        .spacing(6)
+        .padding(20)
+        .push(Text::new(localizer.text("help-controls")).size(30));
+    for binding in &KEYMAP {
+        column = column.push(row(binding.keys(), &binding.action.label(localizer)));
+    }
+    for (keys, message) in &OTHER_CONTROLS {
+        column = column.push(row((*keys).to_owned(), &localizer.text(message)));
+    }
+    column = column.push(Text::new(localizer.text("help-colors")).size(30));
+    let mut modes: Vec<&str> = config
+        .entries()
+        .iter()
+        .filter_map(|entry| entry.mode())
+        .collect();
+    modes.sort_unstable();
+    modes.dedup();
+    for mode in modes {
+        column = column.push(
+            Row::new()
+                .spacing(20)
+                .align_items(Align::Center)
+                .push(
+                    Container::new(Space::new(
+                        Length::Units(MODE_MARKER_WIDTH),
+                        Length::Units(24),
+                    ))
+                    .style(style::ModeMarker(theme.mode_color(mode))),
+                )
+                .push(
+                    Text::new(localizer.format("help-mode", vec![("mode", mode.into())])).size(18),
+                ),
+        );
+    }
+    let tokens = [
+        (
+            i3_command::TokenKind::Criteria,
+            "[class=\"Firefox\"]",
+            "token-criteria",
+        ),
+        (i3_command::TokenKind::Verb, "move", "token-command"),
+        (i3_command::TokenKind::Separator, ";", "token-separator"),
+    ];
+    for (kind, example, message) in &tokens {
+        let example = Text::new(*example)
+            .font(FONT)
+            .size(18)
+            .width(Length::Units(200));
+        let example = match token_color(*kind) {
+            Some(color) => example.color(color),
+            None => example,
+        };
+        column = column.push(
+            Row::new()
+                .spacing(20)
+                .push(example)
+                .push(Text::new(localizer.text(message)).size(18)),
+        );
+    }
+    column.into()
+}
+
+/// File and line of the entry, or its source if it wasn't read from the config
+fn entry_location(config: &i3_config::ConfigMetadata, entry: &i3_config::ConfigEntry) -> String {
+    match (entry.source(), config.source_path()) {
+        (Some(source), _) if source != config.source() => source.to_string(),
+        (_, Some(path)) => format!("{}:{}", path.display(), entry.line()),
+        (_, None) => format!("line {}", entry.line()),
+    }
+}
+
+/// Location and config lines of the entry shown when hovering its row
+fn row_tooltip(config: &i3_config::ConfigMetadata, entry: &i3_config::ConfigEntry) -> String {
+    let mut lines = vec![entry_location(config, entry)];
+    // Lines of imported entries aren't in the config text
+    if entry
+        .source()
+        .is_none_or(|source| source == config.source())
+    {
+        lines.extend(
+            config
+                .excerpt(entry)
+                .into_iter()
+                .map(str::trim)
+                .map(str::to_owned),
+        );
+    }
+    lines.join("\n")
+}
+
+/// One line like `moves "Firefox" to workspace 2, then switches from workspace 1 to 2`
+fn explanation(effects: &[preview::Effect], localizer: &Localizer) -> String {
+    effects
+        .iter()
+        .map(|effect| effect_text(effect, localizer))
+        .reduce(|before, after| {
+            localizer.format(
+                "preview-then",
+                vec![("before", before.into()), ("after", after.into())],
+            )
+        })
+        .unwrap_or_default()
+}
+
+fn effect_text(effect: &preview::Effect, localizer: &Localizer) -> String {
+    use preview::Effect;
+    let text = |value: &str| value.to_owned().into();
+    let subject = |subject| ("subject", subject_text(subject, localizer).into());
+    match effect {
+        Effect::Starts(program) => {
+            localizer.format("preview-starts", vec![("program", text(program))])
+        }
+        Effect::SwitchesWorkspace {
+            from: Some(from),
+            to,
+        } => localizer.format(
+            "preview-switches",
+            vec![("from", text(from)), ("to", text(to))],
+        ),
+        Effect::SwitchesWorkspace { from: None, to } => {
+            localizer.format("preview-switches-to", vec![("to", text(to))])
+        }
+        Effect::MovesToScratchpad(window) => {
+            localizer.format("preview-to-scratchpad", vec![subject(window)])
+        }
+        Effect::Moves(window, target) => localizer.format(
+            "preview-moves",
+            vec![subject(window), ("target", text(target))],
+        ),
+        Effect::Closes(window) => localizer.format("preview-closes", vec![subject(window)]),
+        Effect::Focuses(window) => localizer.format("preview-focuses", vec![subject(window)]),
+        Effect::MovesFocus(direction) => {
+            localizer.format("preview-moves-focus", vec![("direction", text(direction))])
+        }
+        Effect::EntersMode(mode) => {
+            localizer.format("preview-enters-mode", vec![("mode", text(mode))])
+        }
+        Effect::Changes(command, window) => localizer.format(
+            "preview-changes",
+            vec![("command", text(command)), subject(window)],
+        ),
+        Effect::Runs(command) => localizer.format("preview-runs", vec![("command", text(command))]),
+    }
+}
+
+fn subject_text(subject: &preview::Subject, localizer: &Localizer) -> String {
+    use preview::Subject;
+    match subject {
+        Subject::Window(title) => {
+            localizer.format("preview-window", vec![("title", title.clone().into())])
+        }
+        Subject::FocusedWindow => localizer.text("preview-focused-window"),
+        Subject::NoWindow { criteria } => localizer.format(
+            "preview-no-window",
+            vec![("criteria", criteria.clone().into())],
+        ),
+        Subject::Windows { count, criteria } => localizer.format(
+            "preview-windows",
+            vec![
+                ("count", (*count).into()),
+                ("criteria", criteria.clone().into()),
+            ],
+        ),
+    }
+}
+
+fn stage_message(stage: loader::LoadStage) -> &'static str {
+    match stage {
+        loader::LoadStage::Connecting => "stage-connecting",
+        loader::LoadStage::Fetching => "stage-fetching",
+        loader::LoadStage::Downloading => "stage-downloading",
+        loader::LoadStage::Syncing => "stage-syncing",
+        loader::LoadStage::Reading => "stage-reading",
+        loader::LoadStage::Parsing => "stage-parsing",
+        loader::LoadStage::Importing => "stage-importing",
+    }
+}
+
+fn action_menu<'a>(
+    menu: &'a mut ActionMenu,
+    theme: Theme,
+    localizer: &Localizer,
+) -> Element<'a, Message> {
+    let selection = menu.selection;
+    EntryAction::ALL
+        .iter()
+        .zip(menu.buttons.iter_mut())
+        .enumerate()
+        .fold(
+            Column::new().spacing(2).padding(10),
+            |column, (index, (action, button_state))| {
+                let button = Button::new(button_state, Text::new(action.label(localizer)).size(18))
+                    .on_press(Message::RunAction(*action))
+                    .width(Length::Fill)
+                    .style(theme);
+                let row = Container::new(button).width(Length::Units(300));
+                if index == selection {
+                    column.push(row.style(style::Selection(theme)))
+                } else {
+                    column.push(row)
+                }
+            },
+        )
+        .into()
+}
+
+fn error_banner<'a>(
+    banner: &'a mut ErrorBanner,
+    theme: Theme,
+    localizer: &Localizer,
+) -> Element<'a, Message> {
+    let dismiss = Button::new(
+        &mut banner.dismiss_button,
+        Text::new(localizer.text("dismiss")).size(16),
+    )
+    .on_press(Message::DismissError)
+    .style(theme);
+    Row::new()
+        .align_items(Align::Center)
+        .spacing(10)
+        .padding(5)
+        .push(
+            Text::new(localizer.format("run-failed", vec![("error", banner.text.as_str().into())]))
+                .size(20)
+                .color(Color::from_rgb(1., 0., 0.))
+                .width(Length::Fill),
+        )
+        .push(dismiss)
+        .into()
+}
+
+fn warnings_panel<'a>(
+    button_state: &'a mut button::State,
+    expanded: bool,
+    diagnostics: &[i3_config::Diagnostic],
+    theme: Theme,
+    localizer: &Localizer,
+) -> Option<Column<'a, Message>> {
+    if diagnostics.is_empty() {
+        return None;
+    }
+    let toggle = Button::new(
+        button_state,
+        Text::new(localizer.format("config-warnings", vec![("count", diagnostics.len().into())]))
+            .size(16),
+    )
+    .on_press(Message::ToggleWarnings)
+    .style(theme);
+    let mut panel = Column::new().spacing(5).padding(5).push(toggle);
+    if expanded {
+        for diagnostic in diagnostics {
+            panel = panel.push(
+                Text::new(diagnostic.to_string())
+                    .font(FONT)
+                    .size(16)
+                    .color(Color::from_rgb(0.9, 0.6, 0.1)),
+            );
+        }
+    }
+    Some(panel)
+}
+
+/// Widths shared by all rows so groups and keys line up like a table
+#[derive(Debug, Clone, Copy)]
+struct ColumnWidths {
+    group: u16,
+    keys: u16,
+    /// Longer descriptions are cut so the keys stay on screen
+    description_chars: usize,
+    /// Sizes of the density the widths were measured for
+    metrics: Metrics,
+}
+
+impl ColumnWidths {
+    /// `available` is the width left for the rows after padding and the sidebar
+    fn of(entries: &[i3_config::ConfigEntry], available: u32, metrics: Metrics) -> Self {
+        let char_width = metrics.char_width();
+        let widest = |text: &dyn Fn(&i3_config::ConfigEntry) -> usize| {
+            let chars = entries.iter().map(text).max().unwrap_or(0);
+            (chars as f32 * char_width).ceil() as u16
+        };
+        let group = widest(&|entry| breadcrumbs(entry.group()).chars().count());
+        let keys = widest(&|entry| displayed_keys(entry).chars().count());
+        // Row padding, spacing and room for the remaining keys hint
+        let reserved = u32::from(group) + u32::from(keys) + 120;
+        ColumnWidths {
+            group,
+            keys,
+            description_chars: (available.saturating_sub(reserved) as f32 / char_width) as usize,
+            metrics,
+        }
+    }
+}
+
+/// Time between redraws and length of the fades of `--animations`
+const ANIMATION_FRAME: std::time::Duration = std::time::Duration::from_millis(16);
+const ANIMATION_DURATION: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// Fades entries that just started matching in and moves the highlight
+/// from the previously selected row to the selected one
+#[derive(Debug)]
+struct Animations {
+    enabled: bool,
+    shown: Vec<i3_config::EntryId>,
+    appeared: Vec<i3_config::EntryId>,
+    results_changed: Option<std::time::Instant>,
+    selection: usize,
+    previous_selection: Option<usize>,
+    selection_changed: Option<std::time::Instant>,
+}
+
+impl Animations {
+    fn new(enabled: bool) -> Self {
+        Animations {
+            enabled,
+            shown: vec![],
+            appeared: vec![],
+            results_changed: None,
+            selection: 0,
+            previous_selection: None,
+            selection_changed: None,
+        }
+    }
+
+    fn update(&mut self, shown: Vec<i3_config::EntryId>, selection: usize) {
+        if shown != self.shown {
+            // Entries that were already visible don't blink when the list is filtered
+            self.appeared = shown
+                .iter()
+                .filter(|id| !self.shown.contains(id))
+                .copied()
+                .collect();
+            self.shown = shown;
+            self.results_changed = Some(std::time::Instant::now());
+        }
+        if selection != self.selection {
+            self.previous_selection = Some(self.selection);
+            self.selection = selection;
+            self.selection_changed = Some(std::time::Instant::now());
+        }
+    }
+
+    fn running(&self) -> bool {
+        progress(self.results_changed) < 1.0 || progress(self.selection_changed) < 1.0
+    }
+
+    /// Opacity of an entry's text
+    fn fade(&self, entry: &i3_config::ConfigEntry) -> f32 {
+        if self.appeared.contains(&entry.id()) {
+            progress(self.results_changed)
+        } else {
+            1.0
+        }
+    }
+
+    /// Opacity of the selection background behind a row
+    fn highlight(&self, index: usize, selected: usize) -> f32 {
+        let progress = progress(self.selection_changed);
+        if index == selected {
+            progress
+        } else if self.previous_selection == Some(index) {
+            1.0 - progress
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Eased progress of an animation started at `started`, finished ones are at 1
+fn progress(started: Option<std::time::Instant>) -> f32 {
+    let elapsed = match started {
+        Some(started) => started.elapsed(),
+        None => return 1.0,
+    };
+    let linear = (elapsed.as_secs_f32() / ANIMATION_DURATION.as_secs_f32()).min(1.0);
+    1.0 - (1.0 - linear).powi(2)
+}
+
+/// What all rows of the list are drawn with
+#[derive(Clone, Copy)]
+struct RowContext<'a> {
+    held: &'a i3_config::Modifiers,
+    source_badge: bool,
+    live_targets: Option<&'a targets::LiveTargets>,
+    widths: ColumnWidths,
+    theme: Theme,
+    localizer: &'a Localizer,
+}
+
+trait ViewModel {
+    fn view(&self, context: RowContext<'_>, fade: f32) -> Element<'static, Message>;
+}
+
+impl ViewModel for i3_config::ConfigEntry {
+    fn view(&self, context: RowContext<'_>, fade: f32) -> Element<'static, Message> {
+        let RowContext {
+            held,
+            source_badge,
+            live_targets,
+            widths,
+            theme,
+            localizer,
+        } = context;
+        let faded = |color: Color| Color {
+            a: color.a * fade,
+            ..color
+        };
+        let metrics = widths.metrics;
+        // Bindings of the default mode keep the space so columns stay aligned
+        let marker: Element<'static, Message> = match self.mode() {
+            Some(mode) => Container::new(Space::new(
+                Length::Units(MODE_MARKER_WIDTH),
+                Length::Units(metrics.text_size + 4),
+            ))
+            .style(style::ModeMarker(faded(theme.mode_color(mode))))
+            .into(),
+            None => Space::new(Length::Units(MODE_MARKER_WIDTH), Length::Shrink).into(),
+        };
+        let mut group = Row::new().width(Length::Units(widths.group));
+        for element in self.matched_group() {
+            match element {
+                i3_config::MatchElement::Matched(element) => {
+                    group = group.push(
+                        Text::new(breadcrumbs(&element))
+                            .font(FONT)
+                            .size(metrics.text_size)
+                            .color(faded(Color::from_rgb(1.0, 0.0, 0.5))),
+                    );
+                }
+
+                i3_config::MatchElement::Unmatched(element) => {
+                    group = group.push(
+                        Text::new(breadcrumbs(&element))
+                            .font(FONT)
+                            .size(metrics.text_size)
+                            .color(faded(Color::from_rgb(0.9, 0.6, 0.1))),
+                    );
+                }
+            }
+        }
+        let mut description = Row::new().width(Length::Fill);
+        let elements =
+            i3_config::truncate_elements(self.matched_description(), widths.description_chars);
+        for element in elements {
+            match element {
+                i3_config::MatchElement::Matched(element) => {
+                    description = description.push(
+                        Text::new(element)
+                            .font(FONT)
+                            .size(metrics.text_size)
+                            .color(faded(Color::from_rgb(1.0, 0.0, 0.5))),
+                    );
+                }
+
+                i3_config::MatchElement::Unmatched(element) => {
+                    description = description.push(
+                        Text::new(element.to_owned())
+                            .font(FONT)
+                            .size(metrics.text_size),
+                    );
+                }
+            }
+        }
+        let mut row = Row::new()
+            .width(Length::Fill)
+            .align_items(Align::Center)
+            .padding(metrics.row_padding)
+            .push(marker)
+            .push(Space::new(Length::Units(6), Length::Shrink))
+            .push(group)
+            .push(Space::new(Length::Units(10), Length::Shrink))
+            .push(description);
+        // Which key completes the binding from the modifiers currently held
+        if !held.is_empty() {
+            if let Some(remaining) = self.sequence().remaining(held) {
+                row = row
+                    .push(
+                        Text::new(remaining)
+                            .font(FONT)
+                            .size(metrics.hint_size)
+                            .color(faded(Color::from_rgb(1.0, 0.0, 0.5))),
+                    )
+                    .push(Space::new(Length::Units(20), Length::Shrink));
+            }
+        }
+        // Bindings moving things to outputs that aren't connected won't do anything
+        let missing_output = match (live_targets, self.command()) {
+            (Some(live), Some(command)) => !live.missing_outputs(command).is_empty(),
+            _ => false,
+        };
+        if missing_output {
+            row = row
+                .push(
+                    Text::new(localizer.text("output-missing"))
+                        .size(metrics.small_text_size)
+                        .color(faded(Color::from_rgb(1., 0., 0.))),
+                )
+                .push(Space::new(Length::Units(10), Length::Shrink));
+        }
+        let mut keys = Row::new().width(Length::Units(widths.keys));
+        if self.kind() == i3_config::BindingKind::Mouse {
+            keys = keys.push(
+                Text::new(format!("{} ", MOUSE_ICON))
+                    .font(FONT)
+                    .size(metrics.text_size),
+            );
+        }
+        for element in self.matched_keys() {
+            match element {
+                i3_config::MatchElement::Matched(element) => {
+                    keys = keys.push(
+                        Text::new(element)
+                            .font(FONT)
+                            .size(metrics.text_size)
+                            .color(faded(Color::from_rgb(1.0, 0.0, 0.5))),
+                    );
+                }
+
+                i3_config::MatchElement::Unmatched(element) => {
+                    keys = keys.push(Text::new(element).font(FONT).size(metrics.text_size));
+                }
+            }
+        }
+        // Keys of collapsed duplicates aren't searched
+        for alternative in self.alternative_keys() {
+            keys = keys.push(
+                Text::new(format!(" / {}", alternative))
+                    .font(FONT)
+                    .size(metrics.text_size),
+            );
+        }
+        row = row.push(keys);
+        if let Some(source) = self.source().filter(|_| source_badge) {
+            row = row
+                .push(Space::new(Length::Units(10), Length::Shrink))
+                .push(
+                    Text::new(format!("[{}]", source.badge()))
+                        .size(metrics.small_text_size)
+                        .color(faded(Color::from_rgb(0.5, 0.5, 0.5))),
+                );
+        }
+        row.into()
+    }
+}
+
+const MODE_MARKER_WIDTH: u16 = 4;
+
+/// Nested groups like `Launch/Browsers` shown as `Launch › Browsers`.
+/// Applied to parts of the group too, the match indices count the slashes
+fn breadcrumbs(group: &str) -> String {
+    group.replace('/', " › ")
+}
+
+/// Keys of the entry followed by the keys of collapsed duplicates
+fn displayed_keys(entry: &i3_config::ConfigEntry) -> String {
+    std::iter::once(entry.keys())
+        .chain(entry.alternative_keys().iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+/// Mouse pointer of the bundled Nerd Font, marks bindings on mouse buttons
+const MOUSE_ICON: &str = "\u{f245}";
+
+/// Font file bundled into the binary, checked by the doctor subcommand
+#[cfg(feature = "cli")]
+pub fn font_bytes() -> &'static [u8] {
+    match FONT {
+        Font::External { bytes, .. } => bytes,
+        Font::Default => &[],
+    }
+}
+
+const FONT: Font = Font::External {
+    name: "MesloLGS",
+    bytes: include_bytes!("../fonts/MesloLGS NF Regular.ttf"),
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use i3_conf_searcher::preferences::Preferences;
+
+    const CONFIG: &str = "## media // play // <> p ##
+        bindsym $mod+p exec playerctl play-pause
+        ## media // next // <> n ##
+        bindsym $mod+n exec playerctl next
+        ## window // close // <> Shift q ##
+        bindsym $mod+Shift+q kill";
+
+    #[derive(Default)]
+    struct TestClipboard {
+        contents: Option<String>,
+    }
+
+    impl iced_native::Clipboard for TestClipboard {
+        fn read(&self) -> Option<String> {
+            self.contents.clone()
+        }
+
+        fn write(&mut self, contents: String) {
+            self.contents = Some(contents);
+        }
+    }
+
+    /// Searcher driven with messages instead of a window, the commands it returns are dropped
+    struct Harness {
+        app: ApplicationState,
+        clipboard: TestClipboard,
+        // Toasts and countdowns create timers when their commands are built
+        runtime: tokio::runtime::Runtime,
+    }
+
+    impl Harness {
+        fn loaded() -> Harness {
+            Harness::remembering_hidden_entries(None)
+        }
+
+        fn remembering_hidden_entries(hidden_entries: Option<std::path::PathBuf>) -> Harness {
+            let preferences = Preferences::default();
+            let app = ApplicationState::new(InitFlags {
+                theme: Theme::Dark,
+                density: preferences.density,
+                window: preferences.window.clone(),
+                collapse_duplicates: false,
+                exit_on_focus_loss: true,
+                focus_loss_grace: preferences.focus_loss_grace(),
+                group_sidebar: false,
+                order: preferences.order,
+                min_score: None,
+                matcher: preferences.matcher,
+                search_text: preferences.search_fields.clone(),
+                sources: loader::SourceOptions::default(),
+                editor: None,
+                strict_modifiers: false,
+                mode: None,
+                source_badges: false,
+                grab_keyboard: false,
+                override_redirect: false,
+                notify_failures: false,
+                usage_stats: false,
+                hidden_entries,
+                stay_in_mode: false,
+                quick: false,
+                auto_height: false,
+                opacity: 1.0,
+                animations: false,
+                announce: false,
+                instance_lock: None,
+            });
+            let mut harness = Harness {
+                app,
+                clipboard: TestClipboard::default(),
+                runtime: tokio::runtime::Runtime::new().unwrap(),
+            };
+            let config = i3_config::ConfigMetadata::parse(CONFIG).unwrap();
+            harness.send(Message::LoadProgressed(loader::LoadProgress::Finished(Ok(
+                Box::new(config),
+            ))));
+            harness
+        }
+
+        fn send(&mut self, message: Message) {
+            let _context = self.runtime.enter();
+            let _ = self.app.dispatch(message, &mut self.clipboard);
+        }
+
+        fn press(&mut self, key_code: KeyCode) {
+            self.send(Message::EventOccurred(iced_native::Event::Keyboard(
+                Event::KeyPressed {
+                    key_code,
+                    modifiers: keyboard::Modifiers::default(),
+                },
+            )));
+        }
+
+        fn state(&self) -> &State {
+            match &self.app.state {
+                Searcher::Searching(state) => state,
+                _ => panic!("the config isn't loaded"),
+            }
+        }
+
+        fn shown(&self) -> Vec<&str> {
+            self.state()
+                .matching_entries()
+                .into_iter()
+                .map(|entry| entry.description())
+                .collect()
+        }
+
+        fn selected(&self) -> Option<String> {
+            self.state()
+                .selected_entry()
+                .map(|entry| entry.description().to_owned())
+        }
+    }
+
+    impl Drop for Harness {
+        fn drop(&mut self) {
+            // Would run the binding once the searcher is dropped
+            self.app.pending_execution.entry = None;
+        }
+    }
+
+    #[test]
+    fn loaded_config_shows_every_entry() {
+        let harness = Harness::loaded();
+        // Sorted by group and description
+        assert_eq!(harness.shown(), vec!["next", "play", "close"]);
+        assert_eq!(harness.selected().as_deref(), Some("next"));
+    }
+
+    #[test]
+    fn typing_filters_and_selects_the_first_match() {
+        let mut harness = Harness::loaded();
+        harness.press(KeyCode::Down);
+        harness.send(Message::InputChanged(String::from("media")));
+        assert_eq!(harness.shown(), vec!["next", "play"]);
+        assert_eq!(harness.state().selection, 0);
+        harness.send(Message::InputChanged(String::from("nothing like this")));
+        assert!(harness.shown().is_empty());
+        assert_eq!(harness.selected(), None);
+    }
+
+    #[test]
+    fn navigation_stays_inside_the_matches() {
+        let mut harness = Harness::loaded();
+        for _ in 0..5 {
+            harness.press(KeyCode::Down);
+        }
+        assert_eq!(harness.selected().as_deref(), Some("close"));
+        harness.press(KeyCode::Up);
+        assert_eq!(harness.selected().as_deref(), Some("play"));
+        for _ in 0..5 {
+            harness.press(KeyCode::Up);
+        }
+        assert_eq!(harness.selected().as_deref(), Some("next"));
+    }
+
+    #[test]
+    fn selection_follows_the_matches_when_they_shrink() {
+        let mut harness = Harness::loaded();
+        harness.press(KeyCode::Down);
+        harness.press(KeyCode::Down);
+        harness.send(Message::RunAction(EntryAction::Hide));
+        assert_eq!(harness.shown(), vec!["next", "play"]);
+        assert_eq!(harness.selected().as_deref(), Some("play"));
+    }
+
+    #[test]
+    fn hidden_entries_stay_hidden_after_a_restart() {
+        let path = std::env::temp_dir().join(format!(
+            "i3-conf-searcher-gui-hidden-{}.json",
+            std::process::id()
+        ));
+        let mut harness = Harness::remembering_hidden_entries(Some(path.clone()));
+        harness.send(Message::RunAction(EntryAction::Hide));
+        drop(harness);
+        let harness = Harness::remembering_hidden_entries(Some(path.clone()));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(harness.shown(), vec!["play", "close"]);
+    }
+
+    #[test]
+    fn selection_stays_on_the_entry_when_the_matches_change() {
+        let mut harness = Harness::loaded();
+        harness.press(KeyCode::Down);
+        harness.press(KeyCode::Down);
+        let shift = keyboard::Modifiers {
+            shift: true,
+            ..Default::default()
+        };
+        harness.send(Message::EventOccurred(iced_native::Event::Keyboard(
+            Event::ModifiersChanged(shift),
+        )));
+        assert_eq!(harness.shown(), vec!["close"]);
+        harness.send(Message::EventOccurred(iced_native::Event::Keyboard(
+            Event::ModifiersChanged(keyboard::Modifiers::default()),
+        )));
+        assert_eq!(harness.shown(), vec!["next", "play", "close"]);
+        assert_eq!(harness.selected().as_deref(), Some("close"));
+    }
+
+    #[test]
+    fn entries_are_filtered_once_per_change() {
+        let mut harness = Harness::loaded();
+        let passes = harness.state().filter_passes;
+        harness.send(Message::AnimationFrame);
+        harness.send(Message::SpinnerTick);
+        assert_eq!(harness.state().filter_passes, passes);
+        let shift = keyboard::Modifiers {
+            shift: true,
+            ..Default::default()
+        };
+        for _ in 0..2 {
+            harness.send(Message::EventOccurred(iced_native::Event::Keyboard(
+                Event::ModifiersChanged(shift),
+            )));
+        }
+        assert_eq!(harness.shown(), vec!["close"]);
+        assert_eq!(harness.state().filter_passes, passes + 1);
+        harness.send(Message::InputChanged(String::from("clo")));
+        assert_eq!(harness.state().filter_passes, passes + 2);
+    }
+
+    #[test]
+    fn quick_mode_only_runs_a_unique_match() {
+        let mut harness = Harness::loaded();
+        harness.app.quick = true;
+        harness.send(Message::InputChanged(String::from("next")));
+        let id = harness.app.quick_run.as_ref().map(|run| run.id).unwrap();
+        // A countdown that outlived a change widening the list
+        harness.send(Message::InputChanged(String::new()));
+        harness.app.quick_run = Some(QuickRun { id, remaining: 1 });
+        harness.send(Message::QuickRunStep(id));
+        assert!(harness.app.pending_execution.entry.is_none());
+        harness.send(Message::InputChanged(String::from("next")));
+        let id = harness.app.quick_run.as_ref().map(|run| run.id).unwrap();
+        for _ in 0..QUICK_RUN_STEPS {
+            harness.send(Message::QuickRunStep(id));
+        }
+        let pending = harness.app.pending_execution.entry.as_ref();
+        assert_eq!(pending.map(|entry| entry.description()), Some("next"));
+    }
+
+    #[test]
+    fn selected_entry_runs_after_the_window_closes() {
+        let mut harness = Harness::loaded();
+        harness.send(Message::InputChanged(String::from("next")));
+        harness.send(Message::ExecuteSelected);
+        let pending = harness.app.pending_execution.entry.as_ref();
+        assert_eq!(pending.map(|entry| entry.description()), Some("next"));
+    }
+
+    #[test]
+    fn previews_are_worded_in_the_language_of_the_user() {
+        let firefox = preview::Window {
+            class: String::from("Firefox"),
+            title: String::from("Mozilla Firefox"),
+            workspace: String::from("1"),
+            ..Default::default()
+        };
+        let scene = preview::Scene {
+            focused: Some(firefox.clone()),
+            workspace: Some(String::from("1")),
+            windows: vec![firefox],
+        };
+        let effects = scene.explain("move container to workspace 2; workspace 2");
+        assert_eq!(
+            explanation(&effects, &Localizer::new("en")),
+            r#"moves "Mozilla Firefox" to workspace 2, then switches from workspace 1 to 2"#
+        );
+        assert_eq!(
+            explanation(&effects, &Localizer::new("de")),
+            "verschiebt „Mozilla Firefox“ to workspace 2, dann wechselt von Arbeitsfläche 1 zu 2"
+        );
+        let effects =
+            scene.explain(r#"[class="Alacritty"] kill; exec --no-startup-id pavucontrol"#);
+        assert_eq!(
+            explanation(&effects, &Localizer::new("en")),
+            r#"closes no window matching [class="Alacritty"], then starts pavucontrol"#
+        );
+    }
+
+    #[test]
+    fn action_copies_the_keys_of_the_selected_entry() {
+        let mut harness = Harness::loaded();
+        harness.press(KeyCode::Down);
+        harness.send(Message::RunAction(EntryAction::CopyKeys));
+        assert_eq!(harness.clipboard.contents.as_deref(), Some("<> p"));
+    }
+}
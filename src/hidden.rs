@@ -0,0 +1,110 @@
+//! Entries hidden from the searcher, kept in `$XDG_DATA_HOME/i3-conf-searcher/hidden.json`
+
+use crate::i3_config::{ConfigEntry, I3ConfigError};
+use crate::usage::usage_key;
+use crate::{json_file, paths};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+/// Keyed by group and description like [`crate::usage::UsageStats`],
+/// so entries stay hidden when the config around them is edited
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct HiddenEntries {
+    entries: BTreeSet<String>,
+}
+
+impl HiddenEntries {
+    /// `$XDG_DATA_HOME/i3-conf-searcher/hidden.json`
+    pub fn path() -> Option<PathBuf> {
+        paths::data_dir().map(|data| data.join("hidden.json"))
+    }
+
+    /// [`HiddenEntries::path`], an error without a data directory
+    pub fn required_path() -> Result<PathBuf> {
+        json_file::required(HiddenEntries::path(), "hidden entries", hidden_error)
+    }
+
+    /// A missing file means nothing was hidden yet
+    pub fn load(path: &Path) -> Result<HiddenEntries> {
+        json_file::load(path, hidden_error)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        json_file::save(self, path, hidden_error)
+    }
+
+    pub fn hide(&mut self, entry: &ConfigEntry) {
+        self.entries.insert(usage_key(entry));
+    }
+
+    pub fn contains(&self, entry: &ConfigEntry) -> bool {
+        self.entries.contains(&usage_key(entry))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Loads the hidden entries, adds the entry and saves them again
+    pub fn remember(path: &Path, entry: &ConfigEntry) -> Result<()> {
+        json_file::update(path, hidden_error, |hidden: &mut HiddenEntries| {
+            hidden.hide(entry)
+        })
+    }
+
+    /// Shows all entries again, returns how many were hidden
+    pub fn clear() -> Result<usize> {
+        let path = HiddenEntries::required_path()?;
+        let count = HiddenEntries::load(&path)?.len();
+        HiddenEntries::default().save(&path)?;
+        Ok(count)
+    }
+}
+
+fn hidden_error(error: io::Error) -> I3ConfigError {
+    I3ConfigError::FailedHiddenEntries(Arc::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_entries_are_kept_by_group_and_description() {
+        let path = std::env::temp_dir().join(format!(
+            "i3-conf-searcher-hidden-{}.json",
+            std::process::id()
+        ));
+        let entry = ConfigEntry::new(
+            String::from("Launch"),
+            String::from("Terminal"),
+            String::from("<> enter"),
+        );
+        let mut hidden = HiddenEntries::load(&path).unwrap();
+        assert!(hidden.is_empty());
+        hidden.hide(&entry);
+        hidden.save(&path).unwrap();
+        let loaded = HiddenEntries::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let moved = ConfigEntry::new(
+            String::from("Launch"),
+            String::from("Terminal"),
+            String::from("<> t"),
+        );
+        assert!(loaded.contains(&moved));
+        assert!(!loaded.contains(&ConfigEntry::new(
+            String::from("Launch"),
+            String::from("Browser"),
+            String::from("<> b"),
+        )));
+    }
+}
@@ -0,0 +1,188 @@
+//! Global hotkey grabbed on the X11 root window, lets `which-key` open the searcher
+//! without an i3 binding pointing at it
+
+use futures::channel::mpsc;
+use i3_conf_searcher::i3_config::I3ConfigError;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt, GrabMode, ModMask};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+/// Combination written like i3 keys, e.g. `Mod4+Shift+slash`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hotkey {
+    modifiers: u16,
+    keysym: u32,
+}
+
+impl std::str::FromStr for Hotkey {
+    type Err = I3ConfigError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+        let mut modifiers = 0;
+        for part in parts {
+            modifiers |= u16::from(match part.to_lowercase().as_str() {
+                "shift" => ModMask::SHIFT,
+                "ctrl" | "control" => ModMask::CONTROL,
+                "mod1" | "alt" => ModMask::M1,
+                "mod4" | "super" => ModMask::M4,
+                _ => return Err(hotkey_error(format!("unknown modifier {}", part))),
+            });
+        }
+        let keysym = keysym(key).ok_or_else(|| hotkey_error(format!("unknown key {}", key)))?;
+        Ok(Hotkey { modifiers, keysym })
+    }
+}
+
+/// Grabs the hotkey and sends a message for every press until the receiver is dropped.
+/// Fails if another program, usually i3 itself, already grabbed the same combination
+pub fn listen(hotkey: Hotkey) -> Result<mpsc::UnboundedReceiver<()>, I3ConfigError> {
+    let (connection, screen) = RustConnection::connect(None).map_err(hotkey_error)?;
+    let root = connection.setup().roots[screen].root;
+    let keycode = keycode(&connection, hotkey.keysym)?;
+    // The grab only applies to the exact modifiers, so Caps Lock and Num Lock are added as well
+    let locks = [
+        0,
+        ModMask::LOCK.into(),
+        ModMask::M2.into(),
+        u16::from(ModMask::LOCK | ModMask::M2),
+    ];
+    for lock in locks {
+        connection
+            .grab_key(
+                false,
+                root,
+                hotkey.modifiers | lock,
+                keycode,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+            )
+            .map_err(hotkey_error)?
+            .check()
+            .map_err(|_| hotkey_error("hotkey is already bound by another program"))?;
+    }
+    let (sender, receiver) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        while let Ok(event) = connection.wait_for_event() {
+            if let Event::KeyPress(_) = event {
+                if sender.unbounded_send(()).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    Ok(receiver)
+}
+
+fn keycode(connection: &RustConnection, keysym: u32) -> Result<u8, I3ConfigError> {
+    let setup = connection.setup();
+    let count = setup.max_keycode - setup.min_keycode + 1;
+    let mapping = connection
+        .get_keyboard_mapping(setup.min_keycode, count)
+        .map_err(hotkey_error)?
+        .reply()
+        .map_err(hotkey_error)?;
+    let per_keycode = usize::from(mapping.keysyms_per_keycode.max(1));
+    mapping
+        .keysyms
+        .iter()
+        .position(|mapped| *mapped == keysym)
+        .map(|index| setup.min_keycode + (index / per_keycode) as u8)
+        .ok_or_else(|| hotkey_error("hotkey isn't on the keyboard layout"))
+}
+
+/// X11 keysym of the i3 key name, only covering keys commonly used for launchers
+fn keysym(name: &str) -> Option<u32> {
+    let named = match name {
+        "space" => 0x20,
+        "apostrophe" => 0x27,
+        "comma" => 0x2c,
+        "minus" => 0x2d,
+        "period" => 0x2e,
+        "slash" => 0x2f,
+        "semicolon" => 0x3b,
+        "equal" => 0x3d,
+        "bracketleft" => 0x5b,
+        "backslash" => 0x5c,
+        "bracketright" => 0x5d,
+        "grave" => 0x60,
+        "BackSpace" => 0xff08,
+        "Tab" => 0xff09,
+        "Return" => 0xff0d,
+        "Escape" => 0xff1b,
+        "Home" => 0xff50,
+        "Left" => 0xff51,
+        "Up" => 0xff52,
+        "Right" => 0xff53,
+        "Down" => 0xff54,
+        "Prior" => 0xff55,
+        "Next" => 0xff56,
+        "End" => 0xff57,
+        "Print" => 0xff61,
+        "Insert" => 0xff63,
+        "Delete" => 0xffff,
+        _ => {
+            // Letters and digits are their lower case ASCII codes, F1 to F35 follow each other
+            let mut chars = name.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphanumeric() => {
+                    Some(u32::from(c.to_ascii_lowercase()))
+                }
+                _ => name
+                    .strip_prefix('F')
+                    .and_then(|number| number.parse::<u32>().ok())
+                    .filter(|number| (1..=35).contains(number))
+                    .map(|number| 0xffbe + number - 1),
+            };
+        }
+    };
+    Some(named)
+}
+
+fn hotkey_error(error: impl std::fmt::Display) -> I3ConfigError {
+    I3ConfigError::FailedKeyboardGrab(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Option<(u16, u32)> {
+        text.parse::<Hotkey>()
+            .ok()
+            .map(|hotkey| (hotkey.modifiers, hotkey.keysym))
+    }
+
+    #[test]
+    fn parse_modifiers_and_synonyms() {
+        let super_shift = u16::from(ModMask::M4 | ModMask::SHIFT);
+        assert_eq!(parse("Mod4+Shift+slash"), Some((super_shift, 0x2f)));
+        assert_eq!(parse("super + shift + slash"), Some((super_shift, 0x2f)));
+        assert_eq!(parse("Ctrl+space"), parse("Control+space"));
+        assert_eq!(parse("Alt+Tab"), Some((u16::from(ModMask::M1), 0xff09)));
+        assert_eq!(parse("Mod1+Tab"), parse("Alt+Tab"));
+        assert_eq!(parse("Return"), Some((0, 0xff0d)));
+        assert_eq!(parse("Hyper+a"), None);
+        assert_eq!(parse("Mod4+"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn keysyms_of_letters_digits_and_function_keys() {
+        assert_eq!(keysym("a"), Some(0x61));
+        assert_eq!(keysym("A"), Some(0x61));
+        assert_eq!(keysym("7"), Some(0x37));
+        assert_eq!(keysym("F1"), Some(0xffbe));
+        assert_eq!(keysym("F12"), Some(0xffc9));
+        assert_eq!(keysym("F35"), Some(0xffe0));
+        assert_eq!(keysym("F0"), None);
+        assert_eq!(keysym("F36"), None);
+        assert_eq!(keysym("BackSpace"), Some(0xff08));
+        assert_eq!(keysym("Delete"), Some(0xffff));
+        assert_eq!(keysym("backspace"), None);
+        assert_eq!(keysym("é"), None);
+        assert_eq!(keysym("ab"), None);
+    }
+}
@@ -0,0 +1,164 @@
+//! Lightweight tokenizing of i3 commands, used for highlighting them
+
+/// Part of a command a token belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// `[class="Firefox"]`
+    Criteria,
+    /// First word of every chained command, like `move` or `exec`
+    Verb,
+    Argument,
+    /// `;` or `,` between chained commands
+    Separator,
+    Whitespace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+}
+
+/// Splits a command into tokens that concatenate back to the exact command.
+/// Quoted strings are single arguments and separators inside quotes or criteria are ignored
+pub fn tokenize(command: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut expect_verb = true;
+    let mut rest = command;
+    while let Some(first) = rest.chars().next() {
+        let (kind, length) = match first {
+            ';' | ',' => {
+                expect_verb = true;
+                (TokenKind::Separator, 1)
+            }
+            '[' if expect_verb => (TokenKind::Criteria, quoted_end(rest, ']')),
+            first if first.is_whitespace() => (
+                TokenKind::Whitespace,
+                rest.find(|c: char| !c.is_whitespace())
+                    .unwrap_or(rest.len()),
+            ),
+            _ => {
+                let length = if first == '"' {
+                    quoted_end(rest, '"')
+                } else {
+                    rest.find(|c: char| c.is_whitespace() || c == ';' || c == ',')
+                        .unwrap_or(rest.len())
+                };
+                let kind = if expect_verb {
+                    TokenKind::Verb
+                } else {
+                    TokenKind::Argument
+                };
+                expect_verb = false;
+                (kind, length)
+            }
+        };
+        let (text, remaining) = rest.split_at(length);
+        tokens.push(Token { kind, text });
+        rest = remaining;
+    }
+    tokens
+}
+
+/// Commands chained with `;` or `,`, trimmed and without the separators.
+/// Commands after a `,` still apply to the criteria before it
+pub fn actions(command: &str) -> Vec<&str> {
+    let mut actions = vec![];
+    let mut start = 0;
+    let mut position = 0;
+    for token in tokenize(command) {
+        if token.kind == TokenKind::Separator {
+            actions.push(&command[start..position]);
+            start = position + token.text.len();
+        }
+        position += token.text.len();
+    }
+    actions.push(&command[start..]);
+    actions
+        .into_iter()
+        .map(str::trim)
+        .filter(|action| !action.is_empty())
+        .collect()
+}
+
+/// Length up to and including `end`, skipping the opening character, quoted parts and escapes.
+/// Unterminated text runs to the end
+fn quoted_end(text: &str, end: char) -> usize {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (index, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' if end != '"' => in_quotes = !in_quotes,
+            c if c == end && !in_quotes => return index + c.len_utf8(),
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(command: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(command)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn tokenize_chained_commands() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(r#"[class="Fire; fox"] move container to workspace "2: www"; workspace 2"#),
+            vec![
+                (Criteria, r#"[class="Fire; fox"]"#),
+                (Verb, "move"),
+                (Argument, "container"),
+                (Argument, "to"),
+                (Argument, "workspace"),
+                (Argument, r#""2: www""#),
+                (Separator, ";"),
+                (Verb, "workspace"),
+                (Argument, "2"),
+            ]
+        );
+        assert_eq!(
+            kinds("focus left, move left"),
+            vec![
+                (Verb, "focus"),
+                (Argument, "left"),
+                (Separator, ","),
+                (Verb, "move"),
+                (Argument, "left"),
+            ]
+        );
+        for command in [
+            r#"exec --no-startup-id "notify-send \"hi\"""#,
+            "[unterminated move",
+            "  ",
+            "",
+        ] {
+            let joined: String = tokenize(command).iter().map(|token| token.text).collect();
+            assert_eq!(joined, command);
+        }
+    }
+
+    #[test]
+    fn split_chained_actions() {
+        assert_eq!(
+            actions(r#"[class="a;b"] focus, move left; exec "notify-send 'x; y'";"#),
+            vec![
+                r#"[class="a;b"] focus"#,
+                "move left",
+                r#"exec "notify-send 'x; y'""#
+            ]
+        );
+        assert_eq!(actions("kill"), vec!["kill"]);
+        assert!(actions(" ; ").is_empty());
+    }
+}
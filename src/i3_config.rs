@@ -1,9 +1,15 @@
-use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
+use crate::matcher::{Matcher, MatcherKind};
+use crate::providers::Provider;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use regex::Regex;
+use serde::Deserialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use thiserror::Error;
-#[cfg(target_family = "unix")]
-use tokio_i3ipc::I3;
 
 type Result<T> = std::result::Result<T, I3ConfigError>;
 
@@ -12,49 +18,177 @@ pub enum I3ConfigError {
     #[error("failed to parse config")]
     ConfigParsingError,
     #[allow(dead_code)]
-    #[error("failed to query i3 for config")]
-    FailedI3Query,
+    #[error("failed to communicate with i3")]
+    FailedI3Query(#[source] Arc<std::io::Error>),
     #[allow(dead_code)]
     #[error("i3 not supported on this platform")]
     UnsupportedPlatform,
-    #[error("Failed to download file")]
-    FailedGetRequest,
-}
-
-#[cfg(target_family = "unix")]
-async fn get_i3_config_ipc() -> Result<String> {
-    let mut i3 = I3::connect()
-        .await
-        .map_err(|_| I3ConfigError::FailedI3Query)?;
-    let config = i3
-        .get_config()
-        .await
-        .map_err(|_| I3ConfigError::FailedI3Query)?;
-    Ok(config.config)
-}
-
-#[cfg(target_family = "windows")]
-async fn get_i3_config_ipc() -> Result<String> {
-    Err(I3ConfigError::UnsupportedPlatform)
-}
-
-async fn download_i3_config(url: &str) -> Result<String> {
-    // TODO (David): This method doesn't really
-    // provide much detail about why it failed.
-    // Maybe add some error propagation. Thiserror
-    // makes that easy
-    let response = reqwest::get(url)
-        .await
-        .map_err(|_| I3ConfigError::FailedGetRequest)?;
-    if !response.status().is_success() {
-        eprintln!("Web request failed with status {:?}", response.status());
-        return Err(I3ConfigError::FailedGetRequest);
-    }
-    let config = response
-        .text()
-        .await
-        .map_err(|_| I3ConfigError::FailedGetRequest)?;
-    Ok(config)
+    #[cfg(feature = "remote")]
+    #[error("failed to download config")]
+    FailedGetRequest(#[source] Arc<reqwest::Error>),
+    #[error("no entry matches \"{0}\"")]
+    NoMatchingEntry(String),
+    #[error("multiple entries match \"{0}\" equally well")]
+    AmbiguousMatch(String),
+    #[error("entry has no bound command")]
+    MissingCommand,
+    #[cfg(all(target_family = "unix", feature = "ipc"))]
+    #[error("i3 failed to run command: {0}")]
+    CommandFailed(String),
+    #[error("found {0} malformed annotations")]
+    InvalidAnnotations(usize),
+    #[error("failed to write config")]
+    FailedConfigWrite(#[source] Arc<std::io::Error>),
+    #[error("\"{0}\" can't be used in an annotation")]
+    InvalidAnnotationField(String),
+    #[error("config file changed since it was loaded, reload i3 and try again")]
+    ConfigChangedOnDisk,
+    #[error("no config backups to restore")]
+    NoBackup,
+    #[error("failed to read preferences")]
+    FailedPreferencesRead(#[source] Arc<std::io::Error>),
+    #[error("invalid preferences")]
+    InvalidPreferences(#[source] Arc<toml::de::Error>),
+    #[error("failed to start process")]
+    FailedSpawn(#[source] Arc<std::io::Error>),
+    #[error("failed to read config file")]
+    FailedConfigRead(#[source] Arc<std::io::Error>),
+    #[error("downloaded config has checksum {actual} instead of {expected}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("git failed: {0}")]
+    FailedGitSync(String),
+    #[error("failed to parse imported bindings")]
+    InvalidImport(#[source] Arc<serde_json::Error>),
+    #[error("{0} checks failed")]
+    FailedChecks(usize),
+    #[error("built without the {0} feature")]
+    MissingFeature(&'static str),
+    #[error("failed to grab keyboard: {0}")]
+    FailedKeyboardGrab(String),
+    #[error("failed to lock the searcher instance")]
+    FailedInstanceLock(#[source] Arc<std::io::Error>),
+    #[error("invalid translation: {0}")]
+    InvalidTranslation(String),
+    #[error("failed to take the window from the window manager: {0}")]
+    FailedOverrideRedirect(String),
+    #[error("failed to update usage stats")]
+    FailedUsageStats(#[source] Arc<std::io::Error>),
+    #[error("invalid state document: {0}")]
+    InvalidState(String),
+    #[error("environment variable {0} is not set")]
+    UnsetVariable(String),
+    #[error("i3 rejected the config: {0}")]
+    InvalidI3Config(String),
+    #[error("failed to show the tray icon: {0}")]
+    FailedTray(String),
+    #[error("control request failed: {0}")]
+    FailedControl(String),
+    #[error("failed to remember the window geometry")]
+    FailedGeometry(#[source] Arc<std::io::Error>),
+    #[error("failed to remember the hidden entries")]
+    FailedHiddenEntries(#[source] Arc<std::io::Error>),
+}
+
+impl I3ConfigError {
+    /// Stable identifier for scripts consuming error output
+    pub fn code(&self) -> &'static str {
+        match self {
+            I3ConfigError::ConfigParsingError => "config_parsing",
+            I3ConfigError::FailedI3Query(_) => "i3_query",
+            I3ConfigError::UnsupportedPlatform => "unsupported_platform",
+            #[cfg(feature = "remote")]
+            I3ConfigError::FailedGetRequest(_) => "download",
+            I3ConfigError::NoMatchingEntry(_) => "no_match",
+            I3ConfigError::AmbiguousMatch(_) => "ambiguous_match",
+            I3ConfigError::MissingCommand => "missing_command",
+            #[cfg(all(target_family = "unix", feature = "ipc"))]
+            I3ConfigError::CommandFailed(_) => "command_failed",
+            I3ConfigError::InvalidAnnotations(_) => "invalid_annotations",
+            I3ConfigError::FailedConfigWrite(_) => "config_write",
+            I3ConfigError::InvalidAnnotationField(_) => "invalid_annotation_field",
+            I3ConfigError::ConfigChangedOnDisk => "config_changed",
+            I3ConfigError::NoBackup => "no_backup",
+            I3ConfigError::FailedPreferencesRead(_) => "preferences_read",
+            I3ConfigError::InvalidPreferences(_) => "invalid_preferences",
+            I3ConfigError::FailedSpawn(_) => "spawn",
+            I3ConfigError::FailedConfigRead(_) => "config_read",
+            I3ConfigError::ChecksumMismatch { .. } => "checksum_mismatch",
+            I3ConfigError::FailedGitSync(_) => "git_sync",
+            I3ConfigError::InvalidImport(_) => "invalid_import",
+            I3ConfigError::FailedChecks(_) => "failed_checks",
+            I3ConfigError::MissingFeature(_) => "missing_feature",
+            I3ConfigError::FailedKeyboardGrab(_) => "keyboard_grab",
+            I3ConfigError::FailedInstanceLock(_) => "instance_lock",
+            I3ConfigError::InvalidTranslation(_) => "invalid_translation",
+            I3ConfigError::FailedOverrideRedirect(_) => "override_redirect",
+            I3ConfigError::FailedUsageStats(_) => "usage_stats",
+            I3ConfigError::InvalidState(_) => "invalid_state",
+            I3ConfigError::UnsetVariable(_) => "unset_variable",
+            I3ConfigError::InvalidI3Config(_) => "invalid_i3_config",
+            I3ConfigError::FailedTray(_) => "tray",
+            I3ConfigError::FailedControl(_) => "control",
+            I3ConfigError::FailedGeometry(_) => "geometry",
+            I3ConfigError::FailedHiddenEntries(_) => "hidden_entries",
+        }
+    }
+
+    #[cfg(all(target_family = "unix", feature = "ipc"))]
+    pub(crate) fn ipc(error: std::io::Error) -> Self {
+        I3ConfigError::FailedI3Query(Arc::new(error))
+    }
+
+    /// Error for talking to i3 when the IPC client isn't available
+    #[cfg(not(all(target_family = "unix", feature = "ipc")))]
+    pub(crate) fn ipc_unavailable() -> Self {
+        if cfg!(target_family = "unix") {
+            I3ConfigError::MissingFeature("ipc")
+        } else {
+            I3ConfigError::UnsupportedPlatform
+        }
+    }
+
+    /// Stable process exit status for each kind of error.
+    /// Existing values must not change, new variants get new numbers
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            I3ConfigError::ConfigParsingError => 2,
+            I3ConfigError::FailedI3Query(_) => 3,
+            I3ConfigError::UnsupportedPlatform => 4,
+            #[cfg(feature = "remote")]
+            I3ConfigError::FailedGetRequest(_) => 5,
+            I3ConfigError::NoMatchingEntry(_) => 6,
+            I3ConfigError::AmbiguousMatch(_) => 7,
+            I3ConfigError::MissingCommand => 8,
+            #[cfg(all(target_family = "unix", feature = "ipc"))]
+            I3ConfigError::CommandFailed(_) => 9,
+            I3ConfigError::InvalidAnnotations(_) => 10,
+            I3ConfigError::FailedConfigWrite(_) => 11,
+            I3ConfigError::InvalidAnnotationField(_) => 12,
+            I3ConfigError::ConfigChangedOnDisk => 13,
+            I3ConfigError::NoBackup => 14,
+            I3ConfigError::FailedPreferencesRead(_) => 15,
+            I3ConfigError::InvalidPreferences(_) => 16,
+            I3ConfigError::FailedSpawn(_) => 17,
+            I3ConfigError::FailedConfigRead(_) => 18,
+            I3ConfigError::ChecksumMismatch { .. } => 19,
+            I3ConfigError::FailedGitSync(_) => 20,
+            I3ConfigError::InvalidImport(_) => 21,
+            I3ConfigError::FailedChecks(_) => 22,
+            I3ConfigError::MissingFeature(_) => 23,
+            I3ConfigError::FailedKeyboardGrab(_) => 24,
+            I3ConfigError::FailedInstanceLock(_) => 25,
+            I3ConfigError::InvalidTranslation(_) => 26,
+            I3ConfigError::FailedOverrideRedirect(_) => 27,
+            I3ConfigError::FailedUsageStats(_) => 28,
+            I3ConfigError::InvalidState(_) => 29,
+            I3ConfigError::UnsetVariable(_) => 30,
+            I3ConfigError::InvalidI3Config(_) => 31,
+            I3ConfigError::FailedTray(_) => 32,
+            I3ConfigError::FailedControl(_) => 33,
+            I3ConfigError::FailedGeometry(_) => 34,
+            I3ConfigError::FailedHiddenEntries(_) => 35,
+        }
+    }
 }
 
 const SHIFT_PATTERN: &str = "<shift>";
@@ -80,6 +214,78 @@ impl Modifiers {
         }
     }
 
+    /// Modifiers mentioned in a keys column or a `bindsym` key combination.
+    /// Synonyms like `Super`, `Mod4` and `$mod` are recognized,
+    /// variables are resolved through `variables` with `$mod` and `$alt` falling back to i3 defaults
+    fn from_keys(keys: &str, variables: &HashMap<String, String>) -> Self {
+        let lower_case_keys = keys.to_lowercase();
+        let mut modifiers = Modifiers::new(
+            lower_case_keys.contains(SHIFT_PATTERN),
+            lower_case_keys.contains(CONTROL_PATTERN),
+            lower_case_keys.contains(ALT_PATTERN),
+            lower_case_keys.contains(META_PATTERN),
+        );
+        let tokens = keys
+            .split(|c: char| c.is_whitespace() || matches!(c, '+' | '<' | '>'))
+            .filter(|token| !token.is_empty());
+        for token in tokens {
+            let token = resolve_variable(token, variables).to_lowercase();
+            match token.as_str() {
+                "shift" => modifiers.shift = true,
+                "ctrl" | "control" => modifiers.control = true,
+                "mod1" | "alt" | "$alt" => modifiers.alt = true,
+                "mod4" | "super" | "win" | "meta" | "logo" | "$mod" => modifiers.meta = true,
+                _ => (),
+            }
+        }
+        modifiers
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Modifiers::default()
+    }
+
+    pub fn shift(&self) -> bool {
+        self.shift
+    }
+
+    fn union(&self, other: &Modifiers) -> Self {
+        Modifiers::new(
+            self.shift || other.shift,
+            self.control || other.control,
+            self.alt || other.alt,
+            self.meta || other.meta,
+        )
+    }
+
+    /// Modifiers of `self` that aren't part of `other`
+    fn difference(&self, other: &Modifiers) -> Self {
+        Modifiers::new(
+            self.shift && !other.shift,
+            self.control && !other.control,
+            self.alt && !other.alt,
+            self.meta && !other.meta,
+        )
+    }
+
+    /// Names of the modifiers in the order they are usually written in a binding
+    fn names(&self) -> Vec<&'static str> {
+        let mut names = vec![];
+        if self.meta {
+            names.push("Super");
+        }
+        if self.control {
+            names.push("Ctrl");
+        }
+        if self.alt {
+            names.push("Alt");
+        }
+        if self.shift {
+            names.push("Shift");
+        }
+        names
+    }
+
     pub fn description(&self) -> String {
         let mut description = String::new();
         if self.meta {
@@ -102,62 +308,400 @@ impl Modifiers {
     }
 }
 
+/// Key combination of a binding split into modifiers and the key completing it
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyCombo {
+    modifiers: Modifiers,
+    key: String,
+}
+
+impl KeyCombo {
+    pub fn new(modifiers: Modifiers, key: String) -> Self {
+        KeyCombo { modifiers, key }
+    }
+
+    /// Same modifiers and key, ignoring the case of key names like `Return`
+    pub fn is_pressed_by(&self, chord: &KeyCombo) -> bool {
+        self.modifiers == chord.modifiers && self.key.eq_ignore_ascii_case(&chord.key)
+    }
+
+    /// Parses `bindsym` combinations like `$mod+Shift+q` and ones written in the keys column
+    fn parse(keys: &str, variables: &HashMap<String, String>) -> Self {
+        let keys = keys.trim();
+        let (modifiers, key) =
+            match keys.rsplit_once(|c: char| matches!(c, '+' | '>') || c.is_whitespace()) {
+                Some((modifiers, key)) => (modifiers, key),
+                None => ("", keys),
+            };
+        KeyCombo {
+            modifiers: Modifiers::from_keys(modifiers, variables),
+            key: resolve_variable(key.trim(), variables).to_owned(),
+        }
+    }
+
+    /// `button1` to `button9`, i3 also accepts these instead of keys
+    pub fn is_mouse_button(&self) -> bool {
+        self.key
+            .to_lowercase()
+            .strip_prefix("button")
+            .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+    }
+
+    /// What still has to be pressed while holding `held`.
+    /// `None` if `held` contains modifiers the combination doesn't use
+    pub fn remaining(&self, held: &Modifiers) -> Option<String> {
+        if !held.difference(&self.modifiers).is_empty() {
+            return None;
+        }
+        let mut parts = self.modifiers.difference(held).names();
+        parts.push(&self.key);
+        Some(parts.join("+"))
+    }
+}
+
+impl std::fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = self.modifiers.names();
+        parts.push(&self.key);
+        write!(f, "{}", parts.join("+"))
+    }
+}
+
+/// Key combinations pressed one after another to trigger a binding
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct KeySequence(Vec<KeyCombo>);
+
+impl KeySequence {
+    /// Combinations separated by `then`, like `<> r then h`
+    fn parse(keys: &str, variables: &HashMap<String, String>) -> Self {
+        let words: Vec<&str> = keys.split_whitespace().collect();
+        KeySequence(
+            words
+                .split(|word| *word == "then")
+                .filter(|chord| !chord.is_empty())
+                .map(|chord| KeyCombo::parse(&chord.join(" "), variables))
+                .collect(),
+        )
+    }
+
+    pub fn combos(&self) -> &[KeyCombo] {
+        &self.0
+    }
+
+    /// Keys left to press while holding `held`, see `KeyCombo::remaining`
+    pub fn remaining(&self, held: &Modifiers) -> Option<String> {
+        let (first, rest) = self.0.split_first()?;
+        let mut parts = vec![first.remaining(held)?];
+        parts.extend(rest.iter().map(|combo| combo.to_string()));
+        Some(parts.join(" "))
+    }
+}
+
+/// What triggers a binding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingKind {
+    Keyboard,
+    /// Mouse button, usually with `--whole-window` or `--border`
+    Mouse,
+}
+
+/// Flag given to a binding before its keys. Some of them are only understood by sway
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindingFlag {
+    Release,
+    Border,
+    WholeWindow,
+    ExcludeTitlebar,
+    Locked,
+    ToCode,
+    Inhibited,
+    NoWarn,
+    NoRepeat,
+    /// Binding only applies to one input device (sway)
+    InputDevice(String),
+    Other(String),
+}
+
+impl BindingFlag {
+    fn parse(flag: &str) -> Self {
+        match flag {
+            "--release" => BindingFlag::Release,
+            "--border" => BindingFlag::Border,
+            "--whole-window" => BindingFlag::WholeWindow,
+            "--exclude-titlebar" => BindingFlag::ExcludeTitlebar,
+            "--locked" => BindingFlag::Locked,
+            "--to-code" => BindingFlag::ToCode,
+            "--inhibited" => BindingFlag::Inhibited,
+            "--no-warn" => BindingFlag::NoWarn,
+            "--no-repeat" => BindingFlag::NoRepeat,
+            _ => match flag.strip_prefix("--input-device=") {
+                Some(device) => BindingFlag::InputDevice(device.to_owned()),
+                None => BindingFlag::Other(flag.to_owned()),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for BindingFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingFlag::Release => write!(f, "--release"),
+            BindingFlag::Border => write!(f, "--border"),
+            BindingFlag::WholeWindow => write!(f, "--whole-window"),
+            BindingFlag::ExcludeTitlebar => write!(f, "--exclude-titlebar"),
+            BindingFlag::Locked => write!(f, "--locked"),
+            BindingFlag::ToCode => write!(f, "--to-code"),
+            BindingFlag::Inhibited => write!(f, "--inhibited"),
+            BindingFlag::NoWarn => write!(f, "--no-warn"),
+            BindingFlag::NoRepeat => write!(f, "--no-repeat"),
+            BindingFlag::InputDevice(device) => write!(f, "--input-device={}", device),
+            BindingFlag::Other(flag) => write!(f, "{}", flag),
+        }
+    }
+}
+
+/// Binding line found after an annotation
+struct Binding {
+    keys: String,
+    command: String,
+    flags: Vec<BindingFlag>,
+}
+
+/// Identifies an entry within its config, assigned when the entry is added so it stays the same
+/// when entries are sorted, filtered or hidden
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntryId(usize);
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ConfigEntry {
+    id: EntryId,
     group: String,
     description: String,
     keys: String,
+    modifiers: Modifiers,
+    sequence: KeySequence,
+    bound_keys: Option<String>,
+    command: Option<String>,
+    flags: Vec<BindingFlag>,
+    line: usize,
+    binding_line: Option<usize>,
+    mode: Option<String>,
+    source: Option<ConfigSource>,
+    provider: Option<Provider>,
+    /// Keys of entries collapsed into this one by `collapse_duplicates`
+    alternative_keys: Vec<String>,
     description_indices: Option<Vec<usize>>,
     group_indices: Option<Vec<usize>>,
+    keys_indices: Option<Vec<usize>>,
 }
 
 impl ConfigEntry {
     pub fn new(group: String, description: String, keys: String) -> Self {
         ConfigEntry {
+            id: EntryId::default(),
+            modifiers: Modifiers::from_keys(&keys, &HashMap::new()),
+            sequence: KeySequence::default(),
             group,
             description,
             keys,
+            bound_keys: None,
+            command: None,
+            flags: vec![],
+            line: 0,
+            binding_line: None,
+            mode: None,
+            source: None,
+            provider: None,
+            alternative_keys: vec![],
             description_indices: None,
             group_indices: None,
+            keys_indices: None,
         }
     }
 
+    /// Sets the key combination and command of the `bindsym` line following the annotation
+    pub fn with_binding(mut self, keys: String, command: String) -> Self {
+        self.modifiers = self
+            .modifiers
+            .union(&Modifiers::from_keys(&keys, &HashMap::new()));
+        self.sequence = KeySequence(vec![KeyCombo::parse(&keys, &HashMap::new())]);
+        self.bound_keys = Some(keys);
+        self.command = Some(command);
+        self
+    }
+
+    /// Sets a command for entries that aren't key bindings
+    pub fn with_command(mut self, command: String) -> Self {
+        self.command = Some(command);
+        self
+    }
+
+    pub fn with_line(mut self, line: usize) -> Self {
+        self.line = line;
+        self
+    }
+
+    pub(crate) fn with_id(mut self, id: EntryId) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn with_flags(mut self, flags: Vec<BindingFlag>) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn with_binding_line(mut self, line: usize) -> Self {
+        self.binding_line = Some(line);
+        self
+    }
+
+    pub fn with_mode(mut self, mode: String) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn with_provider(mut self, provider: Provider) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
     pub fn group(&self) -> &str {
         &self.group
     }
 
-    pub fn description(&self) -> &str {
-        &self.description
+    /// Segments of a group written as `parent/child`
+    pub fn group_path(&self) -> Vec<&str> {
+        group_path(&self.group)
+    }
+
+    /// Whether the segments of `group` appear in order in the group path, ignoring case.
+    /// `Launch` finds entries of `Launch/Browsers` and `Apps/Launch`
+    pub fn is_in_group(&self, group: &str) -> bool {
+        let lowercase =
+            |path: Vec<&str>| -> Vec<String> { path.into_iter().map(str::to_lowercase).collect() };
+        let path = lowercase(self.group_path());
+        let wanted = lowercase(group_path(group));
+        !wanted.is_empty()
+            && path
+                .windows(wanted.len())
+                .any(|segments| segments == &wanted[..])
     }
 
-    pub fn full_text(&self) -> String {
-        format!("{} {}", self.group, self.description)
+    pub fn description(&self) -> &str {
+        &self.description
     }
 
     pub fn keys(&self) -> &str {
         &self.keys
     }
 
+    pub fn command(&self) -> Option<&str> {
+        self.command.as_deref()
+    }
+
+    /// Key combination as written in the `bindsym` line, e.g. `$mod+Shift+q`
+    pub fn bound_keys(&self) -> Option<&str> {
+        self.bound_keys.as_deref()
+    }
+
+    pub fn id(&self) -> EntryId {
+        self.id
+    }
+
+    /// Line of the annotation in the config, starting from 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Flags of the binding such as `--release` or sway's `--locked`
+    pub fn flags(&self) -> &[BindingFlag] {
+        &self.flags
+    }
+
+    /// Line of the `bindsym` following the annotation, starting from 1
+    pub fn binding_line(&self) -> Option<usize> {
+        self.binding_line
+    }
+
+    /// i3 mode the binding belongs to, `None` for the default mode
+    pub fn mode(&self) -> Option<&str> {
+        self.mode.as_deref()
+    }
+
+    /// Where the entry was loaded from, entries of merged configs keep their own source
+    pub fn source(&self) -> Option<&ConfigSource> {
+        self.source.as_ref()
+    }
+
+    /// Other keys running the same command, see [`ConfigMetadata::collapse_duplicates`]
+    pub fn alternative_keys(&self) -> &[String] {
+        &self.alternative_keys
+    }
+
+    /// Provider that found the entry, `None` for annotated bindings
+    pub fn provider(&self) -> Option<Provider> {
+        self.provider
+    }
+
     pub fn matches_modifiers(&self, modifiers: &Modifiers) -> bool {
-        let lower_case_keys = self.keys.to_lowercase();
-        if modifiers.shift && !lower_case_keys.contains(SHIFT_PATTERN) {
-            return false;
-        }
-        if modifiers.control && !lower_case_keys.contains(CONTROL_PATTERN) {
-            return false;
+        (!modifiers.shift || self.modifiers.shift)
+            && (!modifiers.control || self.modifiers.control)
+            && (!modifiers.alt || self.modifiers.alt)
+            && (!modifiers.meta || self.modifiers.meta)
+    }
+
+    /// Like `matches_modifiers` but also rejects entries needing modifiers that aren't held.
+    /// Without any modifiers held every entry matches
+    pub fn matches_modifiers_strictly(&self, modifiers: &Modifiers) -> bool {
+        modifiers.is_empty() || self.modifiers == *modifiers
+    }
+
+    /// Keys of the binding, empty if the annotation isn't followed by a binding.
+    /// Sequences documented in the keys column like `<> r then h` take precedence over the binding
+    pub fn sequence(&self) -> &KeySequence {
+        &self.sequence
+    }
+
+    /// Whether pressing `chord` triggers the binding, or starts its sequence
+    pub fn is_bound_to(&self, chord: &KeyCombo) -> bool {
+        self.sequence
+            .combos()
+            .first()
+            .is_some_and(|combo| combo.is_pressed_by(chord))
+    }
+
+    pub fn kind(&self) -> BindingKind {
+        match self.sequence.combos().last() {
+            Some(combo) if combo.is_mouse_button() => BindingKind::Mouse,
+            _ => BindingKind::Keyboard,
         }
-        if modifiers.alt && !lower_case_keys.contains(ALT_PATTERN) {
-            return false;
+    }
+
+    /// Recomputes modifiers and key sequence of the keys column and binding
+    /// with the variables of the config
+    fn resolve_variables(&mut self, variables: &HashMap<String, String>) {
+        let mut modifiers = Modifiers::from_keys(&self.keys, variables);
+        if let Some(bound_keys) = &self.bound_keys {
+            modifiers = modifiers.union(&Modifiers::from_keys(bound_keys, variables));
+            self.sequence = KeySequence(vec![KeyCombo::parse(bound_keys, variables)]);
         }
-        if modifiers.meta && !lower_case_keys.contains(META_PATTERN) {
-            return false;
+        let documented = KeySequence::parse(&self.keys, variables);
+        if documented.combos().len() > 1 {
+            self.sequence = documented;
         }
-        true
+        self.modifiers = modifiers;
+    }
+
+    pub fn set_annotation(&mut self, group: String, description: String) {
+        self.group = group;
+        self.description = description;
     }
 
     pub fn clear_matches(&mut self) {
         self.group_indices = None;
         self.description_indices = None;
+        self.keys_indices = None;
     }
 
     pub fn set_group_indices(&mut self, indices: Vec<usize>) {
@@ -168,11 +712,18 @@ impl ConfigEntry {
         self.description_indices = Some(indices);
     }
 
+    pub fn set_keys_indices(&mut self, indices: Vec<usize>) {
+        self.keys_indices = Some(indices);
+    }
+
     pub fn matched_description(&self) -> Vec<MatchElement> {
-        split_to_groups_by_indices(&self.description(), self.description_indices.as_ref())
+        split_to_groups_by_indices(self.description(), self.description_indices.as_ref())
     }
     pub fn matched_group(&self) -> Vec<MatchElement> {
-        split_to_groups_by_indices(&self.group(), self.group_indices.as_ref())
+        split_to_groups_by_indices(self.group(), self.group_indices.as_ref())
+    }
+    pub fn matched_keys(&self) -> Vec<MatchElement> {
+        split_to_groups_by_indices(self.keys(), self.keys_indices.as_ref())
     }
 }
 
@@ -182,6 +733,40 @@ pub enum MatchElement {
     Unmatched(String),
 }
 
+impl MatchElement {
+    fn text(&self) -> &str {
+        match self {
+            MatchElement::Matched(text) | MatchElement::Unmatched(text) => text,
+        }
+    }
+}
+
+/// Cuts highlighted text to at most `max_chars` characters, ending with an ellipsis if it was cut
+pub fn truncate_elements(elements: Vec<MatchElement>, max_chars: usize) -> Vec<MatchElement> {
+    let total: usize = elements
+        .iter()
+        .map(|element| element.text().chars().count())
+        .sum();
+    if total <= max_chars {
+        return elements;
+    }
+    let mut remaining = max_chars.saturating_sub(1);
+    let mut truncated = vec![];
+    for element in elements {
+        if remaining == 0 {
+            break;
+        }
+        let text: String = element.text().chars().take(remaining).collect();
+        remaining -= text.chars().count();
+        truncated.push(match element {
+            MatchElement::Matched(_) => MatchElement::Matched(text),
+            MatchElement::Unmatched(_) => MatchElement::Unmatched(text),
+        });
+    }
+    truncated.push(MatchElement::Unmatched(String::from("…")));
+    truncated
+}
+
 fn split_to_groups_by_indices(text: &str, indices: Option<&Vec<usize>>) -> Vec<MatchElement> {
     if let Some(indices) = indices {
         let mut parts = vec![];
@@ -221,18 +806,193 @@ fn split_to_groups_by_indices(text: &str, indices: Option<&Vec<usize>>) -> Vec<M
     }
 }
 
+/// Where the config was loaded from
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    Ipc,
+    File(PathBuf),
+    Url(String),
+    /// Repository url and path of the config inside it
+    Git(String, PathBuf),
+    /// JSON file of bindings merged into the config
+    Import(PathBuf),
+}
+
+impl ConfigSource {
+    /// Short name shown next to entries when sources are merged
+    pub fn badge(&self) -> String {
+        match self {
+            ConfigSource::Ipc => String::from("i3"),
+            ConfigSource::File(path) | ConfigSource::Import(path) => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| String::from("file")),
+            ConfigSource::Url(_) => String::from("url"),
+            ConfigSource::Git(..) => String::from("git"),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Ipc => write!(f, "i3 IPC"),
+            ConfigSource::File(path) => write!(f, "{}", path.display()),
+            ConfigSource::Url(url) => write!(f, "{}", url),
+            ConfigSource::Git(url, path) => write!(f, "{} ({})", url, path.display()),
+            ConfigSource::Import(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Problem found while parsing a single line of the config
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    line: usize,
+    message: String,
+}
+
+impl Diagnostic {
+    fn new(line: usize, message: String) -> Self {
+        Diagnostic { line, message }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ConfigMetadata {
     entries: Vec<ConfigEntry>,
+    /// Id of the next added entry, ids aren't reused after entries are dropped
+    next_id: usize,
+    diagnostics: Vec<Diagnostic>,
+    source: ConfigSource,
+    source_path: Option<PathBuf>,
+    text: String,
+    strict_modifiers: bool,
+    order: EntryOrder,
+    min_score: Option<MinScore>,
+    /// Matches dropped by `min_score` in the last filter
+    weak_matches: usize,
+    matcher: MatcherKind,
+    search_text: SearchText,
+}
+
+/// Field of an entry that query words are matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SearchField {
+    Group,
+    Description,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeightedField {
+    pub field: SearchField,
+    /// Scores of matches in the field are multiplied by it
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// Fields every query word is matched against, built like
+/// `SearchText::new().field(SearchField::Description, 2.0).field(SearchField::Group, 1.0)`.
+/// A word counts for the field it scores best in after weighting, earlier fields win ties.
+/// Words matching none of them are looked up in the keys
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct SearchText {
+    fields: Vec<WeightedField>,
+}
+
+impl SearchText {
+    /// No fields, only the keys are searched
+    pub fn new() -> Self {
+        SearchText { fields: vec![] }
+    }
+
+    pub fn field(mut self, field: SearchField, weight: f64) -> Self {
+        self.fields.push(WeightedField { field, weight });
+        self
+    }
+
+    pub fn fields(&self) -> &[WeightedField] {
+        &self.fields
+    }
+}
+
+/// Group, then description, both weighted equally
+impl Default for SearchText {
+    fn default() -> Self {
+        SearchText::new()
+            .field(SearchField::Group, 1.0)
+            .field(SearchField::Description, 1.0)
+    }
+}
+
+/// Fuzzy matches scoring lower are dropped, only applies to non-empty queries
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MinScore {
+    /// Score of the matcher, a single well matching character scores about 20
+    Absolute(i64),
+    /// Fraction of the best score of the query
+    Relative(f64),
+}
+
+/// Order of entries that match equally well, like all of them for an empty query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EntryOrder {
+    /// Groups alphabetically, then descriptions
+    #[default]
+    Group,
+    /// As they appear in the config
+    File,
 }
 
 impl ConfigMetadata {
-    fn parse(text: &str) -> Result<ConfigMetadata> {
-        let re = Regex::new(r"(?m)^\s*##(?P<group>.*)//(?P<description>.*)//(?P<keys>.*)##")
+    /// Reads the annotations of a config, lines that look like broken annotations become diagnostics
+    pub fn parse(text: &str) -> Result<ConfigMetadata> {
+        let re = Regex::new(r"^\s*##(?P<group>.*)//(?P<description>.*)//(?P<keys>.*)##")
+            .map_err(|_| I3ConfigError::ConfigParsingError)?;
+        let mode_re = Regex::new(r#"^mode\s+(?:--pango_markup\s+)?"?(?P<mode>[^"]+?)"?\s*\{$"#)
             .map_err(|_| I3ConfigError::ConfigParsingError)?;
+        let lines: Vec<&str> = text.lines().collect();
+        let variables = parse_variables(&lines);
         let mut entries = vec![];
-        for cap in re.captures_iter(text) {
-            let entry = ConfigEntry::new(
+        let mut diagnostics = vec![];
+        // Open blocks with the name of the mode if the block is a mode
+        let mut blocks: Vec<Option<String>> = vec![];
+        for (index, line) in lines.iter().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.ends_with('{') && !trimmed.starts_with('#') {
+                blocks.push(
+                    mode_re
+                        .captures(trimmed)
+                        .map(|cap| resolve_variable(&cap["mode"], &variables).to_owned()),
+                );
+            } else if trimmed == "}" {
+                blocks.pop();
+            }
+            let cap = match re.captures(line) {
+                Some(cap) => cap,
+                None => {
+                    if let Some(problem) = annotation_problem(line) {
+                        diagnostics.push(Diagnostic::new(index + 1, problem));
+                    }
+                    continue;
+                }
+            };
+            let mut entry = ConfigEntry::new(
                 cap.name("group")
                     .ok_or(I3ConfigError::ConfigParsingError)?
                     .as_str()
@@ -248,52 +1008,626 @@ impl ConfigMetadata {
                     .as_str()
                     .trim()
                     .to_owned(),
-            );
-            entries.push(entry);
+            )
+            .with_line(index + 1);
+            if entry.group().is_empty() || entry.description().is_empty() {
+                diagnostics.push(Diagnostic::new(
+                    index + 1,
+                    String::from("annotation has an empty group or description"),
+                ));
+                continue;
+            }
+            if let Some((offset, binding)) = find_binding(&lines[index + 1..]) {
+                entry = entry
+                    .with_binding(binding.keys, binding.command)
+                    .with_flags(binding.flags)
+                    .with_binding_line(index + offset + 2);
+            }
+            if let Some(mode) = blocks.iter().rev().flatten().next() {
+                entry = entry.with_mode(mode.clone());
+            }
+            entry.resolve_variables(&variables);
+            entries.push(entry.with_id(EntryId(entries.len())));
+        }
+        Ok(ConfigMetadata {
+            next_id: entries.len(),
+            entries,
+            diagnostics,
+            source: ConfigSource::Ipc,
+            source_path: None,
+            text: text.to_owned(),
+            strict_modifiers: false,
+            order: EntryOrder::default(),
+            min_score: None,
+            weak_matches: 0,
+            matcher: MatcherKind::default(),
+            search_text: SearchText::default(),
+        })
+    }
+
+    /// Sets where the config came from. Configs from i3 are edited in the file i3 reads
+    pub(crate) fn with_source(mut self, source: ConfigSource) -> Self {
+        self.source_path = match &source {
+            ConfigSource::Ipc => default_config_path(),
+            ConfigSource::File(path) => Some(path.clone()),
+            ConfigSource::Url(_) | ConfigSource::Git(..) | ConfigSource::Import(_) => None,
+        };
+        for entry in &mut self.entries {
+            entry.source = Some(source.clone());
+        }
+        self.source = source;
+        self
+    }
+
+    /// Config of entries that weren't parsed from an i3 config
+    pub(crate) fn from_entries(entries: Vec<ConfigEntry>) -> Self {
+        let entries: Vec<ConfigEntry> = entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| entry.with_id(EntryId(index)))
+            .collect();
+        ConfigMetadata {
+            next_id: entries.len(),
+            entries,
+            diagnostics: vec![],
+            source: ConfigSource::Ipc,
+            source_path: None,
+            text: String::new(),
+            strict_modifiers: false,
+            order: EntryOrder::default(),
+            min_score: None,
+            weak_matches: 0,
+            matcher: MatcherKind::default(),
+            search_text: SearchText::default(),
+        }
+    }
+
+    /// Adds the entries of `other` skipping ones with the same group, description and keys.
+    /// Fields are compared ignoring case and surrounding whitespace
+    pub fn merge(&mut self, other: ConfigMetadata) {
+        let same = |a: &str, b: &str| a.trim().to_lowercase() == b.trim().to_lowercase();
+        for entry in other.entries {
+            let duplicate = self.entries.iter().any(|existing| {
+                same(&existing.group, &entry.group)
+                    && same(&existing.description, &entry.description)
+                    && same(&existing.keys, &entry.keys)
+            });
+            if !duplicate {
+                self.push_entry(entry);
+            }
+        }
+        self.diagnostics.extend(other.diagnostics);
+    }
+
+    /// Whether the entry can be edited in the file this config was read from,
+    /// entries of providers have no annotation to edit
+    pub fn is_editable(&self, entry: &ConfigEntry) -> bool {
+        self.source_path.is_some()
+            && entry.source() == Some(&self.source)
+            && entry.provider().is_none()
+    }
+
+    pub fn source(&self) -> &ConfigSource {
+        &self.source
+    }
+
+    /// Drops the entry from the loaded config, the file itself is left untouched
+    pub fn hide_entry(&mut self, id: EntryId) {
+        self.entries.retain(|entry| entry.id != id);
+    }
+
+    /// Drops every entry `hidden` returns true for, see [`ConfigMetadata::hide_entry`]
+    pub fn hide_entries(&mut self, hidden: impl Fn(&ConfigEntry) -> bool) {
+        self.entries.retain(|entry| !hidden(entry));
+    }
+
+    pub fn entry(&self, id: EntryId) -> Option<&ConfigEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    fn push_entry(&mut self, entry: ConfigEntry) {
+        self.entries.push(entry.with_id(EntryId(self.next_id)));
+        self.next_id += 1;
+    }
+
+    pub fn entries(&self) -> &[ConfigEntry] {
+        &self.entries
+    }
+
+    /// Local file the config was read from if known
+    pub fn source_path(&self) -> Option<&Path> {
+        self.source_path.as_deref()
+    }
+
+    /// Hides entries needing modifiers that aren't held when filtering
+    pub fn set_strict_modifiers(&mut self, strict: bool) {
+        self.strict_modifiers = strict;
+    }
+
+    pub fn set_order(&mut self, order: EntryOrder) {
+        self.order = order;
+    }
+
+    pub fn set_matcher(&mut self, matcher: MatcherKind) {
+        self.matcher = matcher;
+    }
+
+    pub fn set_min_score(&mut self, min_score: Option<MinScore>) {
+        self.min_score = min_score;
+    }
+
+    pub fn set_search_text(&mut self, search_text: SearchText) {
+        self.search_text = search_text;
+    }
+
+    /// Number of matches the minimum score dropped in the last filter
+    pub fn weak_matches(&self) -> usize {
+        self.weak_matches
+    }
+
+    /// All entries in the configured order
+    pub fn ordered_entries(&self) -> Vec<&ConfigEntry> {
+        let mut entries: Vec<&ConfigEntry> = self.entries.iter().collect();
+        sort_by_order(self.order, &mut entries, |entry| *entry);
+        entries
+    }
+
+    /// Hash of the text the entries were parsed from
+    pub fn content_hash(&self) -> u64 {
+        content_hash(&self.text)
+    }
+
+    /// Records the text of the config after it was written by the searcher
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+    }
+
+    /// Raw config lines from the annotation of an entry up to its binding
+    pub fn excerpt(&self, entry: &ConfigEntry) -> Vec<&str> {
+        let first = entry.line().saturating_sub(1);
+        let last = entry.binding_line().unwrap_or_else(|| entry.line());
+        self.text
+            .lines()
+            .skip(first)
+            .take(last.saturating_sub(first))
+            .collect()
+    }
+
+    /// Problems with annotations that were skipped while parsing
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Updates the entry annotated on `line` after it was changed in the config file
+    pub fn update_annotation(&mut self, line: usize, group: &str, description: &str) {
+        let source = &self.source;
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.line == line && entry.source.as_ref() == Some(source))
+        {
+            entry.set_annotation(group.trim().to_owned(), description.trim().to_owned());
+        }
+    }
+
+    /// Adds the entries the providers find in the text the config was parsed from
+    pub fn add_provider_entries(&mut self, providers: &[Provider]) {
+        for provider in providers {
+            for entry in provider.entries(&self.text) {
+                self.add_entry(entry);
+            }
+        }
+    }
+
+    /// Adds an entry that was appended to the config file
+    pub fn add_entry(&mut self, mut entry: ConfigEntry) {
+        entry.source = Some(self.source.clone());
+        self.push_entry(entry);
+    }
+
+    /// Entries already bound to the same key combination, used to detect duplicate bindings
+    pub fn entries_bound_to(&self, keys: &str) -> Vec<&ConfigEntry> {
+        let keys = normalize_key_combo(keys);
+        self.entries
+            .iter()
+            .filter(|entry| entry.bound_keys().map(normalize_key_combo) == Some(keys.clone()))
+            .collect()
+    }
+
+    /// Unique groups with number of entries in each, in the [`EntryOrder`] of the entries,
+    /// so alphabetical by default and in order of first appearance for `File`
+    pub fn groups(&self) -> Vec<(&str, usize)> {
+        let mut groups: Vec<(&str, usize)> = vec![];
+        for entry in self.ordered_entries() {
+            match groups.iter_mut().find(|(group, _)| *group == entry.group()) {
+                Some((_, count)) => *count += 1,
+                None => groups.push((entry.group(), 1)),
+            }
+        }
+        groups
+    }
+
+    /// Drops all entries that don't belong to the i3 mode
+    pub fn retain_mode(&mut self, mode: &str) {
+        self.entries.retain(|entry| entry.mode() == Some(mode));
+    }
+
+    /// Folds entries with the same group, description, mode and command into the first of them,
+    /// keeping the keys of the others as alternatives. Running any of them does the same
+    pub fn collapse_duplicates(&mut self) {
+        let mut collapsed: Vec<ConfigEntry> = vec![];
+        for entry in self.entries.drain(..) {
+            let original = collapsed.iter_mut().find(|other| {
+                entry.command.is_some()
+                    && other.command == entry.command
+                    && other.group == entry.group
+                    && other.description == entry.description
+                    && other.mode == entry.mode
+            });
+            match original {
+                Some(original) => original.alternative_keys.push(entry.keys),
+                None => collapsed.push(entry),
+            }
+        }
+        self.entries = collapsed;
+    }
+
+    /// Entries belonging to a group or one of its subgroups, see [`ConfigEntry::is_in_group`]
+    pub fn entries_in_group(&self, group: &str) -> Vec<&ConfigEntry> {
+        self.ordered_entries()
+            .into_iter()
+            .filter(|entry| entry.is_in_group(group))
+            .collect()
+    }
+
+    pub fn filter(&mut self, filter: &str, modifiers: &Modifiers) -> Vec<&ConfigEntry> {
+        self.filter_scored(filter, modifiers)
+            .into_iter()
+            .map(|(entry, _)| entry)
+            .collect()
+    }
+
+    pub fn filter_scored(
+        &mut self,
+        filter: &str,
+        modifiers: &Modifiers,
+    ) -> Vec<(&ConfigEntry, i64)> {
+        let matches = self.scored_indices(filter, modifiers);
+        let entries = &self.entries;
+        matches
+            .into_iter()
+            .map(|(index, score)| (&entries[index], score))
+            .collect()
+    }
+
+    /// Like [`ConfigMetadata::filter`] but returns positions in [`ConfigMetadata::entries`]
+    /// so the matches can be kept without borrowing the config
+    pub fn filter_indices(&mut self, filter: &str, modifiers: &Modifiers) -> Vec<usize> {
+        self.scored_indices(filter, modifiers)
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn scored_indices(&mut self, filter: &str, modifiers: &Modifiers) -> Vec<(usize, i64)> {
+        let tokens: Vec<&str> = filter.split_whitespace().collect();
+        let strict_modifiers = self.strict_modifiers;
+        let search_text = &self.search_text;
+        let score = |matcher: &mut Matcher, entry: &mut ConfigEntry| {
+            entry.clear_matches();
+            let modifiers_match = if strict_modifiers {
+                entry.matches_modifiers_strictly(modifiers)
+            } else {
+                entry.matches_modifiers(modifiers)
+            };
+            if !modifiers_match {
+                return None;
+            }
+            let token_match = match_tokens(matcher, entry, search_text, &tokens)?;
+            entry.set_group_indices(token_match.group_indices);
+            entry.set_description_indices(token_match.description_indices);
+            entry.set_keys_indices(token_match.keys_indices);
+            Some(token_match.score)
+        };
+        let kind = self.matcher;
+        // Every worker thread gets its own matcher, scores come back in entry order
+        #[cfg(feature = "parallel")]
+        let scores: Vec<Option<i64>> = self
+            .entries
+            .par_iter_mut()
+            .with_min_len(PARALLEL_CHUNK)
+            .map_init(|| Matcher::new(kind), score)
+            .collect();
+        #[cfg(not(feature = "parallel"))]
+        let scores: Vec<Option<i64>> = {
+            let mut matcher = Matcher::new(kind);
+            self.entries
+                .iter_mut()
+                .map(|entry| score(&mut matcher, entry))
+                .collect()
+        };
+        let mut matches: Vec<(usize, i64)> = scores
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, score)| Some((index, score?)))
+            .collect();
+        let entries = &self.entries;
+        sort_by_order(self.order, &mut matches, |(index, _)| &entries[*index]);
+        matches.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        let found = matches.len();
+        let min_score = match (self.min_score, matches.first()) {
+            (_, None) => None,
+            _ if tokens.is_empty() => None,
+            (Some(MinScore::Absolute(min)), _) => Some(min),
+            (Some(MinScore::Relative(fraction)), Some((_, top))) => {
+                Some((*top as f64 * fraction).ceil() as i64)
+            }
+            (None, _) => None,
+        };
+        if let Some(min_score) = min_score {
+            matches.retain(|(_, score)| *score >= min_score);
+        }
+        self.weak_matches = found - matches.len();
+        matches
+    }
+
+    /// Finds the best matching entry for a query.
+    /// Fails if the top score is shared by multiple entries unless `allow_ambiguous` is set
+    pub fn best_match(&mut self, query: &str, allow_ambiguous: bool) -> Result<&ConfigEntry> {
+        let matches = self.filter_scored(query, &Modifiers::default());
+        match matches.as_slice() {
+            [] => Err(I3ConfigError::NoMatchingEntry(query.to_owned())),
+            [(_, first_score), (_, second_score), ..]
+                if first_score == second_score && !allow_ambiguous =>
+            {
+                Err(I3ConfigError::AmbiguousMatch(query.to_owned()))
+            }
+            [(entry, _), ..] => Ok(entry),
+        }
+    }
+}
+
+/// Entries scored by one thread at a time with the parallel feature,
+/// small configs aren't worth the synchronization
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK: usize = 512;
+
+/// Stable so file order is kept for `EntryOrder::File` and entries that compare equal
+fn sort_by_order<'a, T>(order: EntryOrder, items: &mut [T], entry: impl Fn(&T) -> &'a ConfigEntry) {
+    if order == EntryOrder::Group {
+        items.sort_by_cached_key(|item| {
+            let entry = entry(item);
+            (
+                entry.group().to_lowercase(),
+                entry.description().to_lowercase(),
+            )
+        });
+    }
+}
+
+/// Splits nested groups like `Launch/Browsers` at the slashes
+pub fn group_path(group: &str) -> Vec<&str> {
+    group
+        .split('/')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Used to detect changes to the config made outside of the searcher
+pub fn content_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// First existing file from the locations i3 searches for its config
+pub fn default_config_path() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let candidates = [
+        home.map(|home| home.join(".i3/config")),
+        crate::paths::xdg_config_home().map(|config| config.join("i3/config")),
+        Some(PathBuf::from("/etc/i3/config")),
+    ];
+    candidates
+        .iter()
+        .flatten()
+        .find(|path| path.is_file())
+        .cloned()
+}
+
+/// Explains why a line that looks like an annotation couldn't be parsed.
+/// Plain `##` comments without any `//` separators are not considered annotations
+fn annotation_problem(line: &str) -> Option<String> {
+    let body = line.trim().strip_prefix("##")?;
+    if annotation_separators(body) == 0 {
+        return None;
+    }
+    let body = match body.find("##") {
+        Some(end) => &body[..end],
+        None => return Some(String::from("annotation is missing closing ##")),
+    };
+    Some(format!(
+        "expected 3 fields separated by //, found {}",
+        annotation_separators(body) + 1
+    ))
+}
+
+/// Number of `//` in `text`, leaving out the ones of URLs like `https://`
+fn annotation_separators(text: &str) -> usize {
+    text.match_indices("//")
+        .filter(|(index, _)| !text[..*index].ends_with(':'))
+        .count()
+}
+
+/// Variables defined with `set` or `set_from_resource` (using the fallback value)
+pub(crate) fn parse_variables(lines: &[&str]) -> HashMap<String, String> {
+    let mut variables = HashMap::new();
+    for line in lines {
+        let (keyword, rest) = split_word(line);
+        let (name, value) = split_word(rest);
+        let value = match keyword {
+            "set" => value,
+            "set_from_resource" => split_word(value).1,
+            _ => continue,
+        };
+        if name.starts_with('$') {
+            variables.insert(name.to_owned(), value.to_owned());
+        }
+    }
+    variables
+}
+
+/// Follows variables referring to other variables, giving up on cycles
+pub(crate) fn resolve_variable<'a>(
+    token: &'a str,
+    variables: &'a HashMap<String, String>,
+) -> &'a str {
+    let mut value = token;
+    for _ in 0..variables.len() {
+        match variables.get(value) {
+            Some(next) => value = next,
+            None => break,
         }
-        Ok(ConfigMetadata { entries })
     }
+    value
+}
+
+/// Looks for the binding directly following an annotation.
+/// Empty lines and plain comments are skipped, another annotation ends the search
+/// Returns the offset of the binding line together with the parsed binding
+fn find_binding(lines: &[&str]) -> Option<(usize, Binding)> {
+    let (offset, line) = lines
+        .iter()
+        .map(|line| line.trim())
+        .enumerate()
+        .find(|(_, line)| !line.is_empty() && (!line.starts_with('#') || line.starts_with("##")))?;
+    Some((offset, parse_binding(line)?))
+}
+
+/// Extracts flags, keys and command from a `bindsym`, `bindcode` or sway `bindswitch` line
+fn parse_binding(line: &str) -> Option<Binding> {
+    let (binding, mut rest) = split_word(line);
+    if !matches!(binding, "bindsym" | "bindcode" | "bindswitch") {
+        return None;
+    }
+    let mut flags = vec![];
+    while rest.starts_with("--") {
+        let (flag, remaining) = split_word(rest);
+        flags.push(BindingFlag::parse(flag));
+        rest = remaining;
+    }
+    let (keys, command) = split_word(rest);
+    if command.is_empty() {
+        None
+    } else {
+        Some(Binding {
+            keys: keys.to_owned(),
+            command: command.to_owned(),
+            flags,
+        })
+    }
+}
+
+/// Describes a `bindsym` key combination in the notation used by annotations,
+/// e.g. `$mod+Shift+q` becomes `<> <shift> q`
+pub fn annotation_keys(keys: &str) -> String {
+    keys.split('+')
+        .map(|part| match part.trim().to_lowercase().as_str() {
+            "$mod" | "mod4" | "super" => String::from(META_PATTERN),
+            "shift" => String::from(SHIFT_PATTERN),
+            "ctrl" | "control" => String::from(CONTROL_PATTERN),
+            "$alt" | "mod1" | "alt" => String::from(ALT_PATTERN),
+            _ => part.trim().to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-    pub async fn load_from_ipc() -> Result<ConfigMetadata> {
-        let config_text = get_i3_config_ipc().await?;
-        ConfigMetadata::parse(&config_text)
+/// Normalizes a key combination so that different spellings of the same binding compare equal.
+/// Modifiers are sorted and everything is lowercased, the final key stays last
+pub fn normalize_key_combo(keys: &str) -> String {
+    let mut parts: Vec<String> = keys
+        .split('+')
+        .map(|part| match part.trim().to_lowercase().as_str() {
+            "control" => String::from("ctrl"),
+            part => part.to_owned(),
+        })
+        .collect();
+    if let Some(key) = parts.pop() {
+        parts.sort();
+        parts.dedup();
+        parts.push(key);
     }
+    parts.join("+")
+}
 
-    pub async fn load_from_web(url: &str) -> Result<ConfigMetadata> {
-        let config_text = download_i3_config(url).await?;
-        ConfigMetadata::parse(&config_text)
+pub(crate) fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (text, ""),
     }
+}
 
-    pub fn filter(&mut self, filter: &str, modifiers: &Modifiers) -> Vec<&ConfigEntry> {
-        let matcher = SkimMatcherV2::default();
-        let mut matches = vec![];
-        for entry in &mut self.entries {
-            entry.clear_matches();
-            if let Some((score, indices)) = matcher.fuzzy_indices(&entry.full_text(), filter) {
-                if entry.matches_modifiers(&modifiers) {
-                    let group_len = entry.group().len();
-                    entry.set_group_indices(
-                        indices
-                            .iter()
-                            .cloned()
-                            .filter(|val| *val < group_len)
-                            .collect(),
-                    );
-                    entry.set_description_indices(
-                        indices
-                            .iter()
-                            .cloned()
-                            .filter(|val| *val > group_len)
-                            .map(|val| val - group_len - 1)
-                            .collect(),
-                    );
-                    matches.push((entry, score))
+#[derive(Debug, Default)]
+struct TokenMatch {
+    score: i64,
+    group_indices: Vec<usize>,
+    description_indices: Vec<usize>,
+    keys_indices: Vec<usize>,
+}
+
+/// Matches each whitespace separated token independently against the fields of `search_text`.
+/// Every token has to match one of the fields, order of tokens doesn't matter.
+/// Tokens matching none are looked up in the keys, e.g. `shift` or `enter`
+fn match_tokens(
+    matcher: &mut Matcher,
+    entry: &ConfigEntry,
+    search_text: &SearchText,
+    tokens: &[&str],
+) -> Option<TokenMatch> {
+    let mut token_match = TokenMatch::default();
+    for token in tokens {
+        let mut best: Option<(SearchField, i64, Vec<usize>)> = None;
+        for weighted in search_text.fields() {
+            let text = match weighted.field {
+                SearchField::Group => entry.group(),
+                SearchField::Description => entry.description(),
+            };
+            if let Some((score, indices)) = matcher.fuzzy_indices(text, token) {
+                let score = (score as f64 * weighted.weight).round() as i64;
+                if best
+                    .as_ref()
+                    .is_none_or(|(_, best_score, _)| score > *best_score)
+                {
+                    best = Some((weighted.field, score, indices));
                 }
             }
         }
-        matches.sort_by(|a, b| b.1.cmp(&a.1));
-        matches.into_iter().map(|(val, _)| &*val).collect()
+        match best {
+            Some((SearchField::Group, score, indices)) => {
+                token_match.score += score;
+                token_match.group_indices.extend(indices);
+            }
+            Some((SearchField::Description, score, indices)) => {
+                token_match.score += score;
+                token_match.description_indices.extend(indices);
+            }
+            None => {
+                let (score, indices) = matcher.fuzzy_indices(entry.keys(), token)?;
+                token_match.score += score;
+                token_match.keys_indices.extend(indices);
+            }
+        }
     }
+    token_match.group_indices.sort_unstable();
+    token_match.group_indices.dedup();
+    token_match.description_indices.sort_unstable();
+    token_match.description_indices.dedup();
+    token_match.keys_indices.sort_unstable();
+    token_match.keys_indices.dedup();
+    Some(token_match)
 }
 
 #[cfg(test)]
@@ -319,6 +1653,12 @@ mod tests {
                 String::from("description1"),
                 String::from("keys1"),
             )
+            .with_binding(
+                String::from("$mod+Ctrl+$alt+Left"),
+                String::from("move workspace to output left")
+            )
+            .with_binding_line(2)
+            .with_line(1)
         );
         assert_eq!(
             config.entries[1],
@@ -327,6 +1667,13 @@ mod tests {
                 String::from("description2"),
                 String::from("keys2"),
             )
+            .with_binding(
+                String::from("$mod+grave"),
+                String::from("exec /usr/bin/x-terminal-emulator")
+            )
+            .with_binding_line(4)
+            .with_line(3)
+            .with_id(EntryId(1))
         );
     }
 
@@ -357,6 +1704,7 @@ mod tests {
                 String::from("description1"),
                 String::from("keys1"),
             )
+            .with_line(1)
         );
     }
 
@@ -379,6 +1727,7 @@ mod tests {
                 String::from("this is description1"),
                 String::from("this is keys1"),
             )
+            .with_line(1)
         );
     }
 
@@ -395,6 +1744,7 @@ mod tests {
                 String::from("description1"),
                 String::from("keys1"),
             )
+            .with_line(2)
         );
     }
 
@@ -437,6 +1787,18 @@ mod tests {
         assert_eq!(filtered_entries[1].description(), String::from("abdc"));
     }
 
+    #[test]
+    fn filter_i3_entries_indices() {
+        let sample = "## group1 // abdc // keys1 ##
+        ## group2 // abc // keys2 ##";
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        assert_eq!(
+            config.filter_indices("abc", &Modifiers::default()),
+            vec![1, 0]
+        );
+        assert_eq!(config.filter_indices("abd", &Modifiers::default()), vec![0]);
+    }
+
     #[test]
     fn filter_i3_by_group() {
         let sample = "## group1 // abdc // keys1 ##
@@ -524,6 +1886,156 @@ mod tests {
         assert!(short_cut.matches_modifiers(&modifiers))
     }
 
+    #[test]
+    fn modifiers_synonyms_in_keys() {
+        let meta = Modifiers::new(false, false, false, true);
+        let annotated = format!("{} m", META_PATTERN);
+        for keys in &[annotated.as_str(), "Mod4 + m", "Super+m", "$mod m", "win m"] {
+            let entry = ConfigEntry::new(
+                String::from("group"),
+                String::from("description"),
+                keys.to_string(),
+            );
+            assert!(entry.matches_modifiers(&meta), "{}", keys);
+        }
+    }
+
+    #[test]
+    fn modifiers_resolved_with_variables() {
+        let sample = "set $mod Mod1
+        set $super Mod4
+        set $hyper $super
+        ## group1 // alt binding // $mod+a ##
+        ## group2 // super binding // m ##
+        bindsym $hyper+m kill";
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        let meta = config.filter("", &Modifiers::new(false, false, false, true));
+        assert_eq!(meta.len(), 1);
+        assert_eq!(meta[0].description(), "super binding");
+        let alt = config.filter("", &Modifiers::new(false, false, true, false));
+        assert_eq!(alt.len(), 1);
+        assert_eq!(alt[0].description(), "alt binding");
+    }
+
+    #[test]
+    fn empty_query_is_ordered_by_group_then_description() {
+        let mut config = ConfigMetadata::parse(
+            "## Media // Volume up // XF86AudioRaiseVolume ##
+            ## Launch // Terminal // <> enter ##
+            ## launch // Browser // <> b ##",
+        )
+        .unwrap();
+        let descriptions = |entries: Vec<&ConfigEntry>| -> Vec<String> {
+            entries
+                .iter()
+                .map(|entry| entry.description().to_owned())
+                .collect()
+        };
+        assert_eq!(
+            descriptions(config.filter("", &Modifiers::default())),
+            vec!["Browser", "Terminal", "Volume up"]
+        );
+        assert_eq!(config.groups()[0], ("launch", 1));
+
+        config.set_order(EntryOrder::File);
+        assert_eq!(
+            descriptions(config.ordered_entries()),
+            vec!["Volume up", "Terminal", "Browser"]
+        );
+    }
+
+    #[test]
+    fn strict_modifiers_hide_entries_with_more_modifiers() {
+        let sample = "## group1 // ctrl // <ctrl> a ##
+        ## group2 // ctrl shift // <ctrl><shift> a ##
+        ## group3 // none // a ##";
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        let control = Modifiers::new(false, true, false, false);
+        assert_eq!(config.filter("", &control).len(), 2);
+        config.set_strict_modifiers(true);
+        let entries = config.filter("", &control);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].description(), "ctrl");
+        assert_eq!(config.filter("", &Modifiers::default()).len(), 3);
+    }
+
+    #[test]
+    fn key_sequence_remaining_keys() {
+        let sample = "set $mod Mod4
+        ## group1 // description1 // keys1 ##
+        bindsym $mod+Shift+q kill";
+        let config = ConfigMetadata::parse(sample).unwrap();
+        let sequence = config.entries[0].sequence();
+        assert_eq!(sequence.combos()[0].to_string(), "Super+Shift+q");
+        let meta = Modifiers::new(false, false, false, true);
+        assert_eq!(sequence.remaining(&meta), Some(String::from("Shift+q")));
+        let meta_shift = Modifiers::new(true, false, false, true);
+        assert_eq!(sequence.remaining(&meta_shift), Some(String::from("q")));
+        let control = Modifiers::new(false, true, false, false);
+        assert_eq!(sequence.remaining(&control), None);
+    }
+
+    #[test]
+    fn entries_bound_to_pressed_chord() {
+        let sample = "set $mod Mod4
+        ## group1 // description1 // keys1 ##
+        bindsym $mod+Shift+q kill
+        ## group1 // description2 // keys2 ##
+        bindsym $mod+Return exec alacritty";
+        let config = ConfigMetadata::parse(sample).unwrap();
+        let meta_shift = Modifiers::new(true, false, false, true);
+        let chord = KeyCombo::new(meta_shift.clone(), String::from("Q"));
+        let bound: Vec<&str> = config
+            .entries()
+            .iter()
+            .filter(|entry| entry.is_bound_to(&chord))
+            .map(|entry| entry.description())
+            .collect();
+        assert_eq!(bound, vec!["description1"]);
+        let meta = Modifiers::new(false, false, false, true);
+        assert!(config.entries()[1].is_bound_to(&KeyCombo::new(meta, String::from("return"))));
+        assert!(
+            !config.entries()[1].is_bound_to(&KeyCombo::new(meta_shift, String::from("Return")))
+        );
+    }
+
+    #[test]
+    fn documented_key_sequences() {
+        let sample = format!(
+            "mode \"resize\" {{
+            ## resize // shrink // {} r then h ##
+            bindsym h resize shrink width 10 px
+        }}",
+            META_PATTERN
+        );
+        let config = ConfigMetadata::parse(&sample).unwrap();
+        let sequence = config.entries[0].sequence();
+        let combos: Vec<String> = sequence.combos().iter().map(ToString::to_string).collect();
+        assert_eq!(combos, vec!["Super+r", "h"]);
+        let meta = Modifiers::new(false, false, false, true);
+        assert_eq!(sequence.remaining(&meta), Some(String::from("r h")));
+    }
+
+    #[test]
+    fn mouse_button_bindings() {
+        let sample = "## mouse // kill // middle click ##
+        bindsym --whole-window $mod+button2 kill
+        ## keys // kill // keys ##
+        bindsym $mod+Shift+q kill
+        ## keys // back // keys ##
+        bindsym $mod+buttons workspace back_and_forth";
+        let config = ConfigMetadata::parse(sample).unwrap();
+        let kinds: Vec<BindingKind> = config.entries.iter().map(ConfigEntry::kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                BindingKind::Mouse,
+                BindingKind::Keyboard,
+                BindingKind::Keyboard
+            ]
+        );
+    }
+
     #[test]
     fn highlight_simple_group() {
         let sample = "## group1 // abdc // keys1 ##";
@@ -541,6 +2053,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn highlight_last_group_and_first_description_character() {
+        let sample = "## group1 // abdc // keys1 ##";
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        let filtered_entries = config.filter("1 a", &Modifiers::default());
+        assert_eq!(
+            filtered_entries[0].matched_group(),
+            vec![
+                MatchElement::Unmatched("group".to_owned()),
+                MatchElement::Matched("1".to_owned()),
+            ]
+        );
+        assert_eq!(
+            filtered_entries[0].matched_description(),
+            vec![
+                MatchElement::Matched("a".to_owned()),
+                MatchElement::Unmatched("bdc".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn highlight_multibyte_characters() {
+        let sample = "## Médias // Écouter // keys1 ##";
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        let filtered_entries = config.filter("édi cou", &Modifiers::default());
+        assert_eq!(
+            filtered_entries[0].matched_group(),
+            vec![
+                MatchElement::Unmatched("M".to_owned()),
+                MatchElement::Matched("édi".to_owned()),
+                MatchElement::Unmatched("as".to_owned()),
+            ]
+        );
+        assert_eq!(
+            filtered_entries[0].matched_description(),
+            vec![
+                MatchElement::Unmatched("É".to_owned()),
+                MatchElement::Matched("cou".to_owned()),
+                MatchElement::Unmatched("ter".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn search_text_fields_and_weights() {
+        let sample = "## media // mute audio // <> m ##";
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        let matched = |config: &mut ConfigMetadata, query: &str| {
+            config
+                .filter(query, &Modifiers::default())
+                .first()
+                .map(|entry| (entry.matched_group(), entry.matched_description()))
+        };
+        let (group, _) = matched(&mut config, "m").unwrap();
+        assert_eq!(group[0], MatchElement::Matched("m".to_owned()));
+
+        config.set_search_text(
+            SearchText::new()
+                .field(SearchField::Group, 1.0)
+                .field(SearchField::Description, 3.0),
+        );
+        let (group, description) = matched(&mut config, "m").unwrap();
+        assert_eq!(group, vec![MatchElement::Unmatched("media".to_owned())]);
+        assert_eq!(description[0], MatchElement::Matched("m".to_owned()));
+
+        config.set_search_text(SearchText::new().field(SearchField::Description, 1.0));
+        assert_eq!(matched(&mut config, "media"), None);
+    }
+
+    #[test]
+    fn highlight_keys() {
+        let sample = "## media // play // <> shift p ##";
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        let filtered_entries = config.filter("play shift", &Modifiers::default());
+        assert_eq!(
+            filtered_entries[0].matched_keys(),
+            vec![
+                MatchElement::Unmatched("<> ".to_owned()),
+                MatchElement::Matched("shift".to_owned()),
+                MatchElement::Unmatched(" p".to_owned()),
+            ]
+        );
+        assert!(config.filter("play ctrl", &Modifiers::default()).is_empty());
+    }
+
     #[test]
     fn highlight_simple_description() {
         let sample = "## group1 // abdc // keys1 ##";
@@ -571,4 +2169,498 @@ mod tests {
             expected_description
         );
     }
+
+    #[test]
+    fn filter_i3_tokens_any_order() {
+        let sample = simple_i3_config();
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        let filtered_entries = config.filter("dsc2 grp", &Modifiers::default());
+        assert_eq!(filtered_entries.len(), 1);
+        assert_eq!(filtered_entries[0].description(), "description2");
+    }
+
+    #[test]
+    fn filter_i3_all_tokens_must_match() {
+        let sample = simple_i3_config();
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        let filtered_entries = config.filter("group1 qw", &Modifiers::default());
+        assert!(filtered_entries.is_empty());
+    }
+
+    #[test]
+    fn highlight_tokens_in_reverse_order() {
+        let sample = "## group1 // abdc // keys1 ##";
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        let filtered_entries = config.filter("dc gro", &Modifiers::default());
+        let expected_group = vec![
+            MatchElement::Matched("gro".to_owned()),
+            MatchElement::Unmatched("up1".to_owned()),
+        ];
+        let expected_description = vec![
+            MatchElement::Unmatched("ab".to_owned()),
+            MatchElement::Matched("dc".to_owned()),
+        ];
+        assert_eq!(filtered_entries[0].matched_group(), expected_group);
+        assert_eq!(
+            filtered_entries[0].matched_description(),
+            expected_description
+        );
+    }
+
+    #[test]
+    fn parse_command_skips_flags_and_comments() {
+        let sample = "## group1 // description1 // keys1 ##
+        # plain comment
+
+        bindsym --release --border $mod+x exec --no-startup-id \"rofi  -show\"";
+        let config = ConfigMetadata::parse(sample).unwrap();
+        assert_eq!(
+            config.entries[0].command(),
+            Some("exec --no-startup-id \"rofi  -show\"")
+        );
+        assert_eq!(config.entries[0].bound_keys(), Some("$mod+x"));
+        assert_eq!(
+            config.entries[0].flags(),
+            &[BindingFlag::Release, BindingFlag::Border]
+        );
+    }
+
+    #[test]
+    fn parse_sway_binding_flags() {
+        let sample = "## media // mute // XF86AudioMute ##
+        bindsym --locked --to-code --input-device=1:1:AT_Translated_Set_2_keyboard --foo XF86AudioMute exec pactl set-sink-mute 0 toggle";
+        let config = ConfigMetadata::parse(sample).unwrap();
+        let entry = &config.entries[0];
+        assert_eq!(entry.bound_keys(), Some("XF86AudioMute"));
+        assert_eq!(entry.command(), Some("exec pactl set-sink-mute 0 toggle"));
+        assert_eq!(
+            entry.flags(),
+            &[
+                BindingFlag::Locked,
+                BindingFlag::ToCode,
+                BindingFlag::InputDevice(String::from("1:1:AT_Translated_Set_2_keyboard")),
+                BindingFlag::Other(String::from("--foo")),
+            ]
+        );
+        assert_eq!(
+            entry.flags()[2].to_string(),
+            "--input-device=1:1:AT_Translated_Set_2_keyboard"
+        );
+    }
+
+    #[test]
+    fn parse_command_stops_at_next_annotation() {
+        let sample = "## group1 // description1 // keys1 ##
+        ## group2 // description2 // keys2 ##
+        bindcode 38 kill";
+        let config = ConfigMetadata::parse(sample).unwrap();
+        assert_eq!(config.entries[0].command(), None);
+        assert_eq!(config.entries[1].command(), Some("kill"));
+    }
+
+    #[test]
+    fn excerpt_spans_annotation_to_binding() {
+        let sample = "## group1 // description1 // keys1 ##
+# plain comment
+bindsym $mod+x kill
+bindsym $mod+y exec true";
+        let config = ConfigMetadata::parse(sample).unwrap();
+        assert_eq!(config.entries[0].binding_line(), Some(3));
+        assert_eq!(
+            config.excerpt(&config.entries[0]),
+            vec![
+                "## group1 // description1 // keys1 ##",
+                "# plain comment",
+                "bindsym $mod+x kill"
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_mode_of_entries() {
+        let sample = "set $resize resize
+        ## group1 // description1 // keys1 ##
+        bindsym $mod+r mode \"resize\"
+        bar {
+            colors {
+            }
+        }
+        mode \"$resize\" {
+            ## resize // shrink // h ##
+            bindsym h resize shrink width 10 px
+        }
+        ## group2 // description2 // keys2 ##";
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        let modes: Vec<Option<&str>> = config.entries.iter().map(|entry| entry.mode()).collect();
+        assert_eq!(modes, vec![None, Some("resize"), None]);
+        config.retain_mode("resize");
+        assert_eq!(config.entries.len(), 1);
+    }
+
+    #[test]
+    fn best_match_picks_top_score() {
+        let sample = simple_i3_config();
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        let entry = config.best_match("description2", false).unwrap();
+        assert_eq!(entry.command(), Some("exec /usr/bin/x-terminal-emulator"));
+    }
+
+    #[test]
+    fn best_match_ambiguous() {
+        let sample = simple_i3_config();
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        assert!(matches!(
+            config.best_match("description", false),
+            Err(I3ConfigError::AmbiguousMatch(_))
+        ));
+        let entry = config.best_match("description", true).unwrap();
+        assert_eq!(entry.description(), "description1");
+    }
+
+    #[test]
+    fn best_match_no_match() {
+        let sample = simple_i3_config();
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        assert!(matches!(
+            config.best_match("qw", false),
+            Err(I3ConfigError::NoMatchingEntry(_))
+        ));
+    }
+
+    #[test]
+    fn groups_with_counts() {
+        let sample = "## media // play // keys1 ##
+        ## launch // terminal // keys2 ##
+        ## media // pause // keys3 ##";
+        let mut config = ConfigMetadata::parse(sample).unwrap();
+        assert_eq!(config.groups(), vec![("launch", 1), ("media", 2)]);
+        config.set_order(EntryOrder::File);
+        assert_eq!(config.groups(), vec![("media", 2), ("launch", 1)]);
+    }
+
+    #[test]
+    fn long_descriptions_are_truncated() {
+        let elements = vec![
+            MatchElement::Unmatched(String::from("Open ")),
+            MatchElement::Matched(String::from("fire")),
+            MatchElement::Unmatched(String::from("fox in a new window")),
+        ];
+        assert_eq!(
+            truncate_elements(elements, 8),
+            vec![
+                MatchElement::Unmatched(String::from("Open ")),
+                MatchElement::Matched(String::from("fi")),
+                MatchElement::Unmatched(String::from("…")),
+            ]
+        );
+        let short = vec![MatchElement::Unmatched(String::from("Terminal"))];
+        assert_eq!(
+            truncate_elements(short, 8),
+            vec![MatchElement::Unmatched(String::from("Terminal"))]
+        );
+    }
+
+    #[test]
+    fn entries_in_group_ignores_case() {
+        let sample = "## Media // play // keys1 ##
+        ## launch // terminal // keys2 ##";
+        let config = ConfigMetadata::parse(sample).unwrap();
+        let entries = config.entries_in_group("media");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].description(), "play");
+    }
+
+    #[test]
+    fn entries_in_nested_groups() {
+        let sample = "## Launch / Browsers // firefox // keys1 ##
+        ## Launch/Terminals // alacritty // keys2 ##
+        ## Apps/launch // rofi // keys3 ##
+        ## Launcher // dmenu // keys4 ##";
+        let config = ConfigMetadata::parse(sample).unwrap();
+        assert_eq!(config.entries()[0].group_path(), vec!["Launch", "Browsers"]);
+        let descriptions = |group: &str| -> Vec<&str> {
+            config
+                .entries_in_group(group)
+                .into_iter()
+                .map(ConfigEntry::description)
+                .collect()
+        };
+        assert_eq!(descriptions("launch"), vec!["rofi", "firefox", "alacritty"]);
+        assert_eq!(descriptions("browsers"), vec!["firefox"]);
+        assert_eq!(descriptions("Launch/Terminals"), vec!["alacritty"]);
+        assert!(descriptions("Terminals/Launch").is_empty());
+    }
+
+    #[test]
+    fn normalize_key_combo_sorts_modifiers() {
+        assert_eq!(
+            normalize_key_combo("Shift+$mod+Control+Q"),
+            normalize_key_combo("$mod+ctrl+shift+q")
+        );
+        assert_ne!(
+            normalize_key_combo("$mod+Shift+q"),
+            normalize_key_combo("$mod+q")
+        );
+    }
+
+    #[test]
+    fn annotation_keys_from_binding() {
+        assert_eq!(
+            annotation_keys("$mod+Shift+q"),
+            format!("{} <shift> q", META_PATTERN)
+        );
+        assert_eq!(
+            annotation_keys("Mod1+Control+Return"),
+            "<alt> <ctrl> Return"
+        );
+    }
+
+    #[test]
+    fn entries_bound_to_finds_duplicates() {
+        let sample = simple_i3_config();
+        let config = ConfigMetadata::parse(sample).unwrap();
+        let duplicates = config.entries_bound_to("Ctrl+$alt+$mod+left");
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].description(), "description1");
+        assert!(config.entries_bound_to("$mod+Left").is_empty());
+    }
+
+    #[test]
+    fn error_exit_codes_are_unique() {
+        #[allow(unused_mut)]
+        let mut errors = vec![
+            I3ConfigError::ConfigParsingError,
+            I3ConfigError::FailedI3Query(Arc::new(std::io::Error::from(
+                std::io::ErrorKind::NotFound,
+            ))),
+            I3ConfigError::UnsupportedPlatform,
+            I3ConfigError::NoMatchingEntry(String::new()),
+            I3ConfigError::AmbiguousMatch(String::new()),
+            I3ConfigError::MissingCommand,
+            I3ConfigError::InvalidAnnotations(1),
+            I3ConfigError::FailedConfigWrite(Arc::new(std::io::Error::from(
+                std::io::ErrorKind::PermissionDenied,
+            ))),
+            I3ConfigError::InvalidAnnotationField(String::new()),
+            I3ConfigError::ConfigChangedOnDisk,
+            I3ConfigError::NoBackup,
+            I3ConfigError::FailedPreferencesRead(Arc::new(std::io::Error::from(
+                std::io::ErrorKind::PermissionDenied,
+            ))),
+            I3ConfigError::InvalidPreferences(Arc::new(
+                toml::from_str::<toml::Value>("=").unwrap_err(),
+            )),
+            I3ConfigError::FailedSpawn(Arc::new(std::io::Error::from(
+                std::io::ErrorKind::NotFound,
+            ))),
+            I3ConfigError::FailedConfigRead(Arc::new(std::io::Error::from(
+                std::io::ErrorKind::NotFound,
+            ))),
+            I3ConfigError::ChecksumMismatch {
+                expected: String::new(),
+                actual: String::new(),
+            },
+            I3ConfigError::FailedGitSync(String::new()),
+            I3ConfigError::InvalidImport(Arc::new(serde_json::from_str::<()>("").unwrap_err())),
+            I3ConfigError::FailedChecks(0),
+            I3ConfigError::MissingFeature("ipc"),
+            I3ConfigError::FailedKeyboardGrab(String::new()),
+            I3ConfigError::FailedInstanceLock(Arc::new(std::io::Error::from(
+                std::io::ErrorKind::PermissionDenied,
+            ))),
+            I3ConfigError::InvalidTranslation(String::new()),
+            I3ConfigError::FailedOverrideRedirect(String::new()),
+            I3ConfigError::FailedUsageStats(Arc::new(std::io::Error::from(
+                std::io::ErrorKind::PermissionDenied,
+            ))),
+            I3ConfigError::InvalidState(String::new()),
+            I3ConfigError::UnsetVariable(String::new()),
+            I3ConfigError::InvalidI3Config(String::new()),
+            I3ConfigError::FailedTray(String::new()),
+            I3ConfigError::FailedControl(String::new()),
+            I3ConfigError::FailedGeometry(Arc::new(std::io::Error::from(
+                std::io::ErrorKind::NotFound,
+            ))),
+            I3ConfigError::FailedHiddenEntries(Arc::new(std::io::Error::from(
+                std::io::ErrorKind::NotFound,
+            ))),
+        ];
+        #[cfg(all(target_family = "unix", feature = "ipc"))]
+        errors.push(I3ConfigError::CommandFailed(String::new()));
+        #[cfg(feature = "remote")]
+        errors.push(I3ConfigError::FailedGetRequest(Arc::new(
+            reqwest::Client::new().get("not a url").build().unwrap_err(),
+        )));
+        let mut codes: Vec<i32> = errors.iter().map(|error| error.exit_code()).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0) && !codes.contains(&1));
+    }
+
+    #[test]
+    fn parse_keeps_going_after_malformed_annotation() {
+        let sample = "## group1 // description1 ##
+        ## group2 // description2 // keys2 ##
+        ## group3 // description3 // keys3
+        ##  // description4 // keys4 ##
+        ## plain comment
+        ## group5 // description5 // keys5 ##";
+        let config = ConfigMetadata::parse(sample).unwrap();
+        assert_eq!(config.entries.len(), 2);
+        assert_eq!(config.entries[0].group(), "group2");
+        assert_eq!(config.entries[1].group(), "group5");
+        let diagnostics = config.diagnostics();
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "line 1: expected 3 fields separated by //, found 2"
+        );
+        assert_eq!(diagnostics[1].line, 3);
+        assert_eq!(diagnostics[1].message, "annotation is missing closing ##");
+        assert_eq!(diagnostics[2].line, 4);
+    }
+
+    #[test]
+    fn comments_with_urls_are_not_annotations() {
+        let sample = "## docs: https://i3wm.org/docs/userguide.html ##
+        ## see http://example.com
+        ## group1 // see https://example.com";
+        let config = ConfigMetadata::parse(sample).unwrap();
+        let diagnostics = config.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 3);
+        assert_eq!(diagnostics[0].message, "annotation is missing closing ##");
+    }
+
+    #[test]
+    fn merge_keeps_provenance_and_skips_duplicates() {
+        let mut config = ConfigMetadata::parse("## Launch // Terminal // <> enter ##")
+            .unwrap()
+            .with_source(ConfigSource::File(PathBuf::from("/tmp/i3/config")));
+        let imported = ConfigMetadata::from_entries(vec![
+            ConfigEntry::new(
+                "launch".to_owned(),
+                "Terminal".to_owned(),
+                "<> enter".to_owned(),
+            ),
+            ConfigEntry::new("tmux".to_owned(), "Split".to_owned(), "Ctrl+b %".to_owned()),
+        ])
+        .with_source(ConfigSource::Import(PathBuf::from("/tmp/tmux.json")));
+        config.merge(imported);
+
+        assert_eq!(config.entries().len(), 2);
+        assert_eq!(config.entries()[1].group(), "tmux");
+        assert_eq!(config.entries()[1].source().unwrap().badge(), "tmux");
+        assert!(config.is_editable(&config.entries()[0]));
+        assert!(!config.is_editable(&config.entries()[1]));
+        assert_ne!(config.entries()[0].id(), config.entries()[1].id());
+    }
+
+    #[test]
+    fn merge_ignores_case_of_every_field() {
+        let mut config = ConfigMetadata::parse("## Launch // Terminal // <> Enter ##").unwrap();
+        config.merge(ConfigMetadata::from_entries(vec![
+            ConfigEntry::new(
+                " launch ".to_owned(),
+                "terminal".to_owned(),
+                "<> enter".to_owned(),
+            ),
+            ConfigEntry::new(
+                "Launch".to_owned(),
+                "Terminal".to_owned(),
+                "<> t".to_owned(),
+            ),
+        ]));
+        let keys: Vec<&str> = config.entries().iter().map(ConfigEntry::keys).collect();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[1], "<> t");
+    }
+
+    #[test]
+    fn large_configs_keep_their_order() {
+        let text: String = (0..3000)
+            .map(|index| format!("## Launch // App {:04} // <> {} ##\n", index, index))
+            .collect();
+        let mut config = ConfigMetadata::parse(&text).unwrap();
+        config.set_order(EntryOrder::File);
+        let lines: Vec<usize> = config
+            .filter("app", &Modifiers::default())
+            .iter()
+            .map(|entry| entry.line())
+            .collect();
+        assert_eq!(lines, (1..=3000).collect::<Vec<_>>());
+        let found = config.filter("app 2999", &Modifiers::default());
+        assert_eq!(found[0].description(), "App 2999");
+        assert!(!found[0].matched_description().is_empty());
+    }
+
+    #[test]
+    fn weak_matches_are_dropped() {
+        let mut config = ConfigMetadata::parse(
+            "## Launch // Terminal // <> enter ##
+## Layout // Toggle tabbed // <> w ##
+## Media // Mute // XF86AudioMute ##",
+        )
+        .unwrap();
+        let query_results = |config: &mut ConfigMetadata, query: &str| {
+            let count = config.filter(query, &Modifiers::default()).len();
+            (count, config.weak_matches())
+        };
+        let (all, _) = query_results(&mut config, "t");
+        assert!(all > 1);
+        config.set_min_score(Some(MinScore::Relative(1.0)));
+        let (count, weak) = query_results(&mut config, "terminal");
+        assert_eq!((count, weak), (1, 0));
+        let (count, weak) = query_results(&mut config, "t");
+        assert!(count >= 1);
+        assert_eq!(count + weak, all);
+        config.set_min_score(Some(MinScore::Absolute(i64::MAX)));
+        assert_eq!(query_results(&mut config, "t"), (0, all));
+        assert_eq!(query_results(&mut config, ""), (3, 0));
+    }
+
+    #[test]
+    fn duplicate_commands_are_collapsed() {
+        let mut config = ConfigMetadata::parse(
+            "## Launch // Terminal // <> enter ##
+bindsym $mod+Return exec alacritty
+## Launch // Terminal // <> t ##
+bindsym $mod+t exec alacritty
+## Launch // Terminal // <> <shift> t ##
+bindsym $mod+Shift+t exec xterm
+## Launch // Terminal // <> x ##
+## Launch // Terminal // <> y ##",
+        )
+        .unwrap();
+        config.collapse_duplicates();
+        let keys: Vec<(&str, &[String])> = config
+            .entries()
+            .iter()
+            .map(|entry| (entry.keys(), entry.alternative_keys()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("<> enter", &[String::from("<> t")][..]),
+                ("<> <shift> t", &[][..]),
+                ("<> x", &[][..]),
+                ("<> y", &[][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn hidden_entries_are_not_found() {
+        let mut config = ConfigMetadata::parse(
+            "## Launch // Terminal // <> enter ##\n## Launch // Browser // <> b ##",
+        )
+        .unwrap();
+        let terminal = config.entries()[0].id();
+        config.hide_entry(terminal);
+        assert_eq!(config.entries().len(), 1);
+        assert_eq!(config.entry(terminal), None);
+        assert!(config.filter("terminal", &Modifiers::default()).is_empty());
+        assert_eq!(config.filter("browser", &Modifiers::default()).len(), 1);
+    }
 }
@@ -0,0 +1,142 @@
+//! Lock file making sure only one searcher window is open at a time
+
+use crate::i3_config::I3ConfigError;
+use crate::paths;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+/// Linux truncates process names to 15 characters
+const PROCESS_NAME: &str = "i3-conf-searche";
+const STOP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// `$XDG_RUNTIME_DIR/i3-conf-searcher.lock`, falling back to the temp directory
+pub fn lock_path() -> PathBuf {
+    paths::runtime_dir().join("i3-conf-searcher.lock")
+}
+
+#[derive(Debug)]
+pub enum Instance {
+    /// No other searcher is open, the lock is held until dropped
+    Locked(InstanceLock),
+    /// Process id of the searcher that's already open, `None` if its lock can't be read
+    Running(Option<u32>),
+}
+
+/// Removes the lock file when dropped
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Takes the lock unless a live searcher holds it.
+/// Locks left behind by searchers that were killed are taken over
+pub fn acquire(path: &Path) -> Result<Instance> {
+    // The lock is linked into place with the pid already written,
+    // so a searcher started at the same moment never reads an empty lock
+    let staged = path.with_extension(format!("lock.{}", std::process::id()));
+    std::fs::write(&staged, std::process::id().to_string()).map_err(lock_error)?;
+    let instance = link_lock(path, &staged);
+    let _ = std::fs::remove_file(&staged);
+    instance
+}
+
+fn link_lock(path: &Path, staged: &Path) -> Result<Instance> {
+    for _ in 0..2 {
+        match std::fs::hard_link(staged, path) {
+            Ok(()) => {
+                return Ok(Instance::Locked(InstanceLock {
+                    path: path.to_owned(),
+                }))
+            }
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                let holder = match std::fs::read_to_string(path) {
+                    Ok(pid) => pid.trim().parse().ok(),
+                    // Released in the meantime
+                    Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+                    Err(_) => None,
+                };
+                match holder {
+                    Some(pid) if !is_searcher(pid) => {
+                        std::fs::remove_file(path).map_err(lock_error)?
+                    }
+                    // A lock without a readable pid is never taken over
+                    holder => return Ok(Instance::Running(holder)),
+                }
+            }
+            Err(error) => return Err(lock_error(error)),
+        }
+    }
+    Err(lock_error(io::Error::from(io::ErrorKind::AlreadyExists)))
+}
+
+fn lock_error(error: io::Error) -> I3ConfigError {
+    I3ConfigError::FailedInstanceLock(Arc::new(error))
+}
+
+/// Asks the searcher to terminate and waits for it to be gone
+pub fn stop(pid: u32) -> Result<()> {
+    std::process::Command::new("kill")
+        .arg(pid.to_string())
+        .status()
+        .map_err(|error| I3ConfigError::FailedSpawn(Arc::new(error)))?;
+    let started = std::time::Instant::now();
+    while is_searcher(pid) && started.elapsed() < STOP_TIMEOUT {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    Ok(())
+}
+
+/// Pids get reused so the name is checked as well
+fn is_searcher(pid: u32) -> bool {
+    pid != std::process::id()
+        && std::fs::read_to_string(format!("/proc/{}/comm", pid))
+            .map(|name| name.trim() == PROCESS_NAME)
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_is_released_and_stale_locks_taken_over() {
+        let path =
+            std::env::temp_dir().join(format!("i3-conf-searcher-test-{}.lock", std::process::id()));
+        let lock = match acquire(&path).unwrap() {
+            Instance::Locked(lock) => lock,
+            Instance::Running(pid) => panic!("lock held by {:?}", pid),
+        };
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            std::process::id().to_string()
+        );
+        drop(lock);
+        assert!(!path.exists());
+
+        std::fs::write(&path, "4194304").unwrap();
+        assert!(matches!(acquire(&path).unwrap(), Instance::Locked(_)));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn unreadable_locks_are_not_taken_over() {
+        let path = std::env::temp_dir().join(format!(
+            "i3-conf-searcher-test-empty-{}.lock",
+            std::process::id()
+        ));
+        std::fs::write(&path, "").unwrap();
+        let instance = acquire(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(instance, Instance::Running(None)));
+    }
+}
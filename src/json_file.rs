@@ -0,0 +1,63 @@
+//! JSON files in the data directory keeping the usage stats, window geometry and hidden entries,
+//! each store passes the constructor of its own error
+
+use crate::i3_config::I3ConfigError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+/// A missing file means nothing was stored yet
+pub fn load<T: DeserializeOwned + Default>(
+    path: &Path,
+    error: impl Fn(io::Error) -> I3ConfigError,
+) -> Result<T> {
+    match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|parse| error(io::Error::new(io::ErrorKind::InvalidData, parse))),
+        Err(read) if read.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+        Err(read) => Err(error(read)),
+    }
+}
+
+/// Creates the directory of the file if needed
+pub fn save<T: Serialize>(
+    value: &T,
+    path: &Path,
+    error: impl Fn(io::Error) -> I3ConfigError,
+) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(&error)?;
+    }
+    let text = serde_json::to_string_pretty(value)
+        .map_err(|serialize| error(io::Error::new(io::ErrorKind::InvalidData, serialize)))?;
+    fs::write(path, text).map_err(error)
+}
+
+/// Loads the file, changes it and saves it again
+pub fn update<T: Serialize + DeserializeOwned + Default>(
+    path: &Path,
+    error: impl Fn(io::Error) -> I3ConfigError,
+    change: impl FnOnce(&mut T),
+) -> Result<()> {
+    let mut value = load(path, &error)?;
+    change(&mut value);
+    save(&value, path, error)
+}
+
+/// The path of a store, which is missing without a data directory
+pub fn required(
+    path: Option<PathBuf>,
+    store: &str,
+    error: impl Fn(io::Error) -> I3ConfigError,
+) -> Result<PathBuf> {
+    path.ok_or_else(|| {
+        error(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no data directory for {}", store),
+        ))
+    })
+}
@@ -0,0 +1,58 @@
+//! Active X11 keyboard grab so window manager bindings don't fire while typing
+
+use i3_conf_searcher::i3_config::I3ConfigError;
+use std::time::Duration;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt, GrabMode, GrabStatus};
+use x11rb::rust_connection::RustConnection;
+use x11rb::CURRENT_TIME;
+
+/// i3 may still hold the keyboard from the binding that launched the searcher
+const ATTEMPTS: usize = 100;
+const RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// Keeps the keyboard grabbed until dropped
+pub struct KeyboardGrab {
+    connection: RustConnection,
+}
+
+impl std::fmt::Debug for KeyboardGrab {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyboardGrab").finish()
+    }
+}
+
+impl KeyboardGrab {
+    /// Grabs the keyboard on the root window.
+    /// Events still reach the searcher window since they are reported to their owner
+    pub fn new() -> Result<Self, I3ConfigError> {
+        let (connection, screen) = RustConnection::connect(None).map_err(grab_error)?;
+        let root = connection.setup().roots[screen].root;
+        for _ in 0..ATTEMPTS {
+            let reply = connection
+                .grab_keyboard(true, root, CURRENT_TIME, GrabMode::ASYNC, GrabMode::ASYNC)
+                .map_err(grab_error)?
+                .reply()
+                .map_err(grab_error)?;
+            if reply.status == GrabStatus::SUCCESS {
+                return Ok(KeyboardGrab { connection });
+            }
+            std::thread::sleep(RETRY_DELAY);
+        }
+        Err(I3ConfigError::FailedKeyboardGrab(String::from(
+            "keyboard is grabbed by another program",
+        )))
+    }
+}
+
+impl Drop for KeyboardGrab {
+    fn drop(&mut self) {
+        // Closing the connection releases the grab as well, this just makes it immediate
+        let _ = self.connection.ungrab_keyboard(CURRENT_TIME);
+        let _ = self.connection.flush();
+    }
+}
+
+fn grab_error(error: impl std::fmt::Display) -> I3ConfigError {
+    I3ConfigError::FailedKeyboardGrab(error.to_string())
+}
@@ -0,0 +1,34 @@
+//! Parsing, loading and editing of annotated i3 configs.
+//! The searcher window and the subcommands of the binary are frontends over this library,
+//! other tools can embed the search through [`SearcherBuilder`]
+
+pub mod config_writer;
+pub mod control;
+pub mod doctor;
+pub mod editor;
+pub mod executor;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod geometry;
+pub mod hidden;
+pub mod i3_command;
+pub mod i3_config;
+pub mod instance;
+pub mod json_file;
+pub mod loader;
+#[cfg(feature = "gui")]
+pub mod localization;
+pub mod matcher;
+pub mod mode_events;
+pub mod paths;
+pub mod preferences;
+pub mod presenter;
+pub mod preview;
+pub mod providers;
+pub mod searcher;
+pub mod state;
+pub mod targets;
+pub mod usage;
+pub mod validation;
+
+pub use searcher::{Searcher, SearcherBuilder};
@@ -0,0 +1,515 @@
+//! Loading the config in stages so progress and the failing stage can be reported
+
+use crate::i3_config::{
+    content_hash, default_config_path, ConfigEntry, ConfigMetadata, ConfigSource, I3ConfigError,
+};
+use crate::paths;
+use crate::preferences::Remote;
+use crate::providers::Provider;
+use futures::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+#[cfg(feature = "remote")]
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+use tokio_i3ipc::I3;
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+/// Config sources given on the command line.
+/// They are tried in order config path, git repository, url.
+/// Without any the config is asked from i3 falling back to the config file if i3 can't be reached
+#[derive(Debug, Clone, Default, Hash)]
+pub struct SourceOptions {
+    pub config: Option<PathBuf>,
+    pub git: Option<GitSource>,
+    pub url: Option<String>,
+    pub remote: Remote,
+    /// JSON files of bindings merged into the config
+    pub imports: Vec<PathBuf>,
+    /// Providers of entries besides the annotated bindings
+    pub providers: Vec<Provider>,
+}
+
+/// Config file committed in a git repository
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct GitSource {
+    pub url: String,
+    /// Path of the config inside the repository
+    pub path: PathBuf,
+}
+
+impl SourceOptions {
+    /// Name the window geometry is remembered under, the config source without imports
+    pub fn profile(&self) -> String {
+        match (&self.config, &self.git, &self.url) {
+            (Some(path), _, _) => path.display().to_string(),
+            (None, Some(git), _) => format!("{}#{}", git.url, git.path.display()),
+            (None, None, Some(url)) => url.clone(),
+            (None, None, None) => String::from("i3"),
+        }
+    }
+
+    /// Expands `~` and environment variables in the paths and urls
+    pub fn expand_variables(self) -> Result<SourceOptions> {
+        Ok(SourceOptions {
+            config: self.config.as_deref().map(paths::expand_path).transpose()?,
+            git: match self.git {
+                Some(git) => Some(GitSource {
+                    url: paths::expand(&git.url)?,
+                    path: paths::expand_path(&git.path)?,
+                }),
+                None => None,
+            },
+            url: self.url.as_deref().map(paths::expand).transpose()?,
+            remote: self.remote,
+            imports: self
+                .imports
+                .iter()
+                .map(|path| paths::expand_path(path))
+                .collect::<Result<_>>()?,
+            providers: self.providers,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadStage {
+    Connecting,
+    Fetching,
+    Downloading,
+    Syncing,
+    Reading,
+    Parsing,
+    Importing,
+}
+
+impl std::fmt::Display for LoadStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadStage::Connecting => write!(f, "connecting to i3"),
+            LoadStage::Fetching => write!(f, "fetching config"),
+            LoadStage::Downloading => write!(f, "downloading config"),
+            LoadStage::Syncing => write!(f, "syncing git repository"),
+            LoadStage::Reading => write!(f, "reading config file"),
+            LoadStage::Parsing => write!(f, "parsing config"),
+            LoadStage::Importing => write!(f, "importing bindings"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LoadProgress {
+    /// The stage that is starting
+    Stage(LoadStage),
+    /// Boxed since a config is much larger than a stage
+    Finished(Result<Box<ConfigMetadata>>),
+}
+
+enum Step {
+    Start(SourceOptions),
+    Connect,
+    #[cfg(all(target_family = "unix", feature = "ipc"))]
+    Fetch(I3),
+    Download(String, Remote),
+    Sync(GitSource),
+    Read(PathBuf),
+    Parse(String, ConfigSource),
+    Import(ConfigMetadata),
+    Done,
+}
+
+/// Loads the config from the first available source of `options`.
+/// Every stage is announced before it starts and the last item is the result
+pub fn load_in_stages(options: SourceOptions) -> impl Stream<Item = LoadProgress> {
+    let imports = options.imports.clone();
+    let providers = options.providers.clone();
+    stream::unfold(
+        (Step::Start(options), imports, providers),
+        |(step, imports, providers)| async move {
+            let (progress, next) = match step {
+                Step::Start(SourceOptions {
+                    config: Some(path), ..
+                }) => (LoadProgress::Stage(LoadStage::Reading), Step::Read(path)),
+                Step::Start(SourceOptions { git: Some(git), .. }) => {
+                    (LoadProgress::Stage(LoadStage::Syncing), Step::Sync(git))
+                }
+                Step::Start(SourceOptions {
+                    url: Some(url),
+                    remote,
+                    ..
+                }) => (
+                    LoadProgress::Stage(LoadStage::Downloading),
+                    Step::Download(url, remote),
+                ),
+                Step::Start(_) => (LoadProgress::Stage(LoadStage::Connecting), Step::Connect),
+                Step::Connect => match connect().await {
+                    #[cfg(all(target_family = "unix", feature = "ipc"))]
+                    Ok(i3) => (LoadProgress::Stage(LoadStage::Fetching), Step::Fetch(i3)),
+                    #[cfg(not(all(target_family = "unix", feature = "ipc")))]
+                    Ok(never) => match never {},
+                    Err(error) => fall_back_to_file(error),
+                },
+                #[cfg(all(target_family = "unix", feature = "ipc"))]
+                Step::Fetch(mut i3) => match i3.get_config().await {
+                    Ok(config) => (
+                        LoadProgress::Stage(LoadStage::Parsing),
+                        Step::Parse(config.config, ConfigSource::Ipc),
+                    ),
+                    Err(error) => fall_back_to_file(I3ConfigError::ipc(error)),
+                },
+                Step::Download(url, remote) => match download_i3_config(&url, &remote).await {
+                    Ok(text) => (
+                        LoadProgress::Stage(LoadStage::Parsing),
+                        Step::Parse(text, ConfigSource::Url(url)),
+                    ),
+                    Err(error) => (LoadProgress::Finished(Err(error)), Step::Done),
+                },
+                Step::Sync(git) => match sync_git_config(&git) {
+                    Ok(text) => (
+                        LoadProgress::Stage(LoadStage::Parsing),
+                        Step::Parse(text, ConfigSource::Git(git.url, git.path)),
+                    ),
+                    Err(error) => (LoadProgress::Finished(Err(error)), Step::Done),
+                },
+                Step::Read(path) => match fs::read_to_string(&path) {
+                    Ok(text) => (
+                        LoadProgress::Stage(LoadStage::Parsing),
+                        Step::Parse(text, ConfigSource::File(path)),
+                    ),
+                    Err(error) => (
+                        LoadProgress::Finished(Err(I3ConfigError::FailedConfigRead(Arc::new(
+                            error,
+                        )))),
+                        Step::Done,
+                    ),
+                },
+                Step::Parse(text, source) => match ConfigMetadata::parse(&text).map(|mut config| {
+                    config.add_provider_entries(&providers);
+                    config
+                }) {
+                    Ok(config) if !imports.is_empty() => (
+                        LoadProgress::Stage(LoadStage::Importing),
+                        Step::Import(config.with_source(source)),
+                    ),
+                    result => (
+                        LoadProgress::Finished(
+                            result.map(|config| Box::new(config.with_source(source))),
+                        ),
+                        Step::Done,
+                    ),
+                },
+                Step::Import(mut config) => {
+                    let merged = imports.iter().try_for_each(|path| {
+                        config.merge(import_bindings(path)?);
+                        Ok(())
+                    });
+                    (
+                        LoadProgress::Finished(merged.map(|_| Box::new(config))),
+                        Step::Done,
+                    )
+                }
+                Step::Done => return None,
+            };
+            Some((progress, (next, imports, providers)))
+        },
+    )
+}
+
+/// Binding of another program, e.g. tmux, in an imported JSON file
+#[derive(Deserialize)]
+struct ImportedBinding {
+    group: String,
+    description: String,
+    keys: String,
+}
+
+fn import_bindings(path: &Path) -> Result<ConfigMetadata> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| I3ConfigError::FailedConfigRead(Arc::new(error)))?;
+    let bindings: Vec<ImportedBinding> = serde_json::from_str(&text)
+        .map_err(|error| I3ConfigError::InvalidImport(Arc::new(error)))?;
+    let entries = bindings
+        .into_iter()
+        .map(|binding| ConfigEntry::new(binding.group, binding.description, binding.keys))
+        .collect();
+    Ok(ConfigMetadata::from_entries(entries).with_source(ConfigSource::Import(path.to_owned())))
+}
+
+/// Reads the config file i3 would use when i3 itself can't be asked
+fn fall_back_to_file(error: I3ConfigError) -> (LoadProgress, Step) {
+    match default_config_path() {
+        Some(path) => (LoadProgress::Stage(LoadStage::Reading), Step::Read(path)),
+        None => (LoadProgress::Finished(Err(error)), Step::Done),
+    }
+}
+
+/// Loads the config without reporting progress
+pub async fn load(options: SourceOptions) -> Result<ConfigMetadata> {
+    let mut progress = Box::pin(load_in_stages(options));
+    while let Some(item) = progress.next().await {
+        if let LoadProgress::Finished(result) = item {
+            return result.map(|config| *config);
+        }
+    }
+    Err(I3ConfigError::ConfigParsingError)
+}
+
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+async fn connect() -> Result<I3> {
+    I3::connect().await.map_err(I3ConfigError::ipc)
+}
+
+#[cfg(not(all(target_family = "unix", feature = "ipc")))]
+async fn connect() -> Result<std::convert::Infallible> {
+    Err(I3ConfigError::ipc_unavailable())
+}
+
+#[cfg(feature = "remote")]
+async fn download_i3_config(url: &str, remote: &Remote) -> Result<String> {
+    let download_error = |error| I3ConfigError::FailedGetRequest(Arc::new(error));
+    let mut request = reqwest::Client::new().get(url);
+    if let Some(token) = &remote.bearer_token {
+        request = request.bearer_auth(token);
+    } else if let Some(username) = &remote.username {
+        request = request.basic_auth(username, remote.password.as_ref());
+    }
+    for (name, value) in &remote.headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    let response = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(download_error)?;
+    let config = response.text().await.map_err(download_error)?;
+    if let Some(expected) = &remote.sha256 {
+        verify_checksum(&config, expected)?;
+    }
+    Ok(config)
+}
+
+/// Clones or pulls the repository into the cache and reads the config from it
+fn sync_git_config(git: &GitSource) -> Result<String> {
+    let cache = paths::cache_dir().ok_or_else(|| {
+        I3ConfigError::FailedGitSync(String::from("no cache directory to clone into"))
+    })?;
+    let checkout = sync_repository(&git.url, &cache.join("git"))?;
+    fs::read_to_string(checkout.join(&git.path))
+        .map_err(|error| I3ConfigError::FailedConfigRead(Arc::new(error)))
+}
+
+/// Every repository gets its own checkout in `cache` named after the hash of its url
+fn sync_repository(url: &str, cache: &Path) -> Result<PathBuf> {
+    let checkout = cache.join(format!("{:x}", content_hash(url)));
+    if checkout.join(".git").is_dir() {
+        run_git(Command::new("git").arg("-C").arg(&checkout).args([
+            "pull",
+            "--ff-only",
+            "--quiet",
+        ]))?;
+    } else {
+        fs::create_dir_all(cache)
+            .map_err(|error| I3ConfigError::FailedGitSync(error.to_string()))?;
+        run_git(
+            Command::new("git")
+                // Urls starting with a dash aren't taken as options
+                .args(["clone", "--depth", "1", "--quiet", "--", url])
+                .arg(&checkout),
+        )?;
+    }
+    Ok(checkout)
+}
+
+fn run_git(command: &mut Command) -> Result<()> {
+    let output = command
+        .output()
+        .map_err(|error| I3ConfigError::FailedSpawn(Arc::new(error)))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(I3ConfigError::FailedGitSync(
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ))
+    }
+}
+
+#[cfg(not(feature = "remote"))]
+async fn download_i3_config(_url: &str, _remote: &Remote) -> Result<String> {
+    Err(I3ConfigError::MissingFeature("remote"))
+}
+
+#[cfg(feature = "remote")]
+fn verify_checksum(config: &str, expected: &str) -> Result<()> {
+    let actual = format!("{:x}", Sha256::digest(config.as_bytes()));
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(I3ConfigError::ChecksumMismatch {
+            expected: expected.to_owned(),
+            actual,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect_progress(options: SourceOptions) -> Vec<LoadProgress> {
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(load_in_stages(options).collect())
+    }
+
+    #[test]
+    fn profile_follows_the_config_source() {
+        assert_eq!(SourceOptions::default().profile(), "i3");
+        let git = SourceOptions {
+            git: Some(GitSource {
+                url: String::from("https://example.com/dotfiles.git"),
+                path: PathBuf::from("i3/config"),
+            }),
+            url: Some(String::from("https://example.com/config")),
+            ..Default::default()
+        };
+        assert_eq!(git.profile(), "https://example.com/dotfiles.git#i3/config");
+    }
+
+    #[test]
+    fn config_path_wins_over_url() {
+        let path =
+            std::env::temp_dir().join(format!("i3-conf-searcher-loader-{}", std::process::id()));
+        fs::write(&path, "## group1 // description1 // keys1 ##").unwrap();
+        let progress = collect_progress(SourceOptions {
+            config: Some(path.clone()),
+            url: Some(String::from("not a url")),
+            ..Default::default()
+        });
+        fs::remove_file(&path).unwrap();
+        match progress.as_slice() {
+            [LoadProgress::Stage(LoadStage::Reading), LoadProgress::Stage(LoadStage::Parsing), LoadProgress::Finished(Ok(config))] =>
+            {
+                assert_eq!(config.entries().len(), 1);
+                assert_eq!(config.source(), &ConfigSource::File(path.clone()));
+                assert_eq!(config.source_path(), Some(path.as_path()));
+            }
+            other => panic!("unexpected progress {:?}", other),
+        }
+    }
+
+    #[test]
+    fn imports_are_merged_into_config() {
+        let directory =
+            std::env::temp_dir().join(format!("i3-conf-searcher-import-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let config = directory.join("config");
+        let import = directory.join("tmux.json");
+        fs::write(&config, "## group1 // description1 // keys1 ##").unwrap();
+        fs::write(
+            &import,
+            r#"[{"group": "tmux", "description": "Split", "keys": "Ctrl+b %"}]"#,
+        )
+        .unwrap();
+        let progress = collect_progress(SourceOptions {
+            config: Some(config),
+            imports: vec![import.clone()],
+            ..Default::default()
+        });
+        fs::remove_dir_all(&directory).unwrap();
+        match progress.last() {
+            Some(LoadProgress::Finished(Ok(config))) => {
+                assert_eq!(config.entries().len(), 2);
+                assert_eq!(
+                    config.entries()[1].source(),
+                    Some(&ConfigSource::Import(import))
+                );
+            }
+            other => panic!("unexpected progress {:?}", other),
+        }
+        assert!(matches!(
+            progress[2],
+            LoadProgress::Stage(LoadStage::Importing)
+        ));
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn failed_download_reports_stage() {
+        let progress = collect_progress(SourceOptions {
+            url: Some(String::from("not a url")),
+            ..Default::default()
+        });
+        assert!(matches!(
+            progress.as_slice(),
+            [
+                LoadProgress::Stage(LoadStage::Downloading),
+                LoadProgress::Finished(Err(I3ConfigError::FailedGetRequest(_)))
+            ]
+        ));
+    }
+
+    fn git(directory: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(directory)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn sync_repository_clones_then_pulls() {
+        let root =
+            std::env::temp_dir().join(format!("i3-conf-searcher-git-{}", std::process::id()));
+        let origin = root.join("origin");
+        let cache = root.join("cache");
+        fs::create_dir_all(origin.join("i3")).unwrap();
+        git(&origin, &["init", "--quiet"]);
+        fs::write(origin.join("i3/config"), "first").unwrap();
+        git(&origin, &["add", "."]);
+        git(&origin, &["commit", "--quiet", "-m", "first"]);
+        let url = origin.to_str().unwrap();
+
+        let checkout = sync_repository(url, &cache).unwrap();
+        assert_eq!(
+            fs::read_to_string(checkout.join("i3/config")).unwrap(),
+            "first"
+        );
+
+        fs::write(origin.join("i3/config"), "second").unwrap();
+        git(&origin, &["commit", "--quiet", "-am", "second"]);
+        assert_eq!(sync_repository(url, &cache).unwrap(), checkout);
+        assert_eq!(
+            fs::read_to_string(checkout.join("i3/config")).unwrap(),
+            "second"
+        );
+
+        assert!(matches!(
+            sync_repository(root.join("missing").to_str().unwrap(), &cache),
+            Err(I3ConfigError::FailedGitSync(_))
+        ));
+        let injected = root.join("injected");
+        let option = format!("--upload-pack=touch {}", injected.display());
+        assert!(sync_repository(&option, &cache).is_err());
+        assert!(!injected.exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn checksum_verification() {
+        let checksum = "a591a6d40bf420404a011733cfb7b190d62c65bf0bcda32b57b277d9ad9f146e";
+        assert!(verify_checksum("Hello World", checksum).is_ok());
+        assert!(verify_checksum("Hello World", &checksum.to_uppercase()).is_ok());
+        assert!(matches!(
+            verify_checksum("Hello world", checksum),
+            Err(I3ConfigError::ChecksumMismatch { .. })
+        ));
+    }
+}
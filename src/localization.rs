@@ -0,0 +1,183 @@
+//! Translations of the searcher window, stored as Fluent resources.
+//! English and German are bundled, more can be added with [`Localizer::add_translation`]
+
+use crate::i3_config::I3ConfigError;
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+use std::path::PathBuf;
+use unic_langid::LanguageIdentifier;
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+const ENGLISH: &str = include_str!("../locales/en.ftl");
+
+const BUNDLED: [(&str, &str); 1] = [("de", include_str!("../locales/de.ftl"))];
+
+/// Looks messages up in the translations for the locale, falling back to English
+pub struct Localizer {
+    /// Most preferred first, English is always last
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+impl std::fmt::Debug for Localizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locales: Vec<String> = self
+            .bundles
+            .iter()
+            .flat_map(|bundle| bundle.locales.iter().map(ToString::to_string))
+            .collect();
+        f.debug_struct("Localizer")
+            .field("locales", &locales)
+            .finish()
+    }
+}
+
+impl Localizer {
+    /// Bundled translation for the language of `locale` like `de_DE.UTF-8`
+    pub fn new(locale: &str) -> Self {
+        let mut localizer = Localizer {
+            bundles: vec![bundle("en", ENGLISH).expect("English messages are valid")],
+        };
+        if let Some(requested) = parse_locale(locale) {
+            for (language, source) in BUNDLED.iter() {
+                if requested.language.as_str() == *language {
+                    localizer
+                        .add_translation(language, source)
+                        .expect("bundled messages are valid");
+                }
+            }
+        }
+        localizer
+    }
+
+    /// Locale of `LC_ALL`, `LC_MESSAGES` or `LANG`, with translations from
+    /// `$XDG_CONFIG_HOME/i3-conf-searcher/locales/<language>.ftl` taking precedence
+    pub fn detect() -> Result<Self> {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        let mut localizer = Localizer::new(&locale);
+        let language = match parse_locale(&locale) {
+            Some(locale) => locale.language.as_str().to_owned(),
+            None => return Ok(localizer),
+        };
+        if let Some(path) = user_translation_path(&language) {
+            match std::fs::read_to_string(&path) {
+                Ok(source) => localizer.add_translation(&language, &source)?,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => (),
+                Err(error) => {
+                    return Err(I3ConfigError::InvalidTranslation(format!(
+                        "{}: {}",
+                        path.display(),
+                        error
+                    )))
+                }
+            }
+        }
+        Ok(localizer)
+    }
+
+    /// Adds Fluent messages that take precedence over the ones added before.
+    /// Messages missing from them are still looked up in the others
+    pub fn add_translation(&mut self, locale: &str, source: &str) -> Result<()> {
+        let translation = bundle(locale, source)?;
+        self.bundles.insert(0, translation);
+        Ok(())
+    }
+
+    pub fn text(&self, id: &str) -> String {
+        self.format(id, std::iter::empty())
+    }
+
+    /// Message with its `$variables` replaced, the id itself if no translation has it
+    pub fn format<'a>(
+        &self,
+        id: &str,
+        args: impl IntoIterator<Item = (&'a str, FluentValue<'a>)>,
+    ) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(name, value);
+        }
+        for bundle in &self.bundles {
+            if let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) {
+                let mut errors = vec![];
+                return bundle
+                    .format_pattern(pattern, Some(&fluent_args), &mut errors)
+                    .into_owned();
+            }
+        }
+        id.to_owned()
+    }
+}
+
+fn bundle(locale: &str, source: &str) -> Result<FluentBundle<FluentResource>> {
+    let language = parse_locale(locale)
+        .ok_or_else(|| I3ConfigError::InvalidTranslation(format!("unknown locale {}", locale)))?;
+    let resource = FluentResource::try_new(source.to_owned()).map_err(|(_, errors)| {
+        I3ConfigError::InvalidTranslation(format!("{} has {} errors", locale, errors.len()))
+    })?;
+    let mut bundle = FluentBundle::new(vec![language]);
+    // Isolation marks show up as boxes in the bundled font
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .map_err(|errors| I3ConfigError::InvalidTranslation(format!("{:?}", errors)))?;
+    Ok(bundle)
+}
+
+/// `de_DE.UTF-8` and `de-DE` both become `de-DE`
+fn parse_locale(locale: &str) -> Option<LanguageIdentifier> {
+    let name = locale.split(['.', '@']).next()?.replace('_', "-");
+    match name.as_str() {
+        "" | "C" | "POSIX" => None,
+        name => name.parse().ok(),
+    }
+}
+
+fn user_translation_path(language: &str) -> Option<PathBuf> {
+    crate::paths::config_dir().map(|config| config.join(format!("locales/{}.ftl", language)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translations_fall_back_to_english() {
+        let english = Localizer::new("C");
+        assert_eq!(english.text("no-matches"), "No matching entries");
+        assert_eq!(
+            english.format("config-warnings", vec![("count", 1.into())]),
+            "1 config warning"
+        );
+
+        let mut german = Localizer::new("de_DE.UTF-8");
+        assert_eq!(german.text("no-matches"), "Keine passenden Einträge");
+        assert_eq!(
+            german.format("source", vec![("source", "i3 IPC".into())]),
+            "Quelle: i3 IPC"
+        );
+
+        german
+            .add_translation("de-AT", "no-matches = Nix gefunden")
+            .unwrap();
+        assert_eq!(german.text("no-matches"), "Nix gefunden");
+        assert_eq!(german.text("dismiss"), "Schließen");
+        assert_eq!(german.text("unknown-message"), "unknown-message");
+        assert!(german.add_translation("de", "= broken").is_err());
+    }
+
+    #[test]
+    fn bundled_translations_are_complete() {
+        let german = Localizer::new("de");
+        let ids = ENGLISH
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|line| line.split(" =").next());
+        for id in ids {
+            assert!(german.bundles[0].has_message(id), "{} isn't translated", id);
+        }
+    }
+}
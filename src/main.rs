@@ -1,16 +1,28 @@
-mod i3_config;
+#[cfg(feature = "cli")]
+mod cli;
+#[cfg(feature = "gui")]
+mod gui;
+#[cfg(feature = "gui")]
+mod hotkey;
+#[cfg(feature = "gui")]
+mod keyboard_grab;
+#[cfg(feature = "gui")]
+mod override_redirect;
+#[cfg(feature = "gui")]
 mod style;
+#[cfg(feature = "tray")]
+mod tray;
+
+#[cfg(not(any(feature = "gui", feature = "cli")))]
+compile_error!("at least one of the gui and cli features has to be enabled");
 
 use clap::Clap;
-use iced::{
-    scrollable, text_input, Align, Application, Clipboard, Color, Column, Command, Container,
-    Element, Font, Length, Row, Scrollable, Settings, Space, Subscription, Text, TextInput,
-};
-use iced_native::{
-    keyboard::{Event, KeyCode},
-    window,
-    Event::{Keyboard, Window},
-};
+#[cfg(feature = "gui")]
+use i3_conf_searcher::hidden::HiddenEntries;
+#[cfg(feature = "gui")]
+use i3_conf_searcher::{executor, instance};
+use i3_conf_searcher::{i3_config, loader, matcher, paths, preferences, presenter};
+#[cfg(feature = "gui")]
 use style::Theme;
 
 #[derive(Clap)]
@@ -19,354 +31,267 @@ use style::Theme;
     author = "David W. <dweis7@gmail.com>"
 )]
 struct Args {
-    #[clap(short, long, about = "Use light theme")]
-    light: bool,
-    #[clap(short, long, about = "Stay alive after focus loss")]
-    keep_alive: bool,
+    #[cfg(feature = "gui")]
+    #[clap(flatten)]
+    window: WindowArgs,
+    /// Path of i3 config
+    /// Takes precedence over --url and i3 domain socket
+    #[clap(long, parse(from_os_str))]
+    config: Option<std::path::PathBuf>,
+    /// Url of git repository containing i3 config
+    /// The repository is cloned and pulled on every start
+    #[clap(long)]
+    git: Option<String>,
+    /// Path of i3 config inside the git repository
+    #[clap(long, requires = "git", parse(from_os_str))]
+    path: Option<std::path::PathBuf>,
     /// Url of i3 config
     /// Use if you don't want to load form i3 domain socket
     #[clap(long)]
     url: Option<String>,
+    /// JSON file of other bindings to search together with the i3 config
+    /// Can be passed multiple times
+    #[clap(
+        long,
+        parse(from_os_str),
+        multiple_occurrences(true),
+        number_of_values = 1
+    )]
+    import: Vec<std::path::PathBuf>,
+    /// Fuzzy matching backend, skim or nucleo if built with the nucleo feature
+    #[clap(long)]
+    matcher: Option<matcher::MatcherKind>,
+    #[clap(long, about = "Print command line errors as JSON")]
+    errors_json: bool,
+    #[clap(
+        long,
+        about = "Print where preferences, caches and data are stored and exit"
+    )]
+    print_paths: bool,
+    #[cfg(feature = "cli")]
+    #[clap(long, about = "Report malformed annotations and exit")]
+    check: bool,
+    /// Pick an entry with a fuzzy finder in the terminal and run it
+    #[cfg(feature = "cli")]
+    #[clap(long, conflicts_with = "check")]
+    pick: bool,
+    #[cfg(feature = "cli")]
+    #[clap(subcommand)]
+    command: Option<cli::SubCommand>,
 }
 
-pub fn main() {
-    let args: Args = Args::parse();
-    let theme = if args.light {
-        Theme::Light
-    } else {
-        Theme::Dark
-    };
-    let init_flags = InitFlags::new(theme, !args.keep_alive, args.url);
-    ApplicationState::run(Settings::with_flags(init_flags)).unwrap()
-}
-
-#[derive(Debug)]
-struct InitFlags {
-    theme: Theme,
-    exit_on_focus_loss: bool,
-    config_url: Option<String>,
+/// Options of the searcher window
+#[cfg(feature = "gui")]
+#[derive(Clap)]
+struct WindowArgs {
+    #[clap(short, long, about = "Use light theme")]
+    light: bool,
+    #[clap(long, conflicts_with = "light", about = "Use high contrast theme")]
+    high_contrast: bool,
+    /// Print the selected entry to stdout whenever it changes, for screen readers
+    #[clap(long)]
+    announce: bool,
+    #[clap(short, long, about = "Stay alive after focus loss")]
+    keep_alive: bool,
+    #[clap(long, about = "Show where each entry was loaded from")]
+    source_badges: bool,
+    /// Command used to open the config with Ctrl+E
+    /// {file} and {line} are replaced with the location of the selected entry
+    #[clap(long)]
+    editor: Option<String>,
+    #[clap(long, about = "Hide entries needing modifiers that aren't held")]
+    strict: bool,
+    /// Only show bindings of this i3 mode
+    #[clap(long)]
+    mode: Option<String>,
+    /// Grab the keyboard while open so i3 bindings don't fire while typing, X11 only
+    #[clap(long)]
+    grab_keyboard: bool,
+    #[clap(
+        long,
+        about = "Close the searcher that's already open instead of focusing it"
+    )]
+    replace: bool,
+    /// Run the entry as soon as the search narrows the list down to it
+    #[clap(long)]
+    quick: bool,
+    /// Shrink the window to fit the matching entries, needs the window to be floating
+    #[clap(long)]
+    auto_height: bool,
+    /// Bypass the window manager and center the window above everything, X11 only.
+    /// Implies --grab-keyboard
+    #[clap(long, conflicts_with = "auto-height")]
+    override_redirect: bool,
+    /// Open the window centered on the mouse pointer instead of the position in the preferences,
+    /// e.g. when started from a bar button
+    #[clap(long)]
+    at_cursor: bool,
+    /// Opacity of the window background from 0 to 1, needs a compositor
+    #[clap(long, default_value = "1", parse(try_from_str = parse_opacity))]
+    opacity: f32,
+    /// Fade in new results and the selection highlight
+    #[clap(long)]
+    animations: bool,
 }
 
-impl InitFlags {
-    fn new(theme: Theme, exit_on_focus_loss: bool, config_url: Option<String>) -> Self {
-        InitFlags {
-            theme,
-            exit_on_focus_loss,
-            config_url,
-        }
+#[cfg(feature = "gui")]
+fn parse_opacity(text: &str) -> Result<f32, String> {
+    match text.parse::<f32>() {
+        Ok(opacity) if (0.0..=1.0).contains(&opacity) => Ok(opacity),
+        _ => Err(String::from("expected a number from 0 to 1")),
     }
 }
 
-#[derive(Debug)]
-struct ApplicationState {
-    theme: Theme,
-    exit_on_focus_loss: bool,
-    state: Searcher,
-    modifier_state: i3_config::Modifiers,
-}
-
-impl ApplicationState {
-    fn new(theme: Theme, exit_on_focus_loss: bool) -> ApplicationState {
-        ApplicationState {
-            theme,
-            exit_on_focus_loss,
-            state: Searcher::Loading,
-            modifier_state: i3_config::Modifiers::default(),
-        }
+pub fn main() {
+    let args: Args = Args::parse();
+    if args.print_paths {
+        print!("{}", presenter::paths_table(&paths::locations()));
+        return;
     }
-}
-
-#[derive(Debug)]
-struct State {
-    scroll: scrollable::State,
-    search_string: String,
-    text_input_state: text_input::State,
-    shortcuts: i3_config::ConfigMetadata,
-}
-
-impl State {
-    pub fn new(config: i3_config::ConfigMetadata) -> State {
-        State {
-            scroll: scrollable::State::new(),
-            search_string: String::from(""),
-            text_input_state: text_input::State::focused(),
-            shortcuts: config,
+    let mut preferences = exit_on_error(preferences::Preferences::load(), args.errors_json);
+    if let Some(matcher) = args.matcher {
+        preferences.matcher = matcher;
+    }
+    let git_path = args.path;
+    let sources = loader::SourceOptions {
+        config: args.config,
+        git: args.git.map(|url| loader::GitSource {
+            url,
+            path: git_path.unwrap_or_else(|| std::path::PathBuf::from("config")),
+        }),
+        url: args.url,
+        remote: preferences.remote.clone().with_env(),
+        imports: args.import,
+        providers: preferences.providers.clone(),
+    };
+    let sources = exit_on_error(sources.expand_variables(), args.errors_json);
+    #[cfg(feature = "cli")]
+    {
+        if args.check {
+            exit_on_error(cli::check(sources), args.errors_json);
+            return;
+        }
+        if args.pick {
+            exit_on_error(cli::pick(sources, &preferences), args.errors_json);
+            return;
+        }
+        if let Some(command) = args.command {
+            #[cfg(feature = "gui")]
+            let result = cli::run(command, sources, &preferences, theme(&args.window));
+            #[cfg(not(feature = "gui"))]
+            let result = cli::run(command, sources, &preferences);
+            exit_on_error(result, args.errors_json);
+            return;
         }
     }
+    #[cfg(feature = "gui")]
+    open_searcher(args.window, &preferences, sources, args.errors_json);
+    #[cfg(not(feature = "gui"))]
+    exit_on_error(
+        Err::<(), _>(i3_config::I3ConfigError::MissingFeature("gui")),
+        args.errors_json,
+    );
 }
 
-#[derive(Debug)]
-enum Searcher {
-    Loading,
-    Searching(State),
-    Error,
-    UnsupportedPlatform,
-}
-
-#[derive(Debug, Clone)]
-enum Message {
-    ConfigLoaded(Result<i3_config::ConfigMetadata, i3_config::I3ConfigError>),
-    InputChanged(String),
-    Exit,
-    EventOccurred(iced_native::Event),
-}
-
-async fn load_i3_config(
-    url: Option<String>,
-) -> Result<i3_config::ConfigMetadata, i3_config::I3ConfigError> {
-    let config_result = match url {
-        Some(url) => i3_config::ConfigMetadata::load_from_web(&url).await,
-        None => i3_config::ConfigMetadata::load_from_ipc().await,
+#[cfg(feature = "gui")]
+fn open_searcher(
+    args: WindowArgs,
+    preferences: &preferences::Preferences,
+    sources: loader::SourceOptions,
+    errors_json: bool,
+) {
+    let theme = theme(&args);
+    let mut window = preferences.window.clone();
+    if args.at_cursor {
+        window.position = Some(preferences::WindowPosition::Mouse);
+    }
+    // which-key popups are shown next to the searcher
+    let instance_lock = match args.mode {
+        Some(_) => None,
+        None => claim_instance(args.replace, errors_json),
     };
-    config_result
+    gui::run(gui::InitFlags {
+        theme,
+        density: preferences.density,
+        window,
+        collapse_duplicates: preferences.collapse_duplicates,
+        exit_on_focus_loss: !args.keep_alive,
+        focus_loss_grace: preferences.focus_loss_grace(),
+        group_sidebar: preferences.group_sidebar,
+        order: preferences.order,
+        min_score: preferences.min_score,
+        matcher: preferences.matcher,
+        search_text: preferences.search_fields.clone(),
+        sources,
+        editor: args.editor,
+        strict_modifiers: args.strict || preferences.strict_modifiers,
+        mode: args.mode,
+        source_badges: args.source_badges,
+        grab_keyboard: args.grab_keyboard || args.override_redirect,
+        override_redirect: args.override_redirect,
+        notify_failures: preferences.notify_failures(),
+        usage_stats: preferences.usage_stats,
+        hidden_entries: HiddenEntries::path(),
+        stay_in_mode: preferences.stay_in_mode,
+        quick: args.quick,
+        auto_height: args.auto_height,
+        opacity: args.opacity,
+        animations: args.animations,
+        announce: args.announce,
+        instance_lock,
+    })
 }
 
-impl Application for ApplicationState {
-    type Executor = iced::executor::Default;
-    type Message = Message;
-    type Flags = InitFlags;
-
-    fn new(flags: Self::Flags) -> (ApplicationState, Command<Message>) {
-        (
-            ApplicationState::new(flags.theme, flags.exit_on_focus_loss),
-            Command::perform(load_i3_config(flags.config_url), Message::ConfigLoaded),
-        )
-    }
-
-    fn title(&self) -> String {
-        String::from("i3 Config Searcher")
+/// Also used for the searchers `which-key` opens
+#[cfg(feature = "gui")]
+fn theme(args: &WindowArgs) -> Theme {
+    if args.light {
+        Theme::Light
+    } else if args.high_contrast {
+        Theme::HighContrast
+    } else {
+        Theme::Dark
     }
+}
 
-    fn update(&mut self, message: Message, _: &mut Clipboard) -> Command<Message> {
-        match message {
-            Message::ConfigLoaded(Ok(config)) => {
-                self.state = Searcher::Searching(State::new(config));
-                Command::none()
-            }
-            Message::ConfigLoaded(Err(error)) => {
-                self.state = match error {
-                    i3_config::I3ConfigError::UnsupportedPlatform => Searcher::UnsupportedPlatform,
-                    _ => Searcher::Error,
-                };
-                Command::none()
-            }
-            Message::InputChanged(input) => match &mut self.state {
-                Searcher::Searching(state) => {
-                    state.scroll = scrollable::State::new();
-                    state.search_string = input;
-                    Command::none()
-                }
-                _ => Command::none(),
-            },
-            Message::Exit => std::process::exit(0),
-            Message::EventOccurred(Keyboard(Event::ModifiersChanged(modifiers))) => {
-                let modifier_state = i3_config::Modifiers::new(
-                    modifiers.shift,
-                    modifiers.control,
-                    modifiers.alt,
-                    modifiers.logo,
-                );
-                self.modifier_state = modifier_state;
-                Command::none()
-            }
-            Message::EventOccurred(Keyboard(Event::KeyReleased {
-                key_code,
-                modifiers,
-            })) => {
-                let modifier_state = i3_config::Modifiers::new(
-                    modifiers.shift,
-                    modifiers.control,
-                    modifiers.alt,
-                    modifiers.logo,
-                );
-                // This will work because KeyDown will release focus from the text input
-                // and then we get the event here
-                // This may be flaky and in the future this may need a better solution
-                self.modifier_state = modifier_state;
-                if key_code == KeyCode::Escape {
-                    std::process::exit(0);
-                }
-                Command::none()
-            }
-            Message::EventOccurred(Window(window::Event::Unfocused)) => {
-                if self.exit_on_focus_loss {
-                    std::process::exit(0);
-                }
-                Command::none()
-            }
-            Message::EventOccurred(_) => Command::none(),
+/// Exits after focusing the open searcher unless `replace` is set.
+/// Without a usable lock file the searcher still opens
+#[cfg(feature = "gui")]
+fn claim_instance(replace: bool, errors_json: bool) -> Option<instance::InstanceLock> {
+    let path = instance::lock_path();
+    let result = instance::acquire(&path).and_then(|instance| match instance {
+        instance::Instance::Running(Some(pid)) if replace => {
+            instance::stop(pid)?;
+            instance::acquire(&path)
         }
-    }
-
-    fn subscription(&self) -> Subscription<Self::Message> {
-        iced_native::subscription::events().map(Message::EventOccurred)
-    }
-
-    fn view(&mut self) -> Element<Message> {
-        match &mut self.state {
-            Searcher::Loading => Container::new(Text::new("Loading config...").size(40))
-                .width(Length::Fill)
-                .height(Length::Fill)
-                .center_x()
-                .center_y()
-                .style(self.theme)
-                .into(),
-            Searcher::Error => Container::new(
-                Text::new("Error loading i3 config")
-                    .size(40)
-                    .color(Color::from_rgb(1., 0., 0.)),
-            )
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .center_x()
-            .center_y()
-            .style(self.theme)
-            .into(),
-            Searcher::UnsupportedPlatform => Container::new(
-                Text::new("i3 only works on Linux")
-                    .size(40)
-                    .color(Color::from_rgb(1., 0., 0.)),
-            )
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .center_x()
-            .center_y()
-            .style(self.theme)
-            .into(),
-            Searcher::Searching(state) => {
-                let input = TextInput::new(
-                    &mut state.text_input_state,
-                    "Enter search here...",
-                    &state.search_string,
-                    Message::InputChanged,
-                )
-                .width(Length::Fill)
-                .style(self.theme)
-                .size(30)
-                .padding(10)
-                .on_submit(Message::Exit);
-
-                let modifiers_label = Row::new()
-                    .width(Length::Fill)
-                    .align_items(Align::Start)
-                    .push(Space::new(Length::Units(10), Length::Units(20)))
-                    .push(
-                        Text::new(self.modifier_state.description())
-                            .color(Color::from_rgb(0.5, 0.5, 0.5))
-                            .font(FONT)
-                            .size(20),
-                    );
-
-                let entries = state
-                    .shortcuts
-                    .filter(&state.search_string, &self.modifier_state);
-
-                let content = if entries.is_empty() {
-                    let warning = Text::new("No matching entries")
-                        .size(40)
-                        .horizontal_alignment(iced::HorizontalAlignment::Center)
-                        .vertical_alignment(iced::VerticalAlignment::Top)
-                        .width(Length::Fill)
-                        .height(Length::Fill)
-                        .color(Color::from_rgb(0.9, 0.6, 0.1));
-
-                    Column::new()
-                        .push(input)
-                        .push(modifiers_label)
-                        .push(warning)
-                        .spacing(10)
-                        .padding(5)
-                } else {
-                    let entries_column = entries.iter().fold(
-                        Column::new().padding(20),
-                        |column: Column<Message>, config_entry| column.push(config_entry.view()),
-                    );
-
-                    let scrollable_entries = Scrollable::new(&mut state.scroll)
-                        .push(entries_column)
-                        .style(self.theme);
-                    Column::new()
-                        .push(input)
-                        .push(modifiers_label)
-                        .push(scrollable_entries)
-                        .spacing(10)
-                        .padding(5)
-                };
-
-                Container::new(content)
-                    .style(self.theme)
-                    .width(Length::Fill)
-                    .height(Length::Fill)
-                    .center_x()
-                    .align_y(iced::Align::Start)
-                    .into()
-            }
+        other => Ok(other),
+    });
+    match result {
+        Ok(instance::Instance::Locked(lock)) => Some(lock),
+        Ok(instance::Instance::Running(_)) => {
+            let focused = tokio::runtime::Runtime::new()
+                .expect("Failed to start tokio runtime")
+                .block_on(executor::focus_searcher());
+            exit_on_error(focused, errors_json);
+            std::process::exit(0);
+        }
+        Err(error) => {
+            eprintln!("{}", presenter::error_chain(&error));
+            None
         }
     }
 }
 
-trait ViewModel {
-    fn view(&self) -> Element<Message>;
-}
-
-impl ViewModel for i3_config::ConfigEntry {
-    fn view(&self) -> Element<Message> {
-        let mut row = Row::new()
-            .width(Length::Fill)
-            .align_items(Align::Center)
-            .padding(10);
-
-        for element in self.matched_group() {
-            match element {
-                i3_config::MatchElement::Matched(element) => {
-                    row = row.push(
-                        Text::new(element)
-                            .font(FONT)
-                            .size(20)
-                            .color(Color::from_rgb(1.0, 0.0, 0.5)),
-                    );
-                }
-
-                i3_config::MatchElement::Unmatched(element) => {
-                    row = row.push(
-                        Text::new(element.to_owned())
-                            .font(FONT)
-                            .size(20)
-                            .color(Color::from_rgb(0.9, 0.6, 0.1)),
-                    );
-                }
+fn exit_on_error<T>(result: Result<T, i3_config::I3ConfigError>, errors_json: bool) -> T {
+    match result {
+        Ok(value) => value,
+        Err(error) => {
+            if errors_json {
+                eprintln!("{}", presenter::error_json(&error));
+            } else {
+                eprintln!("{}", presenter::error_chain(&error));
             }
+            std::process::exit(error.exit_code());
         }
-        // .push(
-        //     Text::new(self.group().to_owned())
-        //         .font(FONT)
-        //         .size(20)
-        //         .color(Color::from_rgb(0.9, 0.6, 0.1)),
-        // )
-        row = row.push(Space::new(Length::Units(10), Length::Shrink));
-        for element in self.matched_description() {
-            match element {
-                i3_config::MatchElement::Matched(element) => {
-                    row = row.push(
-                        Text::new(element)
-                            .font(FONT)
-                            .size(20)
-                            .color(Color::from_rgb(1.0, 0.0, 0.5)),
-                    );
-                }
-
-                i3_config::MatchElement::Unmatched(element) => {
-                    row = row.push(Text::new(element.to_owned()).font(FONT).size(20));
-                }
-            }
-        }
-        row.push(Space::new(Length::Fill, Length::Shrink))
-            .push(Text::new(self.keys().to_owned()).font(FONT).size(20))
-            .into()
     }
 }
-
-const FONT: Font = Font::External {
-    name: "MesloLGS",
-    bytes: include_bytes!("../fonts/MesloLGS NF Regular.ttf"),
-};
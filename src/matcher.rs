@@ -0,0 +1,109 @@
+//! Fuzzy matching backends, all of them report the indices of the matched characters
+//! so matches can be highlighted
+
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::Deserialize;
+
+/// Backend used for searching, see `--matcher`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MatcherKind {
+    #[default]
+    Skim,
+    /// Faster and normalizes Unicode, scores aren't comparable to skim's
+    #[cfg(feature = "nucleo")]
+    Nucleo,
+}
+
+impl std::str::FromStr for MatcherKind {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "skim" => Ok(MatcherKind::Skim),
+            #[cfg(feature = "nucleo")]
+            "nucleo" => Ok(MatcherKind::Nucleo),
+            #[cfg(not(feature = "nucleo"))]
+            "nucleo" => Err(String::from("built without the nucleo feature")),
+            _ => Err(String::from("expected skim or nucleo")),
+        }
+    }
+}
+
+pub(crate) enum Matcher {
+    /// Boxed since it carries its scoring buffers inline
+    Skim(Box<SkimMatcherV2>),
+    #[cfg(feature = "nucleo")]
+    Nucleo(nucleo_matcher::Matcher),
+}
+
+impl Matcher {
+    pub(crate) fn new(kind: MatcherKind) -> Self {
+        match kind {
+            MatcherKind::Skim => Matcher::Skim(Box::default()),
+            #[cfg(feature = "nucleo")]
+            MatcherKind::Nucleo => Matcher::Nucleo(nucleo_matcher::Matcher::new(
+                nucleo_matcher::Config::DEFAULT,
+            )),
+        }
+    }
+
+    /// Score and sorted character indices of `pattern` in `text`, `None` if it doesn't match.
+    /// Both ignore case unless the pattern contains upper case characters
+    pub(crate) fn fuzzy_indices(&mut self, text: &str, pattern: &str) -> Option<(i64, Vec<usize>)> {
+        match self {
+            Matcher::Skim(matcher) => matcher.fuzzy_indices(text, pattern),
+            #[cfg(feature = "nucleo")]
+            Matcher::Nucleo(matcher) => {
+                use nucleo_matcher::pattern::{Atom, AtomKind, CaseMatching, Normalization};
+                let atom = Atom::new(
+                    pattern,
+                    CaseMatching::Smart,
+                    Normalization::Smart,
+                    AtomKind::Fuzzy,
+                    false,
+                );
+                let mut buffer = vec![];
+                let mut indices = vec![];
+                let score = atom.indices(
+                    nucleo_matcher::Utf32Str::new(text, &mut buffer),
+                    matcher,
+                    &mut indices,
+                )?;
+                indices.sort_unstable();
+                indices.dedup();
+                Some((
+                    i64::from(score),
+                    indices.into_iter().map(|index| index as usize).collect(),
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backends() -> Vec<MatcherKind> {
+        vec![
+            MatcherKind::Skim,
+            #[cfg(feature = "nucleo")]
+            MatcherKind::Nucleo,
+        ]
+    }
+
+    #[test]
+    fn backends_report_char_indices() {
+        for kind in backends() {
+            let mut matcher = Matcher::new(kind);
+            let (_, indices) = matcher.fuzzy_indices("Terminal", "trm").unwrap();
+            assert_eq!(indices, vec![0, 2, 3], "{:?}", kind);
+            let (_, indices) = matcher.fuzzy_indices("Lautstärke", "rke").unwrap();
+            assert_eq!(indices, vec![7, 8, 9], "{:?}", kind);
+            assert_eq!(matcher.fuzzy_indices("Terminal", "xyz"), None, "{:?}", kind);
+            assert_eq!(matcher.fuzzy_indices("Terminal", "TRM"), None, "{:?}", kind);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Following i3 binding mode changes and config reloads over IPC
+
+use crate::i3_config::I3ConfigError;
+use futures::stream::{BoxStream, LocalBoxStream};
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+use futures::StreamExt;
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+use tokio_i3ipc::{
+    event::{Event, ShutdownChange, Subscribe, WorkspaceChange},
+    I3,
+};
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigChange {
+    /// i3 read its config again, entries loaded over IPC may be outdated
+    Reloaded,
+    /// i3 restarts in place, the connection closes and the config may change
+    Restarting,
+    Exited,
+}
+
+/// Names of the binding modes i3 switches to, `default` when a mode is left
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+pub async fn mode_changes() -> Result<LocalBoxStream<'static, Result<String>>> {
+    let mut i3 = I3::connect().await.map_err(I3ConfigError::ipc)?;
+    i3.subscribe([Subscribe::Mode])
+        .await
+        .map_err(I3ConfigError::ipc)?;
+    Ok(i3
+        .listen()
+        .filter_map(|event| async move {
+            match event {
+                Ok(Event::Mode(mode)) => Some(Ok(mode.change)),
+                Ok(_) => None,
+                Err(error) => Some(Err(I3ConfigError::ipc(error))),
+            }
+        })
+        .boxed_local())
+}
+
+#[cfg(not(all(target_family = "unix", feature = "ipc")))]
+pub async fn mode_changes() -> Result<LocalBoxStream<'static, Result<String>>> {
+    Err(I3ConfigError::ipc_unavailable())
+}
+
+/// Reloads, restarts and exits of i3. The stream ends with the connection
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+pub async fn config_changes() -> Result<BoxStream<'static, Result<ConfigChange>>> {
+    let mut i3 = I3::connect().await.map_err(I3ConfigError::ipc)?;
+    // i3 announces reloads with a workspace event
+    i3.subscribe([Subscribe::Workspace, Subscribe::Shutdown])
+        .await
+        .map_err(I3ConfigError::ipc)?;
+    Ok(i3
+        .listen()
+        .filter_map(|event| async move {
+            match event {
+                Ok(Event::Workspace(workspace)) if workspace.change == WorkspaceChange::Reload => {
+                    Some(Ok(ConfigChange::Reloaded))
+                }
+                Ok(Event::Shutdown(shutdown)) => Some(Ok(match shutdown.change {
+                    ShutdownChange::Restart => ConfigChange::Restarting,
+                    ShutdownChange::Exit => ConfigChange::Exited,
+                })),
+                Ok(_) => None,
+                Err(error) => Some(Err(I3ConfigError::ipc(error))),
+            }
+        })
+        .boxed())
+}
+
+#[cfg(not(all(target_family = "unix", feature = "ipc")))]
+pub async fn config_changes() -> Result<BoxStream<'static, Result<ConfigChange>>> {
+    Err(I3ConfigError::ipc_unavailable())
+}
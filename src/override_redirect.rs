@@ -0,0 +1,141 @@
+//! Taking the searcher window away from the window manager like dmenu does,
+//! so it floats centered above everything without `for_window` rules
+
+use i3_conf_searcher::i3_config::I3ConfigError;
+use i3_conf_searcher::preferences::WindowPosition;
+use std::time::Duration;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    AtomEnum, ChangeWindowAttributesAux, ConfigureWindowAux, ConnectionExt, InputFocus, StackMode,
+    Window,
+};
+use x11rb::rust_connection::RustConnection;
+use x11rb::CURRENT_TIME;
+
+/// The window may not be mapped yet and i3 hands it back asynchronously after the unmap
+const ATTEMPTS: usize = 100;
+const RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// Unmaps the searcher so the window manager lets go of it,
+/// then maps it again at the position with override-redirect set, centered on the screen by default
+pub fn apply(position: Option<WindowPosition>) -> Result<(), I3ConfigError> {
+    let (connection, screen) = RustConnection::connect(None).map_err(x11_error)?;
+    let screen = &connection.setup().roots[screen];
+    let root = screen.root;
+    let window = retry(|| own_window(&connection, root))?;
+
+    connection.unmap_window(window).map_err(x11_error)?;
+    connection.flush().map_err(x11_error)?;
+    // i3 reparents the window back to the root once it stops managing it
+    retry(|| Ok(Some(parent(&connection, window)?).filter(|parent| *parent == root)))?;
+
+    connection
+        .change_window_attributes(
+            window,
+            &ChangeWindowAttributesAux::new().override_redirect(1),
+        )
+        .map_err(x11_error)?;
+    let geometry = connection
+        .get_geometry(window)
+        .map_err(x11_error)?
+        .reply()
+        .map_err(x11_error)?;
+    let (width, height) = (i32::from(geometry.width), i32::from(geometry.height));
+    let (screen_width, screen_height) = (
+        i32::from(screen.width_in_pixels),
+        i32::from(screen.height_in_pixels),
+    );
+    let (x, y) = match position.unwrap_or(WindowPosition::Center) {
+        WindowPosition::Center => ((screen_width - width) / 2, (screen_height - height) / 2),
+        WindowPosition::Mouse => {
+            let pointer = connection
+                .query_pointer(root)
+                .map_err(x11_error)?
+                .reply()
+                .map_err(x11_error)?;
+            (
+                i32::from(pointer.root_x) - width / 2,
+                i32::from(pointer.root_y) - height / 2,
+            )
+        }
+        WindowPosition::At([x, y]) => (x, y),
+    };
+    // Kept on the screen when the pointer is close to an edge
+    connection
+        .configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(x.min(screen_width - width).max(0))
+                .y(y.min(screen_height - height).max(0))
+                .stack_mode(StackMode::ABOVE),
+        )
+        .map_err(x11_error)?;
+    connection.map_window(window).map_err(x11_error)?;
+    // Nothing hands focus to windows the window manager doesn't know about
+    retry(|| {
+        let focused = connection
+            .set_input_focus(InputFocus::PARENT, window, CURRENT_TIME)
+            .map_err(x11_error)?
+            .check()
+            .is_ok();
+        Ok(Some(()).filter(|_| focused))
+    })?;
+    connection.flush().map_err(x11_error)
+}
+
+/// Top level window with our pid in `_NET_WM_PID`, set by winit
+fn own_window(connection: &RustConnection, root: Window) -> Result<Option<Window>, I3ConfigError> {
+    let pid_atom = connection
+        .intern_atom(false, b"_NET_WM_PID")
+        .map_err(x11_error)?
+        .reply()
+        .map_err(x11_error)?
+        .atom;
+    let mut pending = vec![root];
+    while let Some(window) = pending.pop() {
+        let pid = connection
+            .get_property(false, window, pid_atom, AtomEnum::CARDINAL, 0, 1)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?
+            .value32()
+            .and_then(|mut values| values.next());
+        if pid == Some(std::process::id()) {
+            return Ok(Some(window));
+        }
+        let tree = connection
+            .query_tree(window)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+        pending.extend(tree.children);
+    }
+    Ok(None)
+}
+
+fn parent(connection: &RustConnection, window: Window) -> Result<Window, I3ConfigError> {
+    Ok(connection
+        .query_tree(window)
+        .map_err(x11_error)?
+        .reply()
+        .map_err(x11_error)?
+        .parent)
+}
+
+fn retry<T>(
+    mut attempt: impl FnMut() -> Result<Option<T>, I3ConfigError>,
+) -> Result<T, I3ConfigError> {
+    for _ in 0..ATTEMPTS {
+        if let Some(value) = attempt()? {
+            return Ok(value);
+        }
+        std::thread::sleep(RETRY_DELAY);
+    }
+    Err(I3ConfigError::FailedOverrideRedirect(String::from(
+        "searcher window didn't show up",
+    )))
+}
+
+fn x11_error(error: impl std::fmt::Display) -> I3ConfigError {
+    I3ConfigError::FailedOverrideRedirect(error.to_string())
+}
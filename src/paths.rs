@@ -0,0 +1,123 @@
+//! Locations of the files the searcher reads and writes, following the XDG base directories
+//! on Linux and the platform conventions elsewhere, and resolution of user supplied paths and urls
+
+use crate::i3_config::I3ConfigError;
+use directories::{BaseDirs, ProjectDirs};
+use regex::{Captures, Regex};
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+const APP_DIR: &str = "i3-conf-searcher";
+
+/// `$XDG_CONFIG_HOME`, defaulting to `~/.config`, where i3 looks for its config on Linux
+pub fn xdg_config_home() -> Option<PathBuf> {
+    BaseDirs::new().map(|dirs| dirs.config_dir().to_owned())
+}
+
+/// Preferences and translations
+pub fn config_dir() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.config_dir().to_owned())
+}
+
+/// Git checkouts
+pub fn cache_dir() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.cache_dir().to_owned())
+}
+
+/// Config backups and usage stats
+pub fn data_dir() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.data_dir().to_owned())
+}
+
+/// `None` without a home directory
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", APP_DIR)
+}
+
+/// `$XDG_RUNTIME_DIR` for the instance lock, falling back to the temp directory
+pub fn runtime_dir() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// Every location the searcher uses, directories are created when first written to
+pub fn locations() -> Vec<(&'static str, Option<PathBuf>)> {
+    let in_dir = |dir: Option<PathBuf>, name: &str| dir.map(|dir| dir.join(name));
+    vec![
+        ("preferences", in_dir(config_dir(), "config.toml")),
+        ("translations", in_dir(config_dir(), "locales")),
+        ("git checkouts", in_dir(cache_dir(), "git")),
+        ("config backups", in_dir(data_dir(), "backups")),
+        ("usage stats", in_dir(data_dir(), "usage.json")),
+        ("window geometry", in_dir(data_dir(), "geometry.json")),
+        ("instance lock", Some(crate::instance::lock_path())),
+        ("control socket", Some(crate::control::socket_path())),
+        ("i3 config", crate::i3_config::default_config_path()),
+    ]
+}
+
+/// Expands a leading `~` and `$VAR` or `${VAR}` anywhere in the text.
+/// Unset variables are an error rather than silently becoming empty
+pub fn expand(text: &str) -> Result<String> {
+    let text = match text.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("$HOME{}", rest),
+        _ => text.to_owned(),
+    };
+    let variable_re = Regex::new(r"\$(?:\{(?P<braced>\w+)\}|(?P<name>\w+))").unwrap();
+    let mut unset = None;
+    let expanded = variable_re.replace_all(&text, |captures: &Captures| {
+        let name = captures
+            .name("braced")
+            .or_else(|| captures.name("name"))
+            .map(|name| name.as_str())
+            .unwrap_or_default();
+        std::env::var(name).unwrap_or_else(|_| {
+            unset.get_or_insert_with(|| name.to_owned());
+            String::new()
+        })
+    });
+    match unset {
+        Some(name) => Err(I3ConfigError::UnsetVariable(name)),
+        None => Ok(expanded.into_owned()),
+    }
+}
+
+/// [`expand`] for paths, which have to be valid UTF-8 to contain variables
+pub fn expand_path(path: &Path) -> Result<PathBuf> {
+    match path.to_str() {
+        Some(text) => expand(text).map(PathBuf::from),
+        None => Ok(path.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_home_and_variables() {
+        std::env::set_var("I3_CONF_SEARCHER_TEST_DIR", "dotfiles");
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(
+            expand("~/.i3/config").unwrap(),
+            format!("{}/.i3/config", home)
+        );
+        assert_eq!(expand("~").unwrap(), home);
+        assert_eq!(expand("~other/config").unwrap(), "~other/config");
+        assert_eq!(
+            expand("https://example.com/${I3_CONF_SEARCHER_TEST_DIR}/i3").unwrap(),
+            "https://example.com/dotfiles/i3"
+        );
+        assert_eq!(
+            expand_path(Path::new("/home/$I3_CONF_SEARCHER_TEST_DIR/config")).unwrap(),
+            PathBuf::from("/home/dotfiles/config")
+        );
+        assert_eq!(expand("no variables").unwrap(), "no variables");
+        assert!(matches!(
+            expand("$I3_CONF_SEARCHER_TEST_UNSET/config"),
+            Err(I3ConfigError::UnsetVariable(name)) if name == "I3_CONF_SEARCHER_TEST_UNSET"
+        ));
+    }
+}
@@ -0,0 +1,279 @@
+//! User preferences read from `$XDG_CONFIG_HOME/i3-conf-searcher/config.toml`
+
+use crate::i3_config::{EntryOrder, I3ConfigError, MinScore, SearchText};
+use crate::matcher::MatcherKind;
+use crate::paths;
+use crate::providers::Provider;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Preferences {
+    /// Hide entries that need modifiers which aren't held
+    pub strict_modifiers: bool,
+    /// Order of entries that match equally well
+    pub order: EntryOrder,
+    /// Drop fuzzy matches scoring lower than this
+    pub min_score: Option<MinScore>,
+    /// Fuzzy matching backend, overridden by --matcher
+    pub matcher: MatcherKind,
+    /// Fields query words are matched against with their weights
+    pub search_fields: SearchText,
+    /// Show the group sidebar when the searcher opens
+    pub group_sidebar: bool,
+    /// Size of the search input and entry rows
+    pub density: Density,
+    /// Show entries running the same command as one row listing all their keys
+    pub collapse_duplicates: bool,
+    /// How long the searcher waits for focus to come back before closing
+    pub focus_loss_grace_ms: Option<u64>,
+    /// Show a desktop notification when a binding run from the window fails, defaults to true
+    pub notifications: Option<bool>,
+    /// Record the bindings run from the searcher for `stats --usage`
+    pub usage_stats: bool,
+    /// Stay in the mode of a binding after running it instead of returning to the default mode
+    pub stay_in_mode: bool,
+    /// Key combination like `Mod4+slash` that `which-key` grabs to toggle the searcher
+    pub hotkey: Option<String>,
+    /// Terminal fuzzy finder used by --pick with its arguments, defaults to fzf
+    pub picker: Option<String>,
+    /// Show a tray icon while `which-key` runs, needs the tray feature
+    pub tray: bool,
+    /// Search config lines besides the annotated bindings, like `window-rules`
+    pub providers: Vec<Provider>,
+    /// How configs passed with --url are downloaded
+    pub remote: Remote,
+    /// Behavior of the searcher window independent of window manager rules
+    pub window: WindowPreferences,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct WindowPreferences {
+    /// Title bar and borders drawn by the window manager
+    pub decorations: bool,
+    pub resizable: bool,
+    pub always_on_top: bool,
+    /// Where i3 moves the window once it opened, only applies to floating windows
+    pub position: Option<WindowPosition>,
+}
+
+impl Default for WindowPreferences {
+    fn default() -> Self {
+        WindowPreferences {
+            decorations: true,
+            resizable: true,
+            always_on_top: false,
+            position: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindowPosition {
+    Center,
+    /// Centered on the mouse pointer
+    Mouse,
+    /// Top left corner in pixels
+    At([i32; 2]),
+}
+
+impl WindowPosition {
+    /// Arguments of the i3 `move position` command
+    pub fn i3_arguments(self) -> String {
+        match self {
+            WindowPosition::Center => String::from("center"),
+            WindowPosition::Mouse => String::from("mouse"),
+            WindowPosition::At([x, y]) => format!("{} px {} px", x, y),
+        }
+    }
+}
+
+/// Presets for the padding and font sizes of the searcher, Ctrl+D cycles through them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Density {
+    Compact,
+    #[default]
+    Comfortable,
+    Spacious,
+}
+
+impl Density {
+    pub fn next(self) -> Density {
+        match self {
+            Density::Compact => Density::Comfortable,
+            Density::Comfortable => Density::Spacious,
+            Density::Spacious => Density::Compact,
+        }
+    }
+}
+
+/// Authentication and verification of downloaded configs
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Hash)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Remote {
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub bearer_token: Option<String>,
+    /// Extra headers sent with the request
+    pub headers: BTreeMap<String, String>,
+    /// Expected hex encoded SHA-256 of the config
+    pub sha256: Option<String>,
+}
+
+impl Remote {
+    /// Credentials from `I3_CONF_SEARCHER_USERNAME`, `I3_CONF_SEARCHER_PASSWORD`
+    /// and `I3_CONF_SEARCHER_TOKEN` override the ones from the file
+    pub fn with_env(self) -> Remote {
+        let var = |name| std::env::var(name).ok();
+        Remote {
+            username: var("I3_CONF_SEARCHER_USERNAME").or(self.username),
+            password: var("I3_CONF_SEARCHER_PASSWORD").or(self.password),
+            bearer_token: var("I3_CONF_SEARCHER_TOKEN").or(self.bearer_token),
+            ..self
+        }
+    }
+}
+
+const DEFAULT_FOCUS_LOSS_GRACE: Duration = Duration::from_millis(300);
+
+impl Preferences {
+    pub fn focus_loss_grace(&self) -> Duration {
+        self.focus_loss_grace_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_FOCUS_LOSS_GRACE)
+    }
+
+    pub fn notify_failures(&self) -> bool {
+        self.notifications.unwrap_or(true)
+    }
+
+    /// Reads the preferences file, a missing file means default preferences
+    pub fn load() -> Result<Preferences> {
+        let path = match preferences_path() {
+            Some(path) => path,
+            None => return Ok(Preferences::default()),
+        };
+        match fs::read_to_string(path) {
+            Ok(text) => Preferences::parse(&text),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Preferences::default()),
+            Err(error) => Err(I3ConfigError::FailedPreferencesRead(Arc::new(error))),
+        }
+    }
+
+    fn parse(text: &str) -> Result<Preferences> {
+        toml::from_str(text).map_err(|error| I3ConfigError::InvalidPreferences(Arc::new(error)))
+    }
+}
+
+fn preferences_path() -> Option<PathBuf> {
+    paths::config_dir().map(|config| config.join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i3_config::SearchField;
+
+    #[test]
+    fn parse_preferences() {
+        assert_eq!(Preferences::parse("").unwrap(), Preferences::default());
+        assert!(
+            Preferences::parse("strict-modifiers = true")
+                .unwrap()
+                .strict_modifiers
+        );
+        assert_eq!(
+            Preferences::default().focus_loss_grace(),
+            DEFAULT_FOCUS_LOSS_GRACE
+        );
+        assert_eq!(
+            Preferences::parse("focus-loss-grace-ms = 0")
+                .unwrap()
+                .focus_loss_grace(),
+            Duration::ZERO
+        );
+        assert!(Preferences::default().notify_failures());
+        assert!(!Preferences::parse("notifications = false")
+            .unwrap()
+            .notify_failures());
+        assert_eq!(
+            Preferences::parse("order = \"file\"").unwrap().order,
+            EntryOrder::File
+        );
+        assert_eq!(
+            Preferences::parse("min-score = { relative = 0.5 }")
+                .unwrap()
+                .min_score,
+            Some(MinScore::Relative(0.5))
+        );
+        assert_eq!(
+            Preferences::parse(
+                "search-fields = [{ field = \"description\", weight = 2.0 }, { field = \"group\" }]"
+            )
+            .unwrap()
+            .search_fields,
+            SearchText::new()
+                .field(SearchField::Description, 2.0)
+                .field(SearchField::Group, 1.0)
+        );
+        assert_eq!(
+            Preferences::parse("density = \"compact\"").unwrap().density,
+            Density::Compact
+        );
+        assert_eq!(
+            Preferences::parse("providers = [\"window-rules\"]")
+                .unwrap()
+                .providers,
+            vec![Provider::WindowRules]
+        );
+        assert!(matches!(
+            Preferences::parse("strict = true"),
+            Err(I3ConfigError::InvalidPreferences(_))
+        ));
+    }
+
+    #[test]
+    fn parse_window_preferences() {
+        assert_eq!(
+            Preferences::parse("").unwrap().window,
+            WindowPreferences::default()
+        );
+        let window = Preferences::parse(
+            "[window]\ndecorations = false\nalways-on-top = true\nposition = { at = [10, 20] }",
+        )
+        .unwrap()
+        .window;
+        assert!(!window.decorations && window.resizable && window.always_on_top);
+        assert_eq!(window.position.unwrap().i3_arguments(), "10 px 20 px");
+        assert_eq!(
+            Preferences::parse("[window]\nposition = \"mouse\"")
+                .unwrap()
+                .window
+                .position,
+            Some(WindowPosition::Mouse)
+        );
+    }
+
+    #[test]
+    fn parse_remote_preferences() {
+        let preferences = Preferences::parse(
+            "[remote]\nbearer-token = \"secret\"\nsha256 = \"abc\"\n\n[remote.headers]\nX-Api-Key = \"key\"",
+        )
+        .unwrap();
+        assert_eq!(preferences.remote.bearer_token.as_deref(), Some("secret"));
+        assert_eq!(preferences.remote.sha256.as_deref(), Some("abc"));
+        assert_eq!(preferences.remote.headers["X-Api-Key"], "key");
+        assert_eq!(preferences.remote.username, None);
+    }
+}
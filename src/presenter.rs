@@ -0,0 +1,332 @@
+//! Plain text presentation of config entries for terminal output
+
+use crate::doctor::{Check, Status};
+use crate::i3_config::{self, ConfigEntry, I3ConfigError};
+use crate::usage::UsageStats;
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Formats groups and their entry counts, one group per line.
+/// Subgroups of `parent/child` groups are listed below their parent
+pub fn groups_table(groups: &[(&str, usize)]) -> String {
+    let mut tree: Vec<GroupNode> = vec![];
+    for (group, count) in groups {
+        GroupNode::insert(&mut tree, &i3_config::group_path(group), *count);
+    }
+    let mut rows = vec![];
+    GroupNode::rows(&tree, 0, &mut rows);
+    aligned_columns(&rows)
+}
+
+/// Group of `parent/child` groups, counting the entries of its subgroups too
+struct GroupNode<'a> {
+    name: &'a str,
+    count: usize,
+    children: Vec<GroupNode<'a>>,
+}
+
+impl<'a> GroupNode<'a> {
+    fn insert(nodes: &mut Vec<GroupNode<'a>>, path: &[&'a str], count: usize) {
+        let (name, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+        let index = match nodes.iter().position(|node| node.name == *name) {
+            Some(index) => index,
+            None => {
+                nodes.push(GroupNode {
+                    name,
+                    count: 0,
+                    children: vec![],
+                });
+                nodes.len() - 1
+            }
+        };
+        nodes[index].count += count;
+        GroupNode::insert(&mut nodes[index].children, rest, count);
+    }
+
+    /// Subgroups are indented below their parent
+    fn rows(nodes: &[GroupNode], depth: usize, rows: &mut Vec<Vec<String>>) {
+        for node in nodes {
+            rows.push(vec![
+                format!("{}{}", "  ".repeat(depth), node.name),
+                node.count.to_string(),
+            ]);
+            GroupNode::rows(&node.children, depth + 1, rows);
+        }
+    }
+}
+
+/// Formats entries as group, description and keys columns
+pub fn entries_table(entries: &[&ConfigEntry]) -> String {
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|entry| {
+            vec![
+                entry.group().to_owned(),
+                entry.description().to_owned(),
+                entry.keys().to_owned(),
+            ]
+        })
+        .collect();
+    aligned_columns(&rows)
+}
+
+/// Formats entries like [`entries_table`] with their index and a tab in front,
+/// the lines are piped into terminal pickers like fzf
+pub fn picker_lines(entries: &[&ConfigEntry]) -> String {
+    entries_table(entries)
+        .lines()
+        .enumerate()
+        .map(|(index, line)| format!("{}\t{}\n", index, line))
+        .collect()
+}
+
+/// Formats entries with how often they were run and how long finding them took,
+/// most used first and never used ones last
+pub fn usage_table(entries: &[&ConfigEntry], stats: &UsageStats) -> String {
+    let mut entries = entries.to_vec();
+    entries.sort_by_key(|entry| std::cmp::Reverse(stats.get(entry).map(|usage| usage.runs)));
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|entry| {
+            let (runs, selection) = match stats.get(entry) {
+                Some(usage) => (
+                    usage.runs.to_string(),
+                    format!("{:.1}s", usage.average_selection_time().as_secs_f32()),
+                ),
+                None => (String::from("0"), String::from("-")),
+            };
+            vec![
+                runs,
+                selection,
+                entry.group().to_owned(),
+                entry.description().to_owned(),
+            ]
+        })
+        .collect();
+    aligned_columns(&rows)
+}
+
+/// Formats named locations, ones that can't be determined are shown as unknown
+pub fn paths_table(locations: &[(&str, Option<PathBuf>)]) -> String {
+    let rows: Vec<Vec<String>> = locations
+        .iter()
+        .map(|(name, path)| {
+            let path = match path {
+                Some(path) => path.display().to_string(),
+                None => String::from("unknown"),
+            };
+            vec![name.to_string(), path]
+        })
+        .collect();
+    aligned_columns(&rows)
+}
+
+/// Formats entries as a single line JSON array of objects with group, description and keys
+pub fn entries_json(entries: &[&ConfigEntry]) -> String {
+    serde_json::Value::from(
+        entries
+            .iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "group": entry.group(),
+                    "description": entry.description(),
+                    "keys": entry.keys(),
+                })
+            })
+            .collect::<Vec<_>>(),
+    )
+    .to_string()
+}
+
+/// Formats an error followed by all of its sources
+pub fn error_chain(error: &dyn Error) -> String {
+    let mut chain = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        chain.push_str(": ");
+        chain.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    chain
+}
+
+/// Formats an error as a single line JSON object
+pub fn error_json(error: &I3ConfigError) -> String {
+    serde_json::json!({
+        "error": error.code(),
+        "message": error_chain(error),
+        "exit_code": error.exit_code(),
+    })
+    .to_string()
+}
+
+/// Formats the doctor checks with their status, colored for terminals
+pub fn doctor_report(checks: &[Check], color: bool) -> String {
+    let rows: Vec<Vec<String>> = checks
+        .iter()
+        .map(|check| {
+            let (label, code) = match check.status {
+                Status::Ok => ("[ ok ]", "32"),
+                Status::Warning => ("[warn]", "33"),
+                Status::Failed => ("[fail]", "31"),
+            };
+            let status = if color {
+                format!("\x1b[{}m{}\x1b[0m", code, label)
+            } else {
+                label.to_owned()
+            };
+            vec![status, check.name.to_owned(), check.detail.clone()]
+        })
+        .collect();
+    aligned_columns(&rows)
+}
+
+const COLUMN_GAP: usize = 2;
+
+fn aligned_columns(rows: &[Vec<String>]) -> String {
+    let column_count = rows.iter().map(|row| row.len()).max().unwrap_or_default();
+    let widths: Vec<usize> = (0..column_count)
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let mut output = String::new();
+    for row in rows {
+        let mut line = String::new();
+        for (column, cell) in row.iter().enumerate() {
+            line.push_str(cell);
+            if column + 1 < row.len() {
+                let padding = widths[column] - cell.chars().count() + COLUMN_GAP;
+                line.push_str(&" ".repeat(padding));
+            }
+        }
+        output.push_str(&line);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_are_aligned() {
+        let first = ConfigEntry::new(
+            String::from("media"),
+            String::from("play"),
+            String::from("<> p"),
+        );
+        let second = ConfigEntry::new(
+            String::from("launch"),
+            String::from("terminal"),
+            String::from("<> enter"),
+        );
+        let table = entries_table(&[&first, &second]);
+        assert_eq!(
+            table,
+            "media   play      <> p\nlaunch  terminal  <> enter\n"
+        );
+    }
+
+    #[test]
+    fn picker_lines_start_with_index() {
+        let first = ConfigEntry::new(
+            String::from("media"),
+            String::from("play"),
+            String::from("<> p"),
+        );
+        let second = ConfigEntry::new(
+            String::from("launch"),
+            String::from("terminal"),
+            String::from("<> enter"),
+        );
+        assert_eq!(
+            picker_lines(&[&first, &second]),
+            "0\tmedia   play      <> p\n1\tlaunch  terminal  <> enter\n"
+        );
+    }
+
+    #[test]
+    fn groups_with_counts() {
+        let table = groups_table(&[("media", 12), ("launch", 3)]);
+        assert_eq!(table, "media   12\nlaunch  3\n");
+    }
+
+    #[test]
+    fn nested_groups_are_indented() {
+        let table = groups_table(&[
+            ("Launch/Browsers", 2),
+            ("media", 12),
+            ("Launch/Terminals", 1),
+            ("Launch", 1),
+        ]);
+        assert_eq!(
+            table,
+            "Launch       4\n  Browsers   2\n  Terminals  1\nmedia        12\n"
+        );
+    }
+
+    #[test]
+    fn doctor_report_columns() {
+        let checks = [
+            Check {
+                name: "config",
+                status: Status::Ok,
+                detail: String::from("loaded from i3 IPC"),
+            },
+            Check {
+                name: "font",
+                status: Status::Failed,
+                detail: String::from("broken"),
+            },
+        ];
+        assert_eq!(
+            doctor_report(&checks, false),
+            "[ ok ]  config  loaded from i3 IPC\n[fail]  font    broken\n"
+        );
+        assert!(doctor_report(&checks, true).starts_with("\x1b[32m[ ok ]\x1b[0m"));
+    }
+
+    #[test]
+    fn error_as_json() {
+        let error = I3ConfigError::NoMatchingEntry(String::from("abc"));
+        let json: serde_json::Value = serde_json::from_str(&error_json(&error)).unwrap();
+        assert_eq!(json["error"], "no_match");
+        assert_eq!(json["exit_code"], 6);
+        assert_eq!(json["message"], "no entry matches \"abc\"");
+    }
+
+    #[test]
+    fn entries_as_json() {
+        let entry = ConfigEntry::new(
+            String::from("media"),
+            String::from("play"),
+            String::from("<> p"),
+        );
+        assert_eq!(
+            entries_json(&[&entry]),
+            r#"[{"description":"play","group":"media","keys":"<> p"}]"#
+        );
+        assert_eq!(entries_json(&[]), "[]");
+    }
+
+    #[test]
+    fn error_with_sources() {
+        let error = I3ConfigError::FailedI3Query(std::sync::Arc::new(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "socket not found",
+        )));
+        assert_eq!(
+            error_chain(&error),
+            "failed to communicate with i3: socket not found"
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Explaining what a bound command would do in the running i3 without running it
+
+use crate::i3_command::{self, TokenKind};
+use crate::i3_config::I3ConfigError;
+use regex::Regex;
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+use tokio_i3ipc::{reply::Node, reply::NodeType, I3};
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+const SEARCHER_CLASS: &str = "i3-conf-searcher";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Window {
+    pub class: String,
+    pub instance: String,
+    pub title: String,
+    pub workspace: String,
+}
+
+/// What a single action of a command does, worded by the searcher in the language of the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum Effect {
+    /// Program started by `exec`, without its options
+    Starts(String),
+    /// `from` is `None` if the current workspace isn't known
+    SwitchesWorkspace {
+        from: Option<String>,
+        to: String,
+    },
+    MovesToScratchpad(Subject),
+    /// The target is worded like in the command, e.g. `to workspace 2`
+    Moves(Subject, String),
+    Closes(Subject),
+    Focuses(Subject),
+    /// Direction like `left`
+    MovesFocus(String),
+    EntersMode(String),
+    /// Commands like `floating toggle`, verb and arguments as written
+    Changes(String, Subject),
+    /// Anything not explained, with the whole action
+    Runs(String),
+}
+
+/// Windows an action applies to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Subject {
+    /// Title of the focused window or the only one matching the criteria
+    Window(String),
+    /// No window is known to be focused
+    FocusedWindow,
+    NoWindow {
+        criteria: String,
+    },
+    Windows {
+        count: usize,
+        criteria: String,
+    },
+}
+
+/// Windows of the running i3 without the searcher itself
+#[derive(Debug, Clone, Default)]
+pub struct Scene {
+    /// Window focused before the searcher opened
+    pub focused: Option<Window>,
+    pub workspace: Option<String>,
+    pub windows: Vec<Window>,
+}
+
+impl Scene {
+    /// One effect per chained action, like moving `"Firefox"` to workspace 2 and then switching
+    /// from workspace 1 to 2. Only criteria on class, instance, title and workspace are matched
+    /// against windows
+    pub fn explain(&self, command: &str) -> Vec<Effect> {
+        i3_command::actions(command)
+            .into_iter()
+            .map(|action| self.explain_action(action))
+            .collect()
+    }
+
+    fn explain_action(&self, action: &str) -> Effect {
+        let tokens: Vec<_> = i3_command::tokenize(action)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .collect();
+        let criteria = tokens
+            .iter()
+            .find(|token| token.kind == TokenKind::Criteria)
+            .map(|token| token.text);
+        let verb = match tokens.iter().find(|token| token.kind == TokenKind::Verb) {
+            Some(verb) => verb.text,
+            None => return Effect::Runs(action.to_owned()),
+        };
+        let rest = tokens
+            .iter()
+            .filter(|token| token.kind == TokenKind::Argument)
+            .map(|token| token.text.trim_matches('"'))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let subject = match criteria {
+            Some(criteria) => self.matching(criteria),
+            None => match &self.focused {
+                Some(window) => Subject::Window(window.title.clone()),
+                None => Subject::FocusedWindow,
+            },
+        };
+        match verb {
+            "exec" => Effect::Starts(
+                rest.split(' ')
+                    .filter(|word| !word.starts_with("--"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            "workspace" => Effect::SwitchesWorkspace {
+                from: self.workspace.clone(),
+                to: rest.trim_start_matches("number ").to_owned(),
+            },
+            "move" => {
+                let target = rest
+                    .trim_start_matches("window ")
+                    .trim_start_matches("container ");
+                match target {
+                    "scratchpad" => Effect::MovesToScratchpad(subject),
+                    target => Effect::Moves(subject, target.to_owned()),
+                }
+            }
+            "kill" => Effect::Closes(subject),
+            "focus" if criteria.is_some() || rest.is_empty() => Effect::Focuses(subject),
+            "focus" => Effect::MovesFocus(rest),
+            "mode" => Effect::EntersMode(rest),
+            "floating" | "fullscreen" | "sticky" | "border" | "resize" | "split" | "layout" => {
+                Effect::Changes(format!("{} {}", verb, rest), subject)
+            }
+            _ => Effect::Runs(action.to_owned()),
+        }
+    }
+
+    /// The window for a single match, otherwise how many windows match
+    fn matching(&self, criteria: &str) -> Subject {
+        let criteria_re = Regex::new(r#"(\w+)\s*=\s*"((?:[^"\\]|\\.)*)""#).unwrap();
+        let mut filters = vec![];
+        for captures in criteria_re.captures_iter(criteria) {
+            let field: fn(&Window) -> &str = match &captures[1] {
+                "class" => |window| &window.class,
+                "instance" => |window| &window.instance,
+                "title" => |window| &window.title,
+                "workspace" => |window| &window.workspace,
+                _ => continue,
+            };
+            // i3 uses PCRE, patterns the regex crate doesn't support match nothing
+            let pattern = Regex::new(&captures[2]).ok();
+            filters.push((field, pattern));
+        }
+        let matches: Vec<&Window> = self
+            .windows
+            .iter()
+            .filter(|window| {
+                filters.iter().all(|(field, pattern)| {
+                    pattern
+                        .as_ref()
+                        .is_some_and(|pattern| pattern.is_match(field(window)))
+                })
+            })
+            .collect();
+        let criteria = criteria.to_owned();
+        match matches.as_slice() {
+            [] => Subject::NoWindow { criteria },
+            [window] => Subject::Window(window.title.clone()),
+            windows => Subject::Windows {
+                count: windows.len(),
+                criteria,
+            },
+        }
+    }
+}
+
+/// Explains the command with the windows i3 currently manages
+pub async fn preview(command: String) -> Result<Vec<Effect>> {
+    Ok(scene().await?.explain(&command))
+}
+
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+pub async fn scene() -> Result<Scene> {
+    let mut i3 = I3::connect().await.map_err(I3ConfigError::ipc)?;
+    let tree = i3.get_tree().await.map_err(I3ConfigError::ipc)?;
+    let mut windows = vec![];
+    collect_windows(&tree, "", &mut windows);
+    // The searcher has focus, so its workspace is the focused one
+    let workspace = windows.first().map(|window| window.workspace.clone());
+    windows.retain(|window| window.class != SEARCHER_CLASS);
+    Ok(Scene {
+        focused: windows.first().cloned(),
+        workspace,
+        windows,
+    })
+}
+
+#[cfg(not(all(target_family = "unix", feature = "ipc")))]
+pub async fn scene() -> Result<Scene> {
+    Err(I3ConfigError::ipc_unavailable())
+}
+
+/// Windows under `node`, most recently focused first
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+fn collect_windows(node: &Node, workspace: &str, windows: &mut Vec<Window>) {
+    let workspace = match (&node.node_type, &node.name) {
+        (NodeType::Workspace, Some(name)) => name.as_str(),
+        _ => workspace,
+    };
+    if let Some(properties) = &node.window_properties {
+        let text = |value: &Option<String>| value.clone().unwrap_or_default();
+        windows.push(Window {
+            class: text(&properties.class),
+            instance: text(&properties.instance),
+            title: text(&properties.title),
+            workspace: workspace.to_owned(),
+        });
+    }
+    let mut children: Vec<&Node> = node.nodes.iter().chain(&node.floating_nodes).collect();
+    children.sort_by_key(|child| {
+        node.focus
+            .iter()
+            .position(|id| *id == child.id)
+            .unwrap_or(usize::MAX)
+    });
+    for child in children {
+        collect_windows(child, workspace, windows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(class: &str, title: &str, workspace: &str) -> Window {
+        Window {
+            class: class.to_owned(),
+            instance: class.to_lowercase(),
+            title: title.to_owned(),
+            workspace: workspace.to_owned(),
+        }
+    }
+
+    #[test]
+    fn explain_commands() {
+        let firefox = window("Firefox", "Mozilla Firefox", "1");
+        let scene = Scene {
+            focused: Some(firefox.clone()),
+            workspace: Some(String::from("1")),
+            windows: vec![
+                firefox,
+                window("Alacritty", "htop", "2"),
+                window("Alacritty", "vim", "2"),
+            ],
+        };
+        let text = String::from;
+        assert_eq!(
+            scene.explain("move container to workspace 2; workspace 2"),
+            vec![
+                Effect::Moves(
+                    Subject::Window(text("Mozilla Firefox")),
+                    text("to workspace 2")
+                ),
+                Effect::SwitchesWorkspace {
+                    from: Some(text("1")),
+                    to: text("2")
+                }
+            ]
+        );
+        assert_eq!(
+            scene.explain(r#"[class="Alacritty"] kill"#),
+            vec![Effect::Closes(Subject::Windows {
+                count: 2,
+                criteria: text(r#"[class="Alacritty"]"#)
+            })]
+        );
+        assert_eq!(
+            scene.explain(r#"[class="^Alac" title="vim"] focus"#),
+            vec![Effect::Focuses(Subject::Window(text("vim")))]
+        );
+        assert_eq!(
+            scene.explain(r#"[class="Spotify"] scratchpad show"#),
+            vec![Effect::Runs(text(r#"[class="Spotify"] scratchpad show"#))]
+        );
+        assert_eq!(
+            scene.explain(r#"[class="Spotify"] move scratchpad"#),
+            vec![Effect::MovesToScratchpad(Subject::NoWindow {
+                criteria: text(r#"[class="Spotify"]"#)
+            })]
+        );
+        assert_eq!(
+            scene.explain("exec --no-startup-id pavucontrol"),
+            vec![Effect::Starts(text("pavucontrol"))]
+        );
+        assert_eq!(
+            scene.explain("focus left"),
+            vec![Effect::MovesFocus(text("left"))]
+        );
+        assert_eq!(
+            Scene::default().explain("floating toggle"),
+            vec![Effect::Changes(
+                text("floating toggle"),
+                Subject::FocusedWindow
+            )]
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Entries for config lines other than annotated bindings,
+//! enabled with `providers` in the preferences
+
+use crate::i3_command::{self, TokenKind};
+use crate::i3_config::{parse_variables, resolve_variable, split_word, ConfigEntry};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Provider {
+    /// `assign` and `for_window` rules
+    WindowRules,
+    /// `exec` and `exec_always` lines, running them starts the program again
+    Autostart,
+}
+
+impl Provider {
+    pub fn group(self) -> &'static str {
+        match self {
+            Provider::WindowRules => "Window rules",
+            Provider::Autostart => "Autostart",
+        }
+    }
+
+    /// Entries for every line of the config this provider understands
+    pub fn entries(self, text: &str) -> Vec<ConfigEntry> {
+        let lines: Vec<&str> = text.lines().collect();
+        let variables = parse_variables(&lines);
+        let resolve = |text: &str| {
+            text.split(' ')
+                .map(|word| resolve_variable(word, &variables))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let (description, command) = match self {
+                    Provider::WindowRules => (window_rule(line)?, None),
+                    Provider::Autostart => autostart(line)?,
+                };
+                let mut entry = ConfigEntry::new(
+                    self.group().to_owned(),
+                    resolve(&description),
+                    String::new(),
+                )
+                .with_line(index + 1)
+                .with_provider(self);
+                if let Some(command) = command {
+                    entry = entry.with_command(resolve(&command));
+                }
+                Some(entry)
+            })
+            .collect()
+    }
+}
+
+/// Describes `exec_always --no-startup-id nm-applet` as `nm-applet`.
+/// The command always uses `exec` since `exec_always` can't be run through IPC
+fn autostart(line: &str) -> Option<(String, Option<String>)> {
+    let (keyword, mut rest) = split_word(line);
+    if !matches!(keyword, "exec" | "exec_always") {
+        return None;
+    }
+    let mut flags = vec![];
+    while rest.starts_with("--") {
+        let (flag, remaining) = split_word(rest);
+        flags.push(flag);
+        rest = remaining;
+    }
+    if rest.is_empty() {
+        return None;
+    }
+    let program = rest.trim_matches('"').to_owned();
+    let command = std::iter::once("exec")
+        .chain(flags)
+        .chain(std::iter::once(rest))
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some((program, Some(command)))
+}
+
+/// Describes `assign [class="Spotify"] 10` as `class="Spotify" → workspace 10`
+/// and `for_window [class="mpv"] floating enable` as `class="mpv" → floating enable`
+fn window_rule(line: &str) -> Option<String> {
+    let (keyword, rest) = split_word(line);
+    if !matches!(keyword, "assign" | "for_window") {
+        return None;
+    }
+    let criteria = i3_command::tokenize(rest)
+        .into_iter()
+        .next()
+        .filter(|token| token.kind == TokenKind::Criteria)?
+        .text;
+    let target = rest[criteria.len()..].trim();
+    let target = target.strip_prefix('→').unwrap_or(target).trim();
+    if target.is_empty() {
+        return None;
+    }
+    // `assign` takes a workspace name or number unless the target is spelled out
+    let target =
+        if keyword == "for_window" || matches!(split_word(target).0, "workspace" | "output") {
+            target.to_owned()
+        } else {
+            format!("workspace {}", target)
+        };
+    let criteria = criteria
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim();
+    Some(format!("{} → {}", criteria, target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_rule_entries() {
+        let config = r#"set $music 10: music
+assign [class="Spotify"] $music
+# assign [class="Commented"] 2
+assign [class="^Firefox$" window_role="browser"] → number 2
+for_window [class="mpv"] floating enable, move position center
+assign [class="Broken"]
+bindsym $mod+m exec spotify"#;
+        let entries = Provider::WindowRules.entries(config);
+        let descriptions: Vec<&str> = entries.iter().map(|entry| entry.description()).collect();
+        assert_eq!(
+            descriptions,
+            vec![
+                r#"class="Spotify" → workspace 10: music"#,
+                r#"class="^Firefox$" window_role="browser" → workspace number 2"#,
+                r#"class="mpv" → floating enable, move position center"#,
+            ]
+        );
+        assert_eq!(entries[0].line(), 2);
+        assert_eq!(entries[0].group(), "Window rules");
+        assert_eq!(entries[0].provider(), Some(Provider::WindowRules));
+        assert_eq!(entries[0].command(), None);
+    }
+
+    #[test]
+    fn autostart_entries() {
+        let config = r#"set $terminal alacritty
+exec --no-startup-id nm-applet
+exec_always "feh --bg-fill ~/wallpaper.png"
+exec $terminal
+exec
+bindsym $mod+Return exec $terminal"#;
+        let entries = Provider::Autostart.entries(config);
+        let described: Vec<(&str, Option<&str>)> = entries
+            .iter()
+            .map(|entry| (entry.description(), entry.command()))
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                ("nm-applet", Some("exec --no-startup-id nm-applet")),
+                (
+                    "feh --bg-fill ~/wallpaper.png",
+                    Some(r#"exec "feh --bg-fill ~/wallpaper.png""#)
+                ),
+                ("alacritty", Some("exec alacritty")),
+            ]
+        );
+        assert_eq!(entries[0].group(), "Autostart");
+        assert_eq!(entries[2].line(), 4);
+    }
+}
@@ -0,0 +1,231 @@
+//! Search over the bindings of an i3 config for tools embedding it without the window.
+//! `SearcherBuilder`, `Searcher` and the types they return only change in breaking releases
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), i3_conf_searcher::i3_config::I3ConfigError> {
+//! use i3_conf_searcher::i3_config::Modifiers;
+//! use i3_conf_searcher::SearcherBuilder;
+//!
+//! let mut searcher = SearcherBuilder::new().strict_modifiers(true).build().await?;
+//! let entry = searcher.best_match("terminal")?.clone();
+//! searcher.execute(&entry).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::executor;
+use crate::i3_config::{
+    ConfigEntry, ConfigMetadata, EntryOrder, I3ConfigError, Modifiers, SearchText,
+};
+use crate::loader::{self, SourceOptions};
+use crate::providers::Provider;
+use std::path::PathBuf;
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+type ExecuteCallback = Box<dyn Fn(&ConfigEntry) -> Result<()> + Send + Sync>;
+
+/// Configures where the config comes from and how it's searched
+#[derive(Default)]
+pub struct SearcherBuilder {
+    sources: SourceOptions,
+    strict_modifiers: bool,
+    order: EntryOrder,
+    search_text: SearchText,
+    mode: Option<String>,
+    stay_in_mode: bool,
+    executor: Option<ExecuteCallback>,
+}
+
+impl std::fmt::Debug for SearcherBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SearcherBuilder")
+            .field("sources", &self.sources)
+            .field("strict_modifiers", &self.strict_modifiers)
+            .field("order", &self.order)
+            .field("search_text", &self.search_text)
+            .field("mode", &self.mode)
+            .field("stay_in_mode", &self.stay_in_mode)
+            .field("executor", &self.executor.is_some())
+            .finish()
+    }
+}
+
+impl SearcherBuilder {
+    /// Loads the config from i3, falling back to the config file i3 reads
+    pub fn new() -> Self {
+        SearcherBuilder::default()
+    }
+
+    /// Sources tried instead of i3, see `SourceOptions`
+    pub fn sources(mut self, sources: SourceOptions) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    pub fn config_file(mut self, path: PathBuf) -> Self {
+        self.sources.config = Some(path);
+        self
+    }
+
+    /// Only match bindings using exactly the held modifiers
+    pub fn strict_modifiers(mut self, strict: bool) -> Self {
+        self.strict_modifiers = strict;
+        self
+    }
+
+    /// Order of entries that match equally well
+    pub fn order(mut self, order: EntryOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Fields query words are matched against, group and description by default
+    pub fn search_text(mut self, search_text: SearchText) -> Self {
+        self.search_text = search_text;
+        self
+    }
+
+    /// Search config lines besides the annotated bindings
+    pub fn providers(mut self, providers: Vec<Provider>) -> Self {
+        self.sources.providers = providers;
+        self
+    }
+
+    /// Only search the bindings of an i3 mode
+    pub fn mode(mut self, mode: String) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Stay in the mode of a run binding instead of returning to the default mode
+    pub fn stay_in_mode(mut self, stay: bool) -> Self {
+        self.stay_in_mode = stay;
+        self
+    }
+
+    /// Called by `Searcher::execute` instead of running the command through i3 IPC
+    pub fn executor<F>(mut self, executor: F) -> Self
+    where
+        F: Fn(&ConfigEntry) -> Result<()> + Send + Sync + 'static,
+    {
+        self.executor = Some(Box::new(executor));
+        self
+    }
+
+    /// Loads the config from the configured sources
+    pub async fn build(self) -> Result<Searcher> {
+        let config = loader::load(self.sources.clone()).await?;
+        Ok(self.with_config(config))
+    }
+
+    /// Parses `text` instead of loading it, sources are ignored
+    pub fn build_from_text(self, text: &str) -> Result<Searcher> {
+        let mut config = ConfigMetadata::parse(text)?;
+        config.add_provider_entries(&self.sources.providers);
+        Ok(self.with_config(config))
+    }
+
+    fn with_config(self, mut config: ConfigMetadata) -> Searcher {
+        config.set_strict_modifiers(self.strict_modifiers);
+        config.set_order(self.order);
+        config.set_search_text(self.search_text);
+        if let Some(mode) = &self.mode {
+            config.retain_mode(mode);
+        }
+        Searcher {
+            config,
+            stay_in_mode: self.stay_in_mode,
+            executor: self.executor,
+        }
+    }
+}
+
+/// Loaded config that can be searched and whose bindings can be run
+pub struct Searcher {
+    config: ConfigMetadata,
+    stay_in_mode: bool,
+    executor: Option<ExecuteCallback>,
+}
+
+impl std::fmt::Debug for Searcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Searcher")
+            .field("config", &self.config)
+            .field("stay_in_mode", &self.stay_in_mode)
+            .field("executor", &self.executor.is_some())
+            .finish()
+    }
+}
+
+impl Searcher {
+    /// Entries fuzzy matching `query` that use the held `modifiers`, best first.
+    /// Matched characters are available through `ConfigEntry::matched_description`
+    pub fn search(&mut self, query: &str, modifiers: &Modifiers) -> Vec<&ConfigEntry> {
+        self.config.filter(query, modifiers)
+    }
+
+    /// Best match for `query`, fails if several entries match equally well
+    pub fn best_match(&mut self, query: &str) -> Result<&ConfigEntry> {
+        self.config.best_match(query, false)
+    }
+
+    /// Groups with the number of entries in them
+    pub fn groups(&self) -> Vec<(&str, usize)> {
+        self.config.groups()
+    }
+
+    pub fn config(&self) -> &ConfigMetadata {
+        &self.config
+    }
+
+    /// Runs the binding with the executor given to the builder, or through i3 IPC
+    pub async fn execute(&self, entry: &ConfigEntry) -> Result<()> {
+        match &self.executor {
+            Some(executor) => executor(entry),
+            None => executor::execute(entry, self.stay_in_mode).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn search_and_execute_with_callback() {
+        let executed = Arc::new(Mutex::new(vec![]));
+        let recorder = executed.clone();
+        let mut searcher = SearcherBuilder::new()
+            .order(EntryOrder::File)
+            .executor(move |entry| {
+                recorder
+                    .lock()
+                    .unwrap()
+                    .push(entry.command().unwrap_or_default().to_owned());
+                Ok(())
+            })
+            .build_from_text(
+                "## Launch // Terminal // <> Enter ##
+                bindsym $mod+Return exec alacritty
+                ## Launch // Browser // <> b ##
+                bindsym $mod+b exec firefox",
+            )
+            .unwrap();
+        let descriptions: Vec<&str> = searcher
+            .search("", &Modifiers::default())
+            .iter()
+            .map(|entry| entry.description())
+            .collect();
+        assert_eq!(descriptions, vec!["Terminal", "Browser"]);
+        assert_eq!(searcher.groups(), vec![("Launch", 2)]);
+
+        let entry = searcher.best_match("browser").unwrap().clone();
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(searcher.execute(&entry))
+            .unwrap();
+        assert_eq!(*executed.lock().unwrap(), vec!["exec firefox"]);
+    }
+}
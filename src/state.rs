@@ -0,0 +1,106 @@
+//! Everything the searcher remembers between runs, bundled into one JSON document
+//! so it can move with the dotfiles to another machine
+
+use crate::geometry::WindowGeometries;
+use crate::hidden::HiddenEntries;
+use crate::i3_config::I3ConfigError;
+use crate::usage::UsageStats;
+use serde::{Deserialize, Serialize};
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+/// Incremented when the document changes incompatibly
+const STATE_VERSION: u32 = 1;
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AppState {
+    pub version: u32,
+    #[serde(default)]
+    pub usage: UsageStats,
+    #[serde(default)]
+    pub geometry: WindowGeometries,
+    #[serde(default)]
+    pub hidden: HiddenEntries,
+}
+
+impl AppState {
+    /// Collects the state stored on this machine
+    pub fn load() -> Result<AppState> {
+        let usage = match UsageStats::path() {
+            Some(path) => UsageStats::load(&path)?,
+            None => UsageStats::default(),
+        };
+        let geometry = match WindowGeometries::path() {
+            Some(path) => WindowGeometries::load(&path)?,
+            None => WindowGeometries::default(),
+        };
+        let hidden = match HiddenEntries::path() {
+            Some(path) => HiddenEntries::load(&path)?,
+            None => HiddenEntries::default(),
+        };
+        Ok(AppState {
+            version: STATE_VERSION,
+            usage,
+            geometry,
+            hidden,
+        })
+    }
+
+    /// Replaces the state stored on this machine
+    pub fn save(&self) -> Result<()> {
+        self.usage.save(&UsageStats::required_path()?)?;
+        self.geometry.save(&WindowGeometries::required_path()?)?;
+        self.hidden.save(&HiddenEntries::required_path()?)
+    }
+
+    pub fn from_json(text: &str) -> Result<AppState> {
+        let state: AppState = serde_json::from_str(text)
+            .map_err(|error| I3ConfigError::InvalidState(error.to_string()))?;
+        if state.version != STATE_VERSION {
+            return Err(I3ConfigError::InvalidState(format!(
+                "unsupported version {}",
+                state.version
+            )));
+        }
+        Ok(state)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("state is always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i3_config::ConfigEntry;
+    use std::time::Duration;
+
+    #[test]
+    fn json_round_trip() {
+        let mut state = AppState {
+            version: STATE_VERSION,
+            usage: UsageStats::default(),
+            geometry: WindowGeometries::default(),
+            hidden: HiddenEntries::default(),
+        };
+        let entry = ConfigEntry::new(
+            String::from("Launch"),
+            String::from("Terminal"),
+            String::from("Super+Enter"),
+        );
+        state.usage.record(&entry, Duration::from_millis(250));
+        state.hidden.hide(&entry);
+        assert_eq!(AppState::from_json(&state.to_json()).unwrap(), state);
+
+        assert!(matches!(
+            AppState::from_json(r#"{ "version": 2 }"#),
+            Err(I3ConfigError::InvalidState(_))
+        ));
+        assert!(matches!(
+            AppState::from_json(r#"{ "version": 1, "favorites": [] }"#),
+            Err(I3ConfigError::InvalidState(_))
+        ));
+    }
+}
@@ -1,18 +1,179 @@
-use iced::{button, container, scrollable, text_input};
+use i3_conf_searcher::i3_config::content_hash;
+use i3_conf_searcher::preferences::Density;
+use iced::{button, container, scrollable, text_input, Background, Color};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Theme {
+    #[default]
     Light,
     Dark,
+    /// White on black with yellow focus borders
+    HighContrast,
 }
 
 impl Theme {
-    pub const ALL: [Theme; 2] = [Theme::Light, Theme::Dark];
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::HighContrast];
+
+    fn colors(self) -> (Color, Color) {
+        match self {
+            Theme::Light => (Color::WHITE, Color::BLACK),
+            Theme::Dark => (dark::BACKGROUND, Color::WHITE),
+            Theme::HighContrast => (Color::BLACK, Color::WHITE),
+        }
+    }
+
+    /// Color marking bindings of an i3 mode, the same mode always gets the same color
+    pub fn mode_color(self, mode: &str) -> Color {
+        let palette: &[Color] = match self {
+            Theme::Light => &light::MODES,
+            Theme::Dark => &dark::MODES,
+            Theme::HighContrast => &high_contrast::MODES,
+        };
+        palette[(content_hash(mode) % palette.len() as u64) as usize]
+    }
+}
+
+/// Sizes of the search input and entry rows for a [`Density`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metrics {
+    pub input_size: u16,
+    pub input_padding: u16,
+    /// Group, description and keys of a row
+    pub text_size: u16,
+    /// Badges and hints next to them
+    pub small_text_size: u16,
+    /// Key completing the binding from the held modifiers
+    pub hint_size: u16,
+    pub row_padding: u16,
+    pub list_padding: u16,
+    /// Estimated height of a row for `--auto-height`
+    pub row_height: u32,
+}
+
+impl Metrics {
+    pub fn of(density: Density) -> Metrics {
+        match density {
+            Density::Compact => Metrics {
+                input_size: 24,
+                input_padding: 6,
+                text_size: 16,
+                small_text_size: 13,
+                hint_size: 20,
+                row_padding: 4,
+                list_padding: 10,
+                row_height: 26,
+            },
+            Density::Comfortable => Metrics {
+                input_size: 30,
+                input_padding: 10,
+                text_size: 20,
+                small_text_size: 16,
+                hint_size: 26,
+                row_padding: 10,
+                list_padding: 20,
+                row_height: 46,
+            },
+            Density::Spacious => Metrics {
+                input_size: 34,
+                input_padding: 14,
+                text_size: 24,
+                small_text_size: 18,
+                hint_size: 30,
+                row_padding: 16,
+                list_padding: 24,
+                row_height: 62,
+            },
+        }
+    }
+
+    /// MesloLGS is monospaced, every character is about this wide
+    pub fn char_width(self) -> f32 {
+        f32::from(self.text_size) * 0.6
+    }
+}
+
+/// Background of the whole window, see `--opacity`
+#[derive(Debug, Clone, Copy)]
+pub struct Window {
+    pub theme: Theme,
+    pub opacity: f32,
+}
+
+impl container::StyleSheet for Window {
+    fn style(&self) -> container::Style {
+        let (background, text) = self.theme.colors();
+        container::Style {
+            background: Some(Background::Color(Color {
+                a: self.opacity,
+                ..background
+            })),
+            text_color: Some(text),
+            ..container::Style::default()
+        }
+    }
 }
 
-impl Default for Theme {
-    fn default() -> Theme {
-        Theme::Light
+/// Entry in the list, `highlight` is the opacity of the selection background
+/// and `fade` the opacity of the text
+#[derive(Debug, Clone, Copy)]
+pub struct Row {
+    pub theme: Theme,
+    pub highlight: f32,
+    pub fade: f32,
+}
+
+impl container::StyleSheet for Row {
+    fn style(&self) -> container::Style {
+        let selection: Box<dyn container::StyleSheet> = Selection(self.theme).into();
+        let style = selection.style();
+        let (_, text) = self.theme.colors();
+        container::Style {
+            background: style.background.filter(|_| self.highlight > 0.0).map(
+                |Background::Color(color)| {
+                    Background::Color(Color {
+                        a: color.a * self.highlight,
+                        ..color
+                    })
+                },
+            ),
+            border_color: Color {
+                a: style.border_color.a * self.highlight,
+                ..style.border_color
+            },
+            text_color: Some(Color {
+                a: self.fade,
+                ..text
+            }),
+            ..style
+        }
+    }
+}
+
+/// Bar in front of entries of a mode, see [`Theme::mode_color`]
+#[derive(Debug, Clone, Copy)]
+pub struct ModeMarker(pub Color);
+
+impl container::StyleSheet for ModeMarker {
+    fn style(&self) -> container::Style {
+        container::Style {
+            background: Some(Background::Color(self.0)),
+            border_radius: 2.0,
+            ..container::Style::default()
+        }
+    }
+}
+
+/// Background of the selected entry
+#[derive(Debug, Clone, Copy)]
+pub struct Selection(pub Theme);
+
+impl From<Selection> for Box<dyn container::StyleSheet> {
+    fn from(selection: Selection) -> Self {
+        match selection.0 {
+            Theme::Light => light::Selection.into(),
+            Theme::Dark => dark::Selection.into(),
+            Theme::HighContrast => high_contrast::Selection.into(),
+        }
     }
 }
 
@@ -21,6 +182,7 @@ impl From<Theme> for Box<dyn container::StyleSheet> {
         match theme {
             Theme::Light => Default::default(),
             Theme::Dark => dark::Container.into(),
+            Theme::HighContrast => high_contrast::Container.into(),
         }
     }
 }
@@ -30,6 +192,7 @@ impl From<Theme> for Box<dyn text_input::StyleSheet> {
         match theme {
             Theme::Light => Default::default(),
             Theme::Dark => dark::TextInput.into(),
+            Theme::HighContrast => high_contrast::TextInput.into(),
         }
     }
 }
@@ -39,6 +202,7 @@ impl From<Theme> for Box<dyn button::StyleSheet> {
         match theme {
             Theme::Light => light::Button.into(),
             Theme::Dark => dark::Button.into(),
+            Theme::HighContrast => high_contrast::Button.into(),
         }
     }
 }
@@ -48,12 +212,33 @@ impl From<Theme> for Box<dyn scrollable::StyleSheet> {
         match theme {
             Theme::Light => Default::default(),
             Theme::Dark => dark::Scrollable.into(),
+            Theme::HighContrast => high_contrast::Scrollable.into(),
         }
     }
 }
 
 mod light {
-    use iced::{button, Background, Color, Vector};
+    use iced::{button, container, Background, Color, Vector};
+
+    pub const MODES: [Color; 5] = [
+        Color::from_rgb(0.85, 0.37, 0.01),
+        Color::from_rgb(0.11, 0.62, 0.47),
+        Color::from_rgb(0.46, 0.44, 0.70),
+        Color::from_rgb(0.91, 0.16, 0.54),
+        Color::from_rgb(0.40, 0.65, 0.12),
+    ];
+
+    pub struct Selection;
+
+    impl container::StyleSheet for Selection {
+        fn style(&self) -> container::Style {
+            container::Style {
+                background: Some(Background::Color(Color::from_rgb8(0xDD, 0xE6, 0xF5))),
+                border_radius: 4.0,
+                ..container::Style::default()
+            }
+        }
+    }
 
     pub struct Button;
 
@@ -87,6 +272,20 @@ mod dark {
         0x4B as f32 / 255.0,
     );
 
+    pub const BACKGROUND: Color = Color::from_rgb(
+        0x36 as f32 / 255.0,
+        0x39 as f32 / 255.0,
+        0x3F as f32 / 255.0,
+    );
+
+    pub const MODES: [Color; 5] = [
+        Color::from_rgb(0.99, 0.55, 0.38),
+        Color::from_rgb(0.40, 0.76, 0.65),
+        Color::from_rgb(0.55, 0.63, 0.80),
+        Color::from_rgb(0.91, 0.54, 0.76),
+        Color::from_rgb(0.65, 0.85, 0.33),
+    ];
+
     const ACCENT: Color = Color::from_rgb(
         0x6F as f32 / 255.0,
         0xFF as f32 / 255.0,
@@ -110,13 +309,25 @@ mod dark {
     impl container::StyleSheet for Container {
         fn style(&self) -> container::Style {
             container::Style {
-                background: Some(Background::Color(Color::from_rgb8(0x36, 0x39, 0x3F))),
+                background: Some(Background::Color(BACKGROUND)),
                 text_color: Some(Color::WHITE),
                 ..container::Style::default()
             }
         }
     }
 
+    pub struct Selection;
+
+    impl container::StyleSheet for Selection {
+        fn style(&self) -> container::Style {
+            container::Style {
+                background: Some(Background::Color(SURFACE)),
+                border_radius: 4.0,
+                ..container::Style::default()
+            }
+        }
+    }
+
     pub struct TextInput;
 
     impl text_input::StyleSheet for TextInput {
@@ -231,3 +442,127 @@ mod dark {
         }
     }
 }
+
+mod high_contrast {
+    use iced::{button, container, scrollable, text_input, Background, Color};
+
+    const FOCUS: Color = Color::from_rgb(1.0, 1.0, 0.0);
+
+    pub const MODES: [Color; 4] = [
+        Color::from_rgb(0.0, 1.0, 1.0),
+        Color::from_rgb(1.0, 0.0, 1.0),
+        Color::from_rgb(0.0, 1.0, 0.0),
+        Color::from_rgb(1.0, 0.5, 0.0),
+    ];
+
+    pub struct Container;
+
+    impl container::StyleSheet for Container {
+        fn style(&self) -> container::Style {
+            container::Style {
+                background: Some(Background::Color(Color::BLACK)),
+                text_color: Some(Color::WHITE),
+                ..container::Style::default()
+            }
+        }
+    }
+
+    /// A border instead of a background so colored text stays readable
+    pub struct Selection;
+
+    impl container::StyleSheet for Selection {
+        fn style(&self) -> container::Style {
+            container::Style {
+                border_radius: 4.0,
+                border_width: 3.0,
+                border_color: FOCUS,
+                ..container::Style::default()
+            }
+        }
+    }
+
+    pub struct TextInput;
+
+    impl text_input::StyleSheet for TextInput {
+        fn active(&self) -> text_input::Style {
+            text_input::Style {
+                background: Background::Color(Color::BLACK),
+                border_radius: 2.0,
+                border_width: 2.0,
+                border_color: Color::WHITE,
+            }
+        }
+
+        fn focused(&self) -> text_input::Style {
+            text_input::Style {
+                border_color: FOCUS,
+                ..self.active()
+            }
+        }
+
+        fn placeholder_color(&self) -> Color {
+            Color::from_rgb(0.8, 0.8, 0.8)
+        }
+
+        fn value_color(&self) -> Color {
+            Color::WHITE
+        }
+
+        fn selection_color(&self) -> Color {
+            Color::from_rgb(0.0, 0.3, 0.8)
+        }
+    }
+
+    pub struct Button;
+
+    impl button::StyleSheet for Button {
+        fn active(&self) -> button::Style {
+            button::Style {
+                background: Some(Background::Color(Color::BLACK)),
+                border_radius: 3.0,
+                border_width: 2.0,
+                border_color: Color::WHITE,
+                text_color: Color::WHITE,
+                ..button::Style::default()
+            }
+        }
+
+        fn hovered(&self) -> button::Style {
+            button::Style {
+                border_color: FOCUS,
+                ..self.active()
+            }
+        }
+    }
+
+    pub struct Scrollable;
+
+    impl scrollable::StyleSheet for Scrollable {
+        fn active(&self) -> scrollable::Scrollbar {
+            scrollable::Scrollbar {
+                background: Some(Background::Color(Color::BLACK)),
+                border_radius: 2.0,
+                border_width: 1.0,
+                border_color: Color::WHITE,
+                scroller: scrollable::Scroller {
+                    color: Color::WHITE,
+                    border_radius: 2.0,
+                    border_width: 0.0,
+                    border_color: Color::TRANSPARENT,
+                },
+            }
+        }
+
+        fn hovered(&self) -> scrollable::Scrollbar {
+            let active = self.active();
+
+            scrollable::Scrollbar {
+                scroller: scrollable::Scroller {
+                    color: FOCUS,
+                    ..active.scroller
+                },
+                ..active
+            }
+        }
+    }
+}
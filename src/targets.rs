@@ -0,0 +1,184 @@
+//! Workspaces and outputs referenced by bound commands, checked against the running i3
+
+use crate::i3_command;
+use crate::i3_config::I3ConfigError;
+use regex::Regex;
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+use tokio_i3ipc::I3;
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+/// Workspace or output a command switches to or moves something to
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandTarget {
+    Workspace(String),
+    WorkspaceNumber(usize),
+    Output(String),
+}
+
+impl std::fmt::Display for CommandTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandTarget::Workspace(name) => write!(f, "workspace {}", name),
+            CommandTarget::WorkspaceNumber(number) => write!(f, "workspace number {}", number),
+            CommandTarget::Output(name) => write!(f, "output {}", name),
+        }
+    }
+}
+
+/// Targets named in a command, relative ones like `workspace next` or `output left` are skipped
+pub fn command_targets(command: &str) -> Vec<CommandTarget> {
+    let output_re = Regex::new(
+        r"^(?:move\s+(?:(?:window|container|workspace)\s+)?(?:to\s+)?|focus\s+)output\s+(?P<name>.+)$",
+    )
+    .unwrap();
+    let workspace_re = Regex::new(
+        r"^(?:move\s+(?:(?:window|container)\s+)?(?:to\s+)?)?workspace\s+(?:--no-auto-back-and-forth\s+)?(?P<number>number\s+)?(?P<name>.+)$",
+    )
+    .unwrap();
+    let criteria_re = Regex::new(r"^\[[^\]]*\]\s*").unwrap();
+
+    let mut targets = vec![];
+    for part in i3_command::actions(command) {
+        let part = criteria_re.replace(part, "");
+        if let Some(captures) = output_re.captures(&part) {
+            let name = unquote(&captures["name"]);
+            if !OUTPUT_DIRECTIONS.contains(&name) {
+                targets.push(CommandTarget::Output(name.to_owned()));
+            }
+        } else if let Some(captures) = workspace_re.captures(&part) {
+            let name = unquote(&captures["name"]);
+            if captures.name("number").is_some() {
+                let digits: String = name.chars().take_while(char::is_ascii_digit).collect();
+                if let Ok(number) = digits.parse() {
+                    targets.push(CommandTarget::WorkspaceNumber(number));
+                }
+            } else if !WORKSPACE_DIRECTIONS.contains(&name) {
+                targets.push(CommandTarget::Workspace(name.to_owned()));
+            }
+        }
+    }
+    targets
+}
+
+const OUTPUT_DIRECTIONS: [&str; 7] = ["left", "right", "up", "down", "primary", "current", "next"];
+
+const WORKSPACE_DIRECTIONS: [&str; 5] = [
+    "next",
+    "prev",
+    "next_on_output",
+    "prev_on_output",
+    "back_and_forth",
+];
+
+fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text)
+}
+
+/// Workspaces and connected outputs of the running i3
+#[derive(Debug, Clone, Default)]
+pub struct LiveTargets {
+    workspaces: Vec<(usize, String)>,
+    outputs: Vec<String>,
+}
+
+impl LiveTargets {
+    pub fn exists(&self, target: &CommandTarget) -> bool {
+        match target {
+            CommandTarget::Workspace(name) => self.workspaces.iter().any(|(_, open)| open == name),
+            CommandTarget::WorkspaceNumber(number) => {
+                self.workspaces.iter().any(|(open, _)| open == number)
+            }
+            CommandTarget::Output(name) => self.outputs.contains(name),
+        }
+    }
+
+    /// Outputs the command points to that aren't connected
+    pub fn missing_outputs(&self, command: &str) -> Vec<CommandTarget> {
+        command_targets(command)
+            .into_iter()
+            .filter(|target| matches!(target, CommandTarget::Output(_)) && !self.exists(target))
+            .collect()
+    }
+}
+
+/// Asks i3 for open workspaces and active outputs
+#[cfg(all(target_family = "unix", feature = "ipc"))]
+pub async fn query() -> Result<LiveTargets> {
+    let mut i3 = I3::connect().await.map_err(I3ConfigError::ipc)?;
+    let workspaces = i3.get_workspaces().await.map_err(I3ConfigError::ipc)?;
+    let outputs = i3.get_outputs().await.map_err(I3ConfigError::ipc)?;
+    Ok(LiveTargets {
+        workspaces: workspaces
+            .into_iter()
+            .map(|workspace| (workspace.num, workspace.name))
+            .collect(),
+        outputs: outputs
+            .into_iter()
+            .filter(|output| output.active)
+            .map(|output| output.name)
+            .collect(),
+    })
+}
+
+#[cfg(not(all(target_family = "unix", feature = "ipc")))]
+pub async fn query() -> Result<LiveTargets> {
+    Err(I3ConfigError::ipc_unavailable())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_of_commands() {
+        assert_eq!(
+            command_targets("workspace number 3: www"),
+            vec![CommandTarget::WorkspaceNumber(3)]
+        );
+        assert_eq!(
+            command_targets(r#"move container to workspace "chat"; workspace "chat""#),
+            vec![
+                CommandTarget::Workspace(String::from("chat")),
+                CommandTarget::Workspace(String::from("chat"))
+            ]
+        );
+        assert_eq!(
+            command_targets("move workspace to output HDMI-1"),
+            vec![CommandTarget::Output(String::from("HDMI-1"))]
+        );
+        assert_eq!(
+            command_targets("[class=\"Firefox\"] focus output eDP-1"),
+            vec![CommandTarget::Output(String::from("eDP-1"))]
+        );
+        assert!(command_targets("workspace next").is_empty());
+        assert!(command_targets("move workspace to output left").is_empty());
+        assert!(command_targets("rename workspace to 1").is_empty());
+        assert!(command_targets("exec firefox").is_empty());
+        assert_eq!(
+            command_targets(r#"workspace "a;b""#),
+            vec![CommandTarget::Workspace(String::from("a;b"))]
+        );
+    }
+
+    #[test]
+    fn missing_outputs() {
+        let live = LiveTargets {
+            workspaces: vec![(1, String::from("1: web"))],
+            outputs: vec![String::from("eDP-1")],
+        };
+        assert!(live.exists(&CommandTarget::WorkspaceNumber(1)));
+        assert!(live.exists(&CommandTarget::Workspace(String::from("1: web"))));
+        assert!(!live.exists(&CommandTarget::Workspace(String::from("2"))));
+        assert!(live
+            .missing_outputs("move workspace to output eDP-1")
+            .is_empty());
+        assert_eq!(
+            live.missing_outputs("move workspace to output HDMI-1; workspace 2"),
+            vec![CommandTarget::Output(String::from("HDMI-1"))]
+        );
+    }
+}
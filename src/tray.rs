@@ -0,0 +1,99 @@
+//! Status notifier icon so `which-key` can be managed from the system tray
+
+use crate::style::Theme;
+use futures::channel::mpsc;
+use i3_conf_searcher::i3_config::I3ConfigError;
+use ksni::menu::{RadioGroup, RadioItem, StandardItem, SubMenu};
+use ksni::MenuItem;
+
+#[derive(Debug, Clone, Copy)]
+pub enum TrayAction {
+    Show,
+    Reload,
+    /// Theme of searchers opened from now on
+    Theme(Theme),
+    Quit,
+}
+
+struct SearcherTray {
+    theme: Theme,
+    actions: mpsc::UnboundedSender<Result<TrayAction, I3ConfigError>>,
+}
+
+impl SearcherTray {
+    fn send(&self, action: TrayAction) {
+        // which-key is shutting down when nobody listens anymore
+        let _ = self.actions.unbounded_send(Ok(action));
+    }
+}
+
+impl ksni::Tray for SearcherTray {
+    fn id(&self) -> String {
+        env!("CARGO_PKG_NAME").into()
+    }
+
+    fn title(&self) -> String {
+        "i3 config searcher".into()
+    }
+
+    fn icon_name(&self) -> String {
+        "edit-find".into()
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        self.send(TrayAction::Show);
+    }
+
+    fn menu(&self) -> Vec<MenuItem<Self>> {
+        let item = |label: &str, action: TrayAction| -> MenuItem<Self> {
+            StandardItem {
+                label: label.into(),
+                activate: Box::new(move |tray: &mut Self| tray.send(action)),
+                ..Default::default()
+            }
+            .into()
+        };
+        let themes = RadioGroup {
+            selected: Theme::ALL
+                .iter()
+                .position(|theme| *theme == self.theme)
+                .unwrap_or_default(),
+            select: Box::new(|tray: &mut Self, index| {
+                tray.theme = Theme::ALL[index];
+                tray.send(TrayAction::Theme(tray.theme));
+            }),
+            options: ["Light", "Dark", "High contrast"]
+                .iter()
+                .map(|label| RadioItem {
+                    label: (*label).into(),
+                    ..Default::default()
+                })
+                .collect(),
+        };
+        vec![
+            item("Show", TrayAction::Show),
+            item("Reload config", TrayAction::Reload),
+            SubMenu {
+                label: "Switch theme".into(),
+                submenu: vec![themes.into()],
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            item("Quit", TrayAction::Quit),
+        ]
+    }
+}
+
+/// Shows the icon on its own thread, the receiver gets an error if there is no tray to show it in
+pub fn spawn(theme: Theme) -> mpsc::UnboundedReceiver<Result<TrayAction, I3ConfigError>> {
+    let (actions, receiver) = mpsc::unbounded();
+    let errors = actions.clone();
+    let service = ksni::TrayService::new(SearcherTray { theme, actions });
+    std::thread::spawn(move || {
+        if let Err(error) = service.run() {
+            let _ = errors.unbounded_send(Err(I3ConfigError::FailedTray(error.to_string())));
+        }
+    });
+    receiver
+}
@@ -0,0 +1,118 @@
+//! Opt-in record of the bindings run from the searcher, kept locally in
+//! `$XDG_DATA_HOME/i3-conf-searcher/usage.json`
+
+use crate::i3_config::{ConfigEntry, I3ConfigError};
+use crate::{json_file, paths};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+/// How often each entry was run, keyed by group and description so edits elsewhere in the
+/// config don't lose the counts
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct UsageStats {
+    entries: BTreeMap<String, EntryUsage>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EntryUsage {
+    pub runs: u64,
+    /// Summed time from starting to search until the entry was run
+    pub selection_ms: u64,
+}
+
+impl EntryUsage {
+    pub fn average_selection_time(&self) -> Duration {
+        Duration::from_millis(self.selection_ms.checked_div(self.runs).unwrap_or_default())
+    }
+}
+
+impl UsageStats {
+    /// `$XDG_DATA_HOME/i3-conf-searcher/usage.json`
+    pub fn path() -> Option<PathBuf> {
+        paths::data_dir().map(|data| data.join("usage.json"))
+    }
+
+    /// [`UsageStats::path`], an error without a data directory
+    pub fn required_path() -> Result<PathBuf> {
+        json_file::required(UsageStats::path(), "usage stats", usage_error)
+    }
+
+    /// A missing file means nothing was recorded yet
+    pub fn load(path: &Path) -> Result<UsageStats> {
+        json_file::load(path, usage_error)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        json_file::save(self, path, usage_error)
+    }
+
+    pub fn record(&mut self, entry: &ConfigEntry, selection_time: Duration) {
+        let usage = self.entries.entry(usage_key(entry)).or_default();
+        usage.runs += 1;
+        usage.selection_ms += selection_time.as_millis() as u64;
+    }
+
+    /// Usage of the entry, `None` if it was never run
+    pub fn get(&self, entry: &ConfigEntry) -> Option<&EntryUsage> {
+        self.entries.get(&usage_key(entry))
+    }
+
+    /// Loads the stats, records the run and saves them again
+    pub fn record_run(entry: &ConfigEntry, selection_time: Duration) -> Result<()> {
+        json_file::update(
+            &UsageStats::required_path()?,
+            usage_error,
+            |stats: &mut UsageStats| stats.record(entry, selection_time),
+        )
+    }
+}
+
+pub(crate) fn usage_key(entry: &ConfigEntry) -> String {
+    format!("{} // {}", entry.group(), entry.description())
+}
+
+fn usage_error(error: io::Error) -> I3ConfigError {
+    I3ConfigError::FailedUsageStats(Arc::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_reload() {
+        let path = std::env::temp_dir().join(format!(
+            "i3-conf-searcher-test-{}/usage.json",
+            std::process::id()
+        ));
+        let terminal = ConfigEntry::new(
+            String::from("Launch"),
+            String::from("Terminal"),
+            String::from("Super+Enter"),
+        );
+        let browser = ConfigEntry::new(
+            String::from("Launch"),
+            String::from("Browser"),
+            String::from("Super+b"),
+        );
+        assert_eq!(UsageStats::load(&path).unwrap(), UsageStats::default());
+
+        let mut stats = UsageStats::default();
+        stats.record(&terminal, Duration::from_millis(300));
+        stats.record(&terminal, Duration::from_millis(100));
+        stats.save(&path).unwrap();
+
+        let stats = UsageStats::load(&path).unwrap();
+        let usage = stats.get(&terminal).unwrap();
+        assert_eq!(usage.runs, 2);
+        assert_eq!(usage.average_selection_time(), Duration::from_millis(200));
+        assert_eq!(stats.get(&browser), None);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
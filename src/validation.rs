@@ -0,0 +1,80 @@
+//! Checking configs with `i3 -C` before i3 loads them
+
+use crate::i3_config::I3ConfigError;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+type Result<T> = std::result::Result<T, I3ConfigError>;
+
+/// Runs `i3 -C` on the config file, or on the one i3 itself reads when `path` is `None`.
+/// Nothing is checked when i3 isn't installed
+pub fn validate(path: Option<&Path>) -> Result<()> {
+    let mut command = Command::new("i3");
+    command.arg("-C");
+    if let Some(path) = path {
+        command.arg("-c").arg(path);
+    }
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(I3ConfigError::FailedSpawn(Arc::new(error))),
+    };
+    let errors = config_errors(&String::from_utf8_lossy(&output.stdout));
+    match (output.status.success(), errors.is_empty()) {
+        (true, true) => Ok(()),
+        (_, false) => Err(I3ConfigError::InvalidI3Config(errors.join("; "))),
+        (false, true) => {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_owned();
+            Err(I3ConfigError::InvalidI3Config(if stderr.is_empty() {
+                output.status.to_string()
+            } else {
+                stderr
+            }))
+        }
+    }
+}
+
+/// Offending lines from the output of `i3 -C` like `line 12: bindsym $mod+Return exc foot`.
+/// i3 prints the lines around an error too, the offending one is underlined with `^`
+fn config_errors(output: &str) -> Vec<String> {
+    let messages: Vec<&str> = output
+        .lines()
+        .filter_map(|line| line.strip_prefix("ERROR: CONFIG: "))
+        .collect();
+    messages
+        .windows(2)
+        .filter(|pair| {
+            let marker = pair[1].trim();
+            !marker.is_empty() && marker.chars().all(|c| c == '^')
+        })
+        .filter_map(|pair| {
+            let (number, text) = pair[0].strip_prefix("Line")?.split_once(':')?;
+            Some(format!("line {}: {}", number.trim(), text.trim()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_i3_check_output() {
+        let output = "\
+ERROR: CONFIG: Expected one of these tokens: <end>, '#', 'set', 'bindsym'
+ERROR: CONFIG: (in file /home/user/.config/i3/config)
+ERROR: CONFIG: Line  11: # Launch // Terminal // <> Enter ##
+ERROR: CONFIG: Line  12: bindsym $mod+Return exc foot
+ERROR: CONFIG:                               ^^^^^^^^
+ERROR: CONFIG: Line  13:
+ERROR: FYI: You are using i3 version 4.22
+";
+        assert_eq!(
+            config_errors(output),
+            vec!["line 12: bindsym $mod+Return exc foot"]
+        );
+        assert!(config_errors("").is_empty());
+    }
+}
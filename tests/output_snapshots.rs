@@ -0,0 +1,27 @@
+//! Snapshots of what the subcommands print, so formatting changes show up in review.
+//! Run `cargo insta review` after an intended change
+
+use i3_conf_searcher::i3_config::{ConfigMetadata, I3ConfigError};
+use i3_conf_searcher::presenter;
+
+fn fixture() -> ConfigMetadata {
+    ConfigMetadata::parse(include_str!("fixtures/regolith.config")).unwrap()
+}
+
+#[test]
+fn list() {
+    let config = fixture();
+    insta::assert_snapshot!(presenter::entries_table(&config.ordered_entries()));
+}
+
+#[test]
+fn list_groups() {
+    let config = fixture();
+    insta::assert_snapshot!(presenter::groups_table(&config.groups()));
+}
+
+#[test]
+fn error_json() {
+    let error = I3ConfigError::AmbiguousMatch(String::from("Terminal, Browser"));
+    insta::assert_snapshot!(presenter::error_json(&error));
+}
@@ -0,0 +1,71 @@
+//! Invariants of highlighting and filtering that have to hold for any text
+
+use i3_conf_searcher::i3_config::{ConfigEntry, ConfigMetadata, MatchElement, Modifiers};
+use proptest::prelude::*;
+
+/// Text with indices that may point past its end or repeat
+fn text_and_indices() -> impl Strategy<Value = (String, Vec<usize>)> {
+    "\\PC{0,30}".prop_flat_map(|text| {
+        let length = text.chars().count();
+        (Just(text), prop::collection::vec(0..length + 5, 0..10))
+    })
+}
+
+fn highlighted(text: &str, indices: Vec<usize>) -> Vec<MatchElement> {
+    let mut entry = ConfigEntry::new(String::new(), text.to_owned(), String::new());
+    entry.set_description_indices(indices);
+    entry.matched_description()
+}
+
+proptest! {
+    #[test]
+    fn highlighted_parts_reproduce_text((text, indices) in text_and_indices()) {
+        let joined: String = highlighted(&text, indices)
+            .iter()
+            .map(|element| match element {
+                MatchElement::Matched(part) | MatchElement::Unmatched(part) => part.as_str(),
+            })
+            .collect();
+        prop_assert_eq!(joined, text);
+    }
+
+    #[test]
+    fn matched_characters_are_the_indices((text, indices) in text_and_indices()) {
+        let mut position = 0;
+        for element in highlighted(&text, indices.clone()) {
+            let (part, matched) = match &element {
+                MatchElement::Matched(part) => (part, true),
+                MatchElement::Unmatched(part) => (part, false),
+            };
+            for _ in part.chars() {
+                prop_assert_eq!(indices.contains(&position), matched);
+                position += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn empty_query_returns_all_entries_in_stable_order(
+        annotations in prop::collection::vec(("[a-z]{1,10}", "[a-z]{1,10}"), 0..20)
+    ) {
+        let text: String = annotations
+            .iter()
+            .map(|(group, description)| format!("## {} // {} // keys ##\n", group, description))
+            .collect();
+        let mut config = ConfigMetadata::parse(&text).unwrap();
+        let expected: Vec<usize> = config.ordered_entries().into_iter().map(ConfigEntry::line).collect();
+        let first: Vec<usize> = config
+            .filter("", &Modifiers::default())
+            .into_iter()
+            .map(ConfigEntry::line)
+            .collect();
+        let second: Vec<usize> = config
+            .filter("", &Modifiers::default())
+            .into_iter()
+            .map(ConfigEntry::line)
+            .collect();
+        prop_assert_eq!(first.len(), annotations.len());
+        prop_assert_eq!(&first, &expected);
+        prop_assert_eq!(first, second);
+    }
+}
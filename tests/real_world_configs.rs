@@ -0,0 +1,107 @@
+//! Parses configs modeled after ones found in the wild to guard the parser against regressions
+
+use i3_conf_searcher::i3_config::{
+    BindingFlag, BindingKind, ConfigEntry, ConfigMetadata, Modifiers,
+};
+
+fn parse(text: &str) -> ConfigMetadata {
+    let config = ConfigMetadata::parse(text).unwrap();
+    assert!(
+        config.diagnostics().is_empty(),
+        "unexpected diagnostics {:?}",
+        config.diagnostics()
+    );
+    config
+}
+
+fn entry<'a>(config: &'a ConfigMetadata, description: &str) -> &'a ConfigEntry {
+    config
+        .entries()
+        .iter()
+        .find(|entry| entry.description() == description)
+        .unwrap_or_else(|| panic!("no entry {}", description))
+}
+
+fn combos(entry: &ConfigEntry) -> Vec<String> {
+    entry
+        .sequence()
+        .combos()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn regolith() {
+    let mut config = parse(include_str!("fixtures/regolith.config"));
+    assert_eq!(config.entries().len(), 20);
+    assert_eq!(
+        config.groups(),
+        vec![
+            ("Launch", 3),
+            ("Modify", 5),
+            ("Navigate", 4),
+            ("Resize", 4),
+            ("Session", 4)
+        ]
+    );
+
+    // set_from_resource falls back to its default value
+    assert_eq!(
+        combos(entry(&config, "Relative Window")),
+        vec!["Super+Left"]
+    );
+    assert_eq!(combos(entry(&config, "Resize Window")), vec!["Left"]);
+    assert_eq!(
+        combos(entry(&config, "Next Workspace on Output")),
+        vec!["Super+Alt+Right"]
+    );
+    assert_eq!(
+        entry(&config, "Exit App").command(),
+        Some("[con_id=\"__focused__\"] kill")
+    );
+
+    let modes: Vec<&str> = config
+        .entries()
+        .iter()
+        .filter_map(ConfigEntry::mode)
+        .collect();
+    assert_eq!(modes, vec!["Resize Mode"; 3]);
+    assert_eq!(entry(&config, "Enter Resize Mode").mode(), None);
+
+    let meta = Modifiers::new(false, false, false, true);
+    assert_eq!(config.filter("", &meta).len(), 17);
+    let meta_shift = Modifiers::new(true, false, false, true);
+    assert_eq!(config.filter("", &meta_shift).len(), 6);
+}
+
+#[test]
+fn sway() {
+    let config = parse(include_str!("fixtures/sway.config"));
+    assert_eq!(config.entries().len(), 14);
+    assert_eq!(combos(entry(&config, "Focus left")), vec!["Super+h"]);
+    assert_eq!(entry(&config, "Terminal").command(), Some("exec $term"));
+
+    assert_eq!(entry(&config, "Volume up").flags(), &[BindingFlag::Locked]);
+    assert_eq!(
+        entry(&config, "Mute").flags(),
+        &[BindingFlag::Locked, BindingFlag::ToCode]
+    );
+    let mouse = entry(&config, "Kill window under pointer");
+    assert_eq!(mouse.kind(), BindingKind::Mouse);
+    assert_eq!(mouse.flags(), &[BindingFlag::WholeWindow]);
+
+    // Sequences resolve variables in every chord
+    let shrink = entry(&config, "Shrink width");
+    assert_eq!(shrink.mode(), Some("resize"));
+    assert_eq!(combos(shrink), vec!["Super+r", "h"]);
+    assert_eq!(combos(entry(&config, "Grow height")), vec!["Super+r", "j"]);
+    assert_eq!(entry(&config, "Enter resize mode").mode(), None);
+}
+
+#[test]
+fn unannotated_default_config() {
+    let config = parse(include_str!("fixtures/i3-default.config"));
+    assert!(config.entries().is_empty());
+    assert!(config.groups().is_empty());
+}
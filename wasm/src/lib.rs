@@ -0,0 +1,103 @@
+//! wasm-bindgen wrapper so a cheatsheet in the browser matches exactly like the searcher
+
+use i3_conf_searcher::i3_config::{ConfigEntry, ConfigMetadata, Modifiers};
+use wasm_bindgen::prelude::*;
+
+/// Parsed config, constructed from JavaScript with `new Cheatsheet(text)`
+#[wasm_bindgen]
+pub struct Cheatsheet {
+    config: ConfigMetadata,
+}
+
+#[wasm_bindgen]
+impl Cheatsheet {
+    /// Throws the error message if the config can't be parsed
+    #[wasm_bindgen(constructor)]
+    pub fn new(text: &str) -> Result<Cheatsheet, JsValue> {
+        ConfigMetadata::parse(text)
+            .map(|config| Cheatsheet { config })
+            .map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Entries in config order
+    pub fn entries(&self) -> Vec<Entry> {
+        self.config.entries().iter().cloned().map(Entry).collect()
+    }
+
+    /// Entries matching the query and held modifiers, best match first
+    pub fn filter(
+        &mut self,
+        query: &str,
+        shift: bool,
+        control: bool,
+        alt: bool,
+        meta: bool,
+    ) -> Vec<Entry> {
+        let modifiers = Modifiers::new(shift, control, alt, meta);
+        self.config
+            .filter(query, &modifiers)
+            .into_iter()
+            .cloned()
+            .map(Entry)
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+pub struct Entry(ConfigEntry);
+
+#[wasm_bindgen]
+impl Entry {
+    #[wasm_bindgen(getter)]
+    pub fn group(&self) -> String {
+        self.0.group().to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn description(&self) -> String {
+        self.0.description().to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn keys(&self) -> String {
+        self.0.keys().to_owned()
+    }
+
+    /// `undefined` if no binding follows the annotation
+    #[wasm_bindgen(getter)]
+    pub fn command(&self) -> Option<String> {
+        self.0.command().map(str::to_owned)
+    }
+
+    /// `undefined` for the default mode
+    #[wasm_bindgen(getter)]
+    pub fn mode(&self) -> Option<String> {
+        self.0.mode().map(str::to_owned)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn line(&self) -> usize {
+        self.0.line()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_returns_matching_entries() {
+        let mut cheatsheet = Cheatsheet::new(
+            "## Launch // Terminal // Super+Enter ##
+            bindsym $mod+Return exec alacritty
+            ## Launch // Browser // Super+b ##",
+        )
+        .unwrap_or_else(|_| panic!("config didn't parse"));
+        assert_eq!(cheatsheet.entries().len(), 2);
+        let results = cheatsheet.filter("browser", false, false, false, false);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].description(), "Browser");
+        assert_eq!(results[0].command(), None);
+        assert_eq!(results[0].line(), 3);
+    }
+}